@@ -1,10 +1,10 @@
-use akropolisos_runtime::types::Token;
+use akropolisos_runtime::types::{Token, ETHEREUM_CHAIN_ID, NATIVE_TOKEN_ID};
 use akropolisos_runtime::{
     constants::currency::*, AccountId, AuthorityDiscoveryConfig, BabeConfig, Balance,
     BalancesConfig, Block, BridgeConfig, ContractsConfig, CouncilConfig, DemocracyConfig,
-    GenesisConfig, GrandpaConfig, ImOnlineConfig, IndicesConfig, SessionConfig, SessionKeys,
-    Signature, SocietyConfig, StakerStatus, StakingConfig, SudoConfig, SystemConfig,
-    TechnicalCommitteeConfig, TokenConfig, WASM_BINARY,
+    EthLightClientConfig, GenesisConfig, GrandpaConfig, ImOnlineConfig, IndicesConfig,
+    PriceOracleConfig, SessionConfig, SessionKeys, Signature, SocietyConfig, StakerStatus,
+    StakingConfig, SudoConfig, SystemConfig, TechnicalCommitteeConfig, TokenConfig, WASM_BINARY,
 };
 use grandpa_primitives::AuthorityId as GrandpaId;
 use hex_literal::hex;
@@ -14,7 +14,7 @@ use sc_service;
 use serde::{Deserialize, Serialize};
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_consensus_babe::AuthorityId as BabeId;
-use sp_core::{crypto::UncheckedInto, sr25519, Pair, Public};
+use sp_core::{crypto::UncheckedInto, sr25519, Pair, Public, H160};
 use sp_runtime::{
     traits::{IdentifyAccount, Verify},
     Perbill,
@@ -346,18 +346,50 @@ pub fn testnet_genesis(
         }),
         pallet_vesting: Some(Default::default()),
         bridge: Some(BridgeConfig {
-            validator_accounts: bridge_validators,
+            validator_accounts: bridge_validators.clone(),
             validators_count: 3u32,
-            current_limits: vec![
-                100 * 10u128.pow(18),
-                200 * 10u128.pow(18),
-                50 * 10u128.pow(18),
-                400 * 10u128.pow(18),
-                10 * 10u128.pow(18),
-            ],
+            // bridge contract is set with register_chain once deployed
+            chains: vec![(
+                ETHEREUM_CHAIN_ID,
+                H160::zero(),
+                tokens
+                    .iter()
+                    .map(|t| t.id)
+                    .chain(std::iter::once(NATIVE_TOKEN_ID))
+                    .collect(),
+            )],
+            current_limits: tokens
+                .iter()
+                .map(|t| {
+                    let unit = 10u128.pow(t.decimals.into());
+                    (
+                        ETHEREUM_CHAIN_ID,
+                        t.id,
+                        vec![100 * unit, 200 * unit, 50 * unit, 400 * unit, 10 * unit],
+                    )
+                })
+                .chain(std::iter::once((
+                    ETHEREUM_CHAIN_ID,
+                    NATIVE_TOKEN_ID,
+                    vec![
+                        100 * DOLLARS,
+                        200 * DOLLARS,
+                        50 * DOLLARS,
+                        400 * DOLLARS,
+                        10 * DOLLARS,
+                    ],
+                )))
+                .collect(),
+            fees: vec![],
+        }),
+        eth_light_client: Some(EthLightClientConfig {
+            relayers: bridge_validators,
         }),
         dao: None,
         token: Some(TokenConfig { tokens }),
+        price_oracle: Some(PriceOracleConfig {
+            sources: akropolisos_runtime::price_oracle::default_sources(),
+        }),
     }
 }
 
@@ -39,7 +39,7 @@ impl<Number> FactoryState<Number> {
 			system::CheckEra::from(Era::mortal(256, phase)),
 			system::CheckNonce::from(index),
 			system::CheckWeight::new(),
-			pallet_transaction_payment::ChargeTransactionPayment::from(0),
+			akropolisos_runtime::ChargeTransactionPaymentInToken::from(0),
 			Default::default(),
 		)
 	}
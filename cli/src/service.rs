@@ -598,7 +598,7 @@ mod tests {
 				let check_era = system::CheckEra::from(Era::Immortal);
 				let check_nonce = system::CheckNonce::from(index);
 				let check_weight = system::CheckWeight::new();
-				let payment = pallet_transaction_payment::ChargeTransactionPayment::from(0);
+				let payment = akropolisos_runtime::ChargeTransactionPaymentInToken::from(0);
 				let extra = (
 					check_version,
 					check_genesis,
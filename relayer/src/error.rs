@@ -0,0 +1,52 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// node is unreachable, the same request should be retried later
+    Connection(String),
+    /// node refused the request, retrying it won't help
+    Rejected(String),
+    /// malformed response or local failure
+    Other(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Connection(e) => write!(f, "connection error: {}", e),
+            Error::Rejected(e) => write!(f, "rejected: {}", e),
+            Error::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<jsonrpc_core_client::RpcError> for Error {
+    fn from(error: jsonrpc_core_client::RpcError) -> Self {
+        match error {
+            jsonrpc_core_client::RpcError::JsonRpcError(e) => Error::Rejected(e.message),
+            e => Error::Connection(e.to_string()),
+        }
+    }
+}
+
+impl From<codec::Error> for Error {
+    fn from(error: codec::Error) -> Self {
+        Error::Other(format!("can't decode: {}", error.what()))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Other(format!("unexpected response: {}", error))
+    }
+}
+
+impl From<sled::Error> for Error {
+    fn from(error: sled::Error) -> Self {
+        Error::Other(format!("database error: {}", error))
+    }
+}
@@ -0,0 +1,457 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sp_core::{ecdsa, hashing::keccak_256, Bytes, H160, H256, U256};
+
+use crate::error::{Error, Result};
+use crate::rpc::RpcClient;
+
+const RELAY_MESSAGE: &[u8] = b"RelayMessage(bytes32,address,bytes32,uint256)";
+const APPROVED_RELAY_MESSAGE: &[u8] = b"ApprovedRelayMessage(bytes32,address,bytes32,uint256)";
+const WITHDRAW_MESSAGE: &[u8] = b"WithdrawMessage(bytes32)";
+
+const APPROVE_TRANSFER: &[u8] = b"approveTransfer(bytes32,address,bytes32,uint256)";
+const CONFIRM_TRANSFER: &[u8] = b"confirmTransfer(bytes32)";
+const WITHDRAW_TRANSFER: &[u8] = b"withdrawTransfer(bytes32,bytes32,address,uint256)";
+
+/// DAIBridge events the relayer reacts to
+#[derive(Debug, Clone, PartialEq)]
+pub enum EthEvent {
+    /// tokens are locked on Ethereum, mint them on Substrate
+    Relay {
+        message_id: H256,
+        sender: H160,
+        recipient: H256,
+        amount: U256,
+    },
+    /// mint is approved on Ethereum, confirm it
+    Approved { message_id: H256 },
+    /// withdrawal is paid out on Ethereum, confirm the burn on Substrate
+    Withdraw { message_id: H256 },
+}
+
+/// DAIBridge calls sent by the validator
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContractCall {
+    ApproveTransfer {
+        message_id: H256,
+        spender: H160,
+        recipient: H256,
+        amount: U256,
+    },
+    ConfirmTransfer {
+        message_id: H256,
+    },
+    WithdrawTransfer {
+        message_id: H256,
+        sender: H256,
+        recipient: H160,
+        amount: U256,
+    },
+}
+
+impl ContractCall {
+    /// ABI encoded call data
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            ContractCall::ApproveTransfer {
+                message_id,
+                spender,
+                recipient,
+                amount,
+            } => abi_call(
+                APPROVE_TRANSFER,
+                &[
+                    *message_id,
+                    address_word(*spender),
+                    *recipient,
+                    uint_word(*amount),
+                ],
+            ),
+            ContractCall::ConfirmTransfer { message_id } => {
+                abi_call(CONFIRM_TRANSFER, &[*message_id])
+            }
+            ContractCall::WithdrawTransfer {
+                message_id,
+                sender,
+                recipient,
+                amount,
+            } => abi_call(
+                WITHDRAW_TRANSFER,
+                &[
+                    *message_id,
+                    *sender,
+                    address_word(*recipient),
+                    uint_word(*amount),
+                ],
+            ),
+        }
+    }
+}
+
+pub trait EthereumChain {
+    fn block_number(&mut self) -> Result<u64>;
+    /// bridge contract events in the inclusive block range
+    fn events(&mut self, from: u64, to: u64) -> Result<Vec<EthEvent>>;
+    /// hash of the sent transaction
+    fn send(&mut self, call: ContractCall) -> Result<H256>;
+    /// block and success of the mined transaction
+    fn receipt(&mut self, hash: H256) -> Result<Option<(u64, bool)>>;
+}
+
+#[derive(Deserialize)]
+struct Log {
+    topics: Vec<H256>,
+    data: Bytes,
+    #[serde(default)]
+    removed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Receipt {
+    block_number: Option<String>,
+    status: Option<String>,
+}
+
+pub struct EthereumRpc {
+    rpc: RpcClient,
+    contract: H160,
+    key: secp256k1::SecretKey,
+    chain_id: u64,
+    gas_limit: u64,
+}
+
+impl EthereumRpc {
+    pub fn new(
+        rpc: RpcClient,
+        contract: H160,
+        key: secp256k1::SecretKey,
+        chain_id: u64,
+        gas_limit: u64,
+    ) -> Self {
+        EthereumRpc {
+            rpc,
+            contract,
+            key,
+            chain_id,
+            gas_limit,
+        }
+    }
+
+    fn quantity(&mut self, method: &str, params: Vec<Value>) -> Result<U256> {
+        let value: String = self.rpc.call(method, params)?;
+        parse_quantity(&value).ok_or_else(|| Error::Other(format!("{} returned {}", method, value)))
+    }
+}
+
+impl EthereumChain for EthereumRpc {
+    fn block_number(&mut self) -> Result<u64> {
+        Ok(self.quantity("eth_blockNumber", vec![])?.low_u64())
+    }
+
+    fn events(&mut self, from: u64, to: u64) -> Result<Vec<EthEvent>> {
+        let topics = [RELAY_MESSAGE, APPROVED_RELAY_MESSAGE, WITHDRAW_MESSAGE]
+            .iter()
+            .map(|event| H256::from(keccak_256(event)))
+            .collect::<Vec<_>>();
+        let filter = json!({
+            "fromBlock": format!("0x{:x}", from),
+            "toBlock": format!("0x{:x}", to),
+            "address": self.contract,
+            "topics": [topics],
+        });
+        let logs: Vec<Log> = self.rpc.call("eth_getLogs", vec![filter])?;
+        Ok(logs
+            .into_iter()
+            .filter(|log| !log.removed)
+            .filter_map(|log| decode_event(&log.topics, &log.data))
+            .collect())
+    }
+
+    fn send(&mut self, call: ContractCall) -> Result<H256> {
+        let from = address(&self.key);
+        let data = call.encode();
+        // a call that would revert is rejected here instead of burning gas
+        self.rpc.call::<Value>(
+            "eth_call",
+            vec![
+                json!({ "from": from, "to": self.contract, "data": Bytes(data.clone()) }),
+                json!("pending"),
+            ],
+        )?;
+        let nonce = self.quantity(
+            "eth_getTransactionCount",
+            vec![json!(from), json!("pending")],
+        )?;
+        let gas_price = self.quantity("eth_gasPrice", vec![])?;
+        let transaction = Transaction {
+            nonce,
+            gas_price,
+            gas_limit: self.gas_limit.into(),
+            to: self.contract,
+            data,
+        };
+        let raw = transaction.sign(&self.key, self.chain_id);
+        self.rpc
+            .call("eth_sendRawTransaction", vec![json!(Bytes(raw))])
+    }
+
+    fn receipt(&mut self, hash: H256) -> Result<Option<(u64, bool)>> {
+        let receipt: Option<Receipt> = self
+            .rpc
+            .call("eth_getTransactionReceipt", vec![json!(hash)])?;
+        // pending transactions have no block yet
+        let (block, status) = match receipt {
+            Some(Receipt {
+                block_number: Some(block),
+                status,
+            }) => (block, status),
+            _ => return Ok(None),
+        };
+        let block = parse_quantity(&block)
+            .ok_or_else(|| Error::Other(format!("receipt of {:?} has block {}", hash, block)))?;
+        let succeeded = status.and_then(|status| parse_quantity(&status)) == Some(U256::one());
+        Ok(Some((block.low_u64(), succeeded)))
+    }
+}
+
+/// Legacy transaction without value transfer
+pub struct Transaction {
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas_limit: U256,
+    pub to: H160,
+    pub data: Vec<u8>,
+}
+
+impl Transaction {
+    /// RLP encoded transaction signed according to EIP-155
+    pub fn sign(&self, key: &secp256k1::SecretKey, chain_id: u64) -> Vec<u8> {
+        let unsigned = self.rlp(&[
+            rlp_uint(chain_id.into()),
+            rlp_uint(U256::zero()),
+            rlp_uint(U256::zero()),
+        ]);
+        let message = secp256k1::Message::parse(&keccak_256(&unsigned));
+        let (signature, recovery_id) = secp256k1::sign(&message, key);
+        let signature = signature.serialize();
+        let v = u64::from(recovery_id.serialize()) + 35 + chain_id * 2;
+        self.rlp(&[
+            rlp_uint(v.into()),
+            rlp_uint(U256::from_big_endian(&signature[..32])),
+            rlp_uint(U256::from_big_endian(&signature[32..])),
+        ])
+    }
+
+    fn rlp(&self, tail: &[Vec<u8>]) -> Vec<u8> {
+        let mut items = vec![
+            rlp_uint(self.nonce),
+            rlp_uint(self.gas_price),
+            rlp_uint(self.gas_limit),
+            rlp_bytes(self.to.as_bytes()),
+            rlp_uint(U256::zero()),
+            rlp_bytes(&self.data),
+        ];
+        items.extend_from_slice(tail);
+        rlp_list(&items)
+    }
+}
+
+/// Ethereum address of the key
+pub fn address(key: &secp256k1::SecretKey) -> H160 {
+    let public = secp256k1::PublicKey::from_secret_key(key).serialize();
+    H160::from_slice(&keccak_256(&public[1..])[12..])
+}
+
+/// signature of the digest in the format the bridge pallet recovers
+pub fn sign_digest(key: &secp256k1::SecretKey, digest: [u8; 32]) -> ecdsa::Signature {
+    let message = secp256k1::Message::parse(&digest);
+    let (signature, recovery_id) = secp256k1::sign(&message, key);
+    let mut raw = [0u8; 65];
+    raw[..64].copy_from_slice(&signature.serialize());
+    raw[64] = recovery_id.serialize();
+    ecdsa::Signature::from_raw(raw)
+}
+
+fn parse_quantity(value: &str) -> Option<U256> {
+    U256::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+fn decode_event(topics: &[H256], data: &[u8]) -> Option<EthEvent> {
+    let topic = topics.first()?;
+    let word = |index: usize| data.get(index * 32..(index + 1) * 32);
+    if *topic == H256::from(keccak_256(RELAY_MESSAGE)) {
+        Some(EthEvent::Relay {
+            message_id: H256::from_slice(word(0)?),
+            sender: H160::from_slice(&word(1)?[12..]),
+            recipient: H256::from_slice(word(2)?),
+            amount: U256::from_big_endian(word(3)?),
+        })
+    } else if *topic == H256::from(keccak_256(APPROVED_RELAY_MESSAGE)) {
+        Some(EthEvent::Approved {
+            message_id: H256::from_slice(word(0)?),
+        })
+    } else if *topic == H256::from(keccak_256(WITHDRAW_MESSAGE)) {
+        Some(EthEvent::Withdraw {
+            message_id: H256::from_slice(word(0)?),
+        })
+    } else {
+        None
+    }
+}
+
+fn abi_call(signature: &[u8], words: &[H256]) -> Vec<u8> {
+    let mut data = keccak_256(signature)[..4].to_vec();
+    for word in words {
+        data.extend_from_slice(word.as_bytes());
+    }
+    data
+}
+
+fn address_word(address: H160) -> H256 {
+    let mut word = H256::zero();
+    word.as_bytes_mut()[12..].copy_from_slice(address.as_bytes());
+    word
+}
+
+fn uint_word(value: U256) -> H256 {
+    let mut word = H256::zero();
+    value.to_big_endian(word.as_bytes_mut());
+    word
+}
+
+fn rlp_uint(value: U256) -> Vec<u8> {
+    let word = uint_word(value);
+    let start = word.as_bytes().iter().position(|b| *b != 0).unwrap_or(32);
+    rlp_bytes(&word.as_bytes()[start..])
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    if bytes.len() == 1 && bytes[0] < 0x80 {
+        return bytes.to_vec();
+    }
+    let mut out = rlp_length(bytes.len(), 0x80);
+    out.extend_from_slice(bytes);
+    out
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut out = rlp_length(payload.len(), 0xc0);
+    out.extend(payload);
+    out
+}
+
+fn rlp_length(length: usize, offset: u8) -> Vec<u8> {
+    if length < 56 {
+        return vec![offset + length as u8];
+    }
+    let bytes = (length as u64).to_be_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(7);
+    let mut out = vec![offset + 55 + (8 - start) as u8];
+    out.extend_from_slice(&bytes[start..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key() -> secp256k1::SecretKey {
+        secp256k1::SecretKey::parse(&[1u8; 32]).unwrap()
+    }
+
+    #[test]
+    fn rlp_encoding_works() {
+        assert_eq!(rlp_uint(U256::zero()), vec![0x80]);
+        assert_eq!(rlp_uint(U256::from(0x7f)), vec![0x7f]);
+        assert_eq!(rlp_uint(U256::from(0x400)), vec![0x82, 0x04, 0x00]);
+        assert_eq!(rlp_bytes(b"dog"), vec![0x83, b'd', b'o', b'g']);
+        assert_eq!(
+            rlp_list(&[rlp_bytes(b"cat"), rlp_bytes(b"dog")]),
+            vec![0xc8, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g']
+        );
+        let long = [0u8; 60];
+        assert_eq!(rlp_bytes(&long)[..2], [0xb8, 60]);
+    }
+
+    #[test]
+    fn contract_calls_should_be_abi_encoded() {
+        let call = ContractCall::WithdrawTransfer {
+            message_id: H256::repeat_byte(1),
+            sender: H256::repeat_byte(2),
+            recipient: H160::repeat_byte(3),
+            amount: 1000.into(),
+        };
+        let data = call.encode();
+        assert_eq!(data.len(), 4 + 4 * 32);
+        assert_eq!(data[..4], keccak_256(WITHDRAW_TRANSFER)[..4]);
+        assert_eq!(data[4..36], [1u8; 32]);
+        assert_eq!(data[36..68], [2u8; 32]);
+        assert_eq!(data[68..80], [0u8; 12]);
+        assert_eq!(data[80..100], [3u8; 20]);
+        assert_eq!(U256::from_big_endian(&data[100..132]), 1000.into());
+    }
+
+    #[test]
+    fn events_should_be_decoded() {
+        let mut data = H256::repeat_byte(1).as_bytes().to_vec();
+        data.extend_from_slice(address_word(H160::repeat_byte(2)).as_bytes());
+        data.extend_from_slice(H256::repeat_byte(3).as_bytes());
+        data.extend_from_slice(uint_word(500.into()).as_bytes());
+        let relay = H256::from(keccak_256(RELAY_MESSAGE));
+        assert_eq!(
+            decode_event(&[relay], &data),
+            Some(EthEvent::Relay {
+                message_id: H256::repeat_byte(1),
+                sender: H160::repeat_byte(2),
+                recipient: H256::repeat_byte(3),
+                amount: 500.into(),
+            })
+        );
+        // truncated data is skipped
+        assert_eq!(decode_event(&[relay], &data[..64]), None);
+        let withdraw = H256::from(keccak_256(WITHDRAW_MESSAGE));
+        assert_eq!(
+            decode_event(&[withdraw], &data[..32]),
+            Some(EthEvent::Withdraw {
+                message_id: H256::repeat_byte(1)
+            })
+        );
+        assert_eq!(decode_event(&[H256::zero()], &data), None);
+    }
+
+    #[test]
+    fn transactions_should_be_signed_by_the_key() {
+        let transaction = Transaction {
+            nonce: 7.into(),
+            gas_price: 1_000_000_000u64.into(),
+            gas_limit: 300_000u64.into(),
+            to: H160::repeat_byte(5),
+            data: vec![1, 2, 3],
+        };
+        let chain_id = 1337;
+        let raw = transaction.sign(&key(), chain_id);
+        assert_eq!(raw[0], 0xf8);
+
+        // v, r and s are the last 3 items, r and s are 32 bytes long without leading zeros
+        let unsigned = transaction.rlp(&[
+            rlp_uint(chain_id.into()),
+            rlp_uint(U256::zero()),
+            rlp_uint(U256::zero()),
+        ]);
+        let message = secp256k1::Message::parse(&keccak_256(&unsigned));
+        let (signature, recovery_id) = secp256k1::sign(&message, &key());
+        let expected = transaction.rlp(&[
+            rlp_uint((u64::from(recovery_id.serialize()) + 35 + chain_id * 2).into()),
+            rlp_uint(U256::from_big_endian(&signature.serialize()[..32])),
+            rlp_uint(U256::from_big_endian(&signature.serialize()[32..])),
+        ]);
+        assert_eq!(raw, expected);
+        let public = secp256k1::recover(&message, &signature, &recovery_id).unwrap();
+        assert_eq!(
+            H160::from_slice(&keccak_256(&public.serialize()[1..])[12..]),
+            address(&key())
+        );
+    }
+}
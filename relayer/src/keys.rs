@@ -0,0 +1,49 @@
+use sp_core::{crypto::KeyTypeId, ecdsa, sr25519};
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+
+/// validator account key, signs Substrate extrinsics like the bridge offchain worker
+pub use akropolisos_runtime::bridge::KEY_TYPE as SUBSTRATE_KEY_TYPE;
+/// validator Ethereum key, signs contract calls and attestations
+pub const ETHEREUM_KEY_TYPE: KeyTypeId = KeyTypeId(*b"beth");
+
+pub struct Keys {
+    pub substrate: sr25519::Pair,
+    pub ethereum: secp256k1::SecretKey,
+}
+
+/// Load the first bridge keys of each type, insert them with `author_insertKey`
+/// or `subkey insert` beforehand
+pub fn load(path: PathBuf, password: Option<String>) -> Result<Keys> {
+    let keystore = sc_keystore::Store::open(path, password.map(Into::into))
+        .map_err(|e| Error::Other(format!("can't open keystore: {}", e)))?;
+    let keystore = keystore.read();
+
+    let public = keystore
+        .public_keys_by_type::<sr25519::Public>(SUBSTRATE_KEY_TYPE)
+        .map_err(|e| Error::Other(e.to_string()))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::Other("no validator key in the keystore".into()))?;
+    let substrate = keystore
+        .key_pair_by_type::<sr25519::Pair>(&public, SUBSTRATE_KEY_TYPE)
+        .map_err(|e| Error::Other(e.to_string()))?;
+
+    let public = keystore
+        .public_keys_by_type::<ecdsa::Public>(ETHEREUM_KEY_TYPE)
+        .map_err(|e| Error::Other(e.to_string()))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::Other("no Ethereum key in the keystore".into()))?;
+    let ethereum = keystore
+        .key_pair_by_type::<ecdsa::Pair>(&public, ETHEREUM_KEY_TYPE)
+        .map_err(|e| Error::Other(e.to_string()))?;
+    let ethereum = secp256k1::SecretKey::parse(&ethereum.seed())
+        .map_err(|_| Error::Other("invalid Ethereum key".into()))?;
+
+    Ok(Keys {
+        substrate,
+        ethereum,
+    })
+}
@@ -0,0 +1,155 @@
+//! Bridge relayer run by every bridge validator.
+//!
+//! Follows the DAIBridge contract and the bridge pallet and votes for transfers:
+//! deposits on Ethereum are minted with `multi_signed_mint`, withdrawals are
+//! attested with `approve_transfer` and paid out with `withdrawTransfer`,
+//! payouts and mints are confirmed on the opposite chain.
+
+mod error;
+mod ethereum;
+mod keys;
+mod relay;
+mod rpc;
+mod store;
+mod substrate;
+
+use akropolisos_runtime::{AccountId, ChainId, TokenId};
+use log::{error, info, warn};
+use sp_core::{Pair, H160};
+use std::{path::PathBuf, thread, time::Duration};
+use structopt::StructOpt;
+
+use crate::error::{Error, Result};
+use crate::ethereum::EthereumRpc;
+use crate::relay::{Config, Relayer};
+use crate::rpc::RpcClient;
+use crate::store::SledStore;
+use crate::substrate::SubstrateRpc;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "bridge-relayer")]
+struct Opt {
+    /// Substrate node WebSocket endpoint
+    #[structopt(long, default_value = "ws://127.0.0.1:9944")]
+    substrate_url: String,
+    /// Ethereum node WebSocket endpoint
+    #[structopt(long, default_value = "ws://127.0.0.1:8545")]
+    ethereum_url: String,
+    /// DAIBridge contract address
+    #[structopt(long, parse(try_from_str = parse_address))]
+    contract: H160,
+    /// EIP-155 id of the Ethereum network
+    #[structopt(long, default_value = "1")]
+    ethereum_chain_id: u64,
+    /// id of the contract chain in the bridge pallet
+    #[structopt(long, default_value = "0")]
+    chain_id: ChainId,
+    /// bridge token relayed by the contract
+    #[structopt(long, default_value = "0")]
+    token_id: TokenId,
+    /// Ethereum blocks on top of a block before its events are relayed
+    #[structopt(long, default_value = "12")]
+    confirmations: u64,
+    #[structopt(long, default_value = "300000")]
+    gas_limit: u64,
+    /// blocks to start from when the database is empty
+    #[structopt(long, default_value = "0")]
+    ethereum_start: u64,
+    #[structopt(long, default_value = "0")]
+    substrate_start: u64,
+    /// seconds between polls
+    #[structopt(long, default_value = "6")]
+    interval: u64,
+    #[structopt(long, parse(from_os_str), default_value = "relayer-db")]
+    db: PathBuf,
+    /// keystore with `brdg` sr25519 and `beth` ecdsa validator keys
+    #[structopt(long, parse(from_os_str))]
+    keystore: PathBuf,
+    #[structopt(long)]
+    password: Option<String>,
+}
+
+const MAX_BLOCKS: u64 = 1000;
+
+fn parse_address(s: &str) -> std::result::Result<H160, String> {
+    let hex = s.trim_start_matches("0x");
+    if hex.len() != 40 {
+        return Err(format!("{} is not an Ethereum address", s));
+    }
+    hex.parse()
+        .map_err(|_| format!("{} is not an Ethereum address", s))
+}
+
+fn main() {
+    env_logger::init();
+    let opt = Opt::from_args();
+    if let Err(e) = run(opt) {
+        error!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(opt: Opt) -> Result<()> {
+    let keys = keys::load(opt.keystore.clone(), opt.password.clone())?;
+    let mut store = Some(SledStore::open(&opt.db)?);
+    let interval = Duration::from_secs(opt.interval);
+    let account: AccountId = keys.substrate.public().into();
+    info!(
+        "Relaying for {} from {:?}",
+        account,
+        ethereum::address(&keys.ethereum)
+    );
+
+    loop {
+        let connected = RpcClient::connect(&opt.ethereum_url).and_then(|ethereum| {
+            let substrate = RpcClient::connect(&opt.substrate_url)?;
+            let substrate = SubstrateRpc::new(substrate, keys.substrate.clone())?;
+            Ok((ethereum, substrate))
+        });
+        let (ethereum, substrate) = match connected {
+            Ok(clients) => clients,
+            Err(e) => {
+                warn!("{}", e);
+                thread::sleep(interval);
+                continue;
+            }
+        };
+        let ethereum = EthereumRpc::new(
+            ethereum,
+            opt.contract,
+            keys.ethereum.clone(),
+            opt.ethereum_chain_id,
+            opt.gas_limit,
+        );
+        let config = Config {
+            chain_id: opt.chain_id,
+            token_id: opt.token_id,
+            confirmations: opt.confirmations,
+            max_blocks: MAX_BLOCKS,
+            ethereum_start: opt.ethereum_start,
+            substrate_start: opt.substrate_start,
+        };
+        let mut relayer = Relayer::new(
+            ethereum,
+            substrate,
+            store.take().expect("store is returned on disconnect; qed"),
+            keys.ethereum.clone(),
+            config,
+        );
+
+        // reconnect when any of the nodes goes away, other errors are logged and retried
+        loop {
+            match relayer.step() {
+                Ok(()) => {}
+                Err(Error::Connection(e)) => {
+                    warn!("Disconnected: {}", e);
+                    break;
+                }
+                Err(e) => warn!("{}", e),
+            }
+            thread::sleep(interval);
+        }
+        store = Some(relayer.into_store());
+        thread::sleep(interval);
+    }
+}
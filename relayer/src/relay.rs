@@ -0,0 +1,770 @@
+use akropolisos_runtime::{
+    bridge, AccountId, Balance, BridgeCall, ChainId, Hash, Runtime, Status, TokenId,
+};
+use codec::{Decode, Encode};
+use log::{info, warn};
+use sp_core::{H160, H256};
+
+use crate::error::{Error, Result};
+use crate::ethereum::{sign_digest, ContractCall, EthEvent, EthereumChain};
+use crate::store::Store;
+use crate::substrate::{Message, SubEvent, SubstrateChain};
+
+const ETHEREUM_BLOCK: &[u8] = b"ethereum_block";
+const SUBSTRATE_BLOCK: &[u8] = b"substrate_block";
+const SENT_TASKS: &[u8] = b"sent";
+
+/// blocks of the target chain the task is waited for before it is sent again
+const RESEND_AFTER: u64 = 20;
+/// sends of the task before it is given up
+const MAX_ATTEMPTS: u32 = 5;
+
+pub struct Config {
+    pub chain_id: ChainId,
+    pub token_id: TokenId,
+    /// Ethereum blocks on top of a block before its events are relayed
+    pub confirmations: u64,
+    /// blocks processed per step on each chain
+    pub max_blocks: u64,
+    /// first blocks to follow when the database is empty
+    pub ethereum_start: u64,
+    pub substrate_start: u64,
+}
+
+/// Single vote or contract call of the validator
+#[derive(Debug, Clone, Encode, Decode)]
+enum Task {
+    /// vote for the Ethereum deposit on Substrate
+    Mint {
+        message_id: Hash,
+        sender: H160,
+        recipient: AccountId,
+        amount: Balance,
+    },
+    /// attest the withdrawal on Substrate
+    Attest(Message),
+    /// confirm the withdrawal paid out on Ethereum
+    ConfirmBurn(Hash),
+    /// pay out the approved withdrawal on Ethereum
+    Withdraw(Message),
+    /// approve the minted deposit on Ethereum
+    ApproveDeposit(Message),
+    /// confirm the approved deposit on Ethereum
+    ConfirmDeposit(Hash),
+}
+
+impl Task {
+    /// database key marking the task as done
+    fn key(&self) -> Vec<u8> {
+        let (kind, message_id) = match self {
+            Task::Mint { message_id, .. } => (0u8, message_id),
+            Task::Attest(message) => (1, &message.message_id),
+            Task::ConfirmBurn(message_id) => (2, message_id),
+            Task::Withdraw(message) => (3, &message.message_id),
+            Task::ApproveDeposit(message) => (4, &message.message_id),
+            Task::ConfirmDeposit(message_id) => (5, message_id),
+        };
+        let mut key = b"task".to_vec();
+        key.push(kind);
+        key.extend_from_slice(message_id.as_bytes());
+        key
+    }
+
+    fn on_ethereum(&self) -> bool {
+        match self {
+            Task::Withdraw(_) | Task::ApproveDeposit(_) | Task::ConfirmDeposit(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Task sent to its chain and not seen there yet
+#[derive(Encode, Decode)]
+struct Sent {
+    task: Task,
+    /// transactions sent for the task, any of them may be mined
+    hashes: Vec<Hash>,
+    /// block of the target chain the task was last sent at
+    block: u64,
+    attempts: u32,
+}
+
+/// Follows both chains and relays bridge transfers between them.
+///
+/// Progress is stored after every processed block range and every executed task,
+/// so a restarted relayer continues where it stopped and never votes twice.
+/// Sent tasks are kept until they are seen on chain and sent again when they are lost.
+pub struct Relayer<E, S, D> {
+    ethereum: E,
+    substrate: S,
+    store: D,
+    key: secp256k1::SecretKey,
+    config: Config,
+}
+
+impl<E: EthereumChain, S: SubstrateChain, D: Store> Relayer<E, S, D> {
+    pub fn new(
+        ethereum: E,
+        substrate: S,
+        store: D,
+        key: secp256k1::SecretKey,
+        config: Config,
+    ) -> Self {
+        Relayer {
+            ethereum,
+            substrate,
+            store,
+            key,
+            config,
+        }
+    }
+
+    pub fn into_store(self) -> D {
+        self.store
+    }
+
+    pub fn step(&mut self) -> Result<()> {
+        self.relay_ethereum()?;
+        self.relay_substrate()?;
+        self.check_sent()
+    }
+
+    fn relay_ethereum(&mut self) -> Result<()> {
+        let last = match self.store.get_u64(ETHEREUM_BLOCK)? {
+            Some(block) => block,
+            None => self.config.ethereum_start.saturating_sub(1),
+        };
+        let head = self.ethereum.block_number()?;
+        let safe = head.saturating_sub(self.config.confirmations);
+        if safe <= last {
+            return Ok(());
+        }
+        let to = safe.min(last + self.config.max_blocks);
+
+        for event in self.ethereum.events(last + 1, to)? {
+            let task = match event {
+                EthEvent::Relay {
+                    message_id,
+                    sender,
+                    recipient,
+                    amount,
+                } => {
+                    if amount > u128::max_value().into() {
+                        warn!("Deposit {:?} amount is too big: {}", message_id, amount);
+                        continue;
+                    }
+                    Task::Mint {
+                        message_id,
+                        sender,
+                        recipient: AccountId::from(recipient.to_fixed_bytes()),
+                        amount: amount.low_u128(),
+                    }
+                }
+                EthEvent::Approved { message_id } => Task::ConfirmDeposit(message_id),
+                EthEvent::Withdraw { message_id } => Task::ConfirmBurn(message_id),
+            };
+            self.execute_once(task)?;
+        }
+        self.store.put_u64(ETHEREUM_BLOCK, to)
+    }
+
+    fn relay_substrate(&mut self) -> Result<()> {
+        let last = match self.store.get_u64(SUBSTRATE_BLOCK)? {
+            Some(block) => block,
+            None => self.config.substrate_start.saturating_sub(1),
+        };
+        let finalized = self.substrate.finalized_number()?;
+        let to = finalized.min(last + self.config.max_blocks);
+
+        for number in last + 1..=to {
+            for event in self.substrate.events(number)? {
+                let message_id = match event {
+                    SubEvent::Relay(id) | SubEvent::Approved(id) | SubEvent::Minted(id) => id,
+                };
+                let message = match self.substrate.transfer_message(message_id)? {
+                    Some(message) => message,
+                    None => continue,
+                };
+                // transfers of other chains and tokens are relayed by other contracts
+                if message.chain != self.config.chain_id || message.token != self.config.token_id {
+                    continue;
+                }
+                let task = match event {
+                    SubEvent::Relay(_) => Task::Attest(message),
+                    SubEvent::Approved(_) => Task::Withdraw(message),
+                    SubEvent::Minted(_) => Task::ApproveDeposit(message),
+                };
+                self.execute_once(task)?;
+            }
+            self.store.put_u64(SUBSTRATE_BLOCK, number)?;
+        }
+        Ok(())
+    }
+
+    /// Send the task unless it was sent before, it is followed by `check_sent`.
+    fn execute_once(&mut self, task: Task) -> Result<()> {
+        let key = task.key();
+        if self.store.get(&key)?.is_some() {
+            return Ok(());
+        }
+        self.send(task, Vec::new(), 0)?;
+        self.store.put(&key, &[1])
+    }
+
+    /// Forget the tasks seen on chain and send again the ones not seen for a while:
+    /// the transaction may be dropped, fail in the block or be rejected by a busy node.
+    fn check_sent(&mut self) -> Result<()> {
+        let pending = self.store.scan_prefix(SENT_TASKS)?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let ethereum_head = self.ethereum.block_number()?;
+        let substrate_head = self.substrate.finalized_number()?;
+        for (key, value) in pending {
+            let sent = Sent::decode(&mut &value[..])?;
+            let head = if sent.task.on_ethereum() {
+                ethereum_head
+            } else {
+                substrate_head
+            };
+            if self.is_seen(&sent, ethereum_head)? {
+                info!("Relayed {:?}", sent.task);
+                self.store.remove(&key)?;
+            } else if head >= sent.block + RESEND_AFTER {
+                if sent.attempts >= MAX_ATTEMPTS {
+                    warn!(
+                        "{:?} is given up after {} attempts",
+                        sent.task, sent.attempts
+                    );
+                    self.store.remove(&key)?;
+                } else {
+                    self.send(sent.task, sent.hashes, sent.attempts)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// vote is finalized on Substrate or the contract call is confirmed on Ethereum
+    fn is_seen(&mut self, sent: &Sent, ethereum_head: u64) -> Result<bool> {
+        let (message_id, vote) = match &sent.task {
+            Task::Mint { message_id, .. } => (
+                bridge::Module::<Runtime>::chain_message_id(self.config.chain_id, *message_id),
+                Status::Approved,
+            ),
+            Task::Attest(message) => (message.message_id, Status::Approved),
+            Task::ConfirmBurn(message_id) => (*message_id, Status::Confirmed),
+            _ => {
+                let safe = ethereum_head.saturating_sub(self.config.confirmations);
+                for hash in &sent.hashes {
+                    if let Some((block, true)) = self.ethereum.receipt(*hash)? {
+                        if block <= safe {
+                            return Ok(true);
+                        }
+                    }
+                }
+                return Ok(false);
+            }
+        };
+        // pruned transfers are finished, there is nothing to vote for
+        Ok(self
+            .substrate
+            .signed_votes(message_id)?
+            .map_or(true, |votes| votes.contains(&vote)))
+    }
+
+    /// Send the task and keep it until it is seen on chain. Rejected tasks are kept
+    /// as well, the node may accept them later.
+    fn send(&mut self, task: Task, mut hashes: Vec<Hash>, attempts: u32) -> Result<()> {
+        let block = if task.on_ethereum() {
+            self.ethereum.block_number()?
+        } else {
+            self.substrate.finalized_number()?
+        };
+        match self.execute(&task) {
+            Ok(hash) => {
+                info!("Sent {:?}", task);
+                hashes.push(hash);
+            }
+            Err(Error::Rejected(reason)) => warn!("{:?} was rejected: {}", task, reason),
+            Err(e) => return Err(e),
+        }
+        let mut key = SENT_TASKS.to_vec();
+        key.extend(task.key());
+        let sent = Sent {
+            task,
+            hashes,
+            block,
+            attempts: attempts + 1,
+        };
+        self.store.put(&key, &sent.encode())
+    }
+
+    fn execute(&mut self, task: &Task) -> Result<Hash> {
+        match task.clone() {
+            Task::Mint {
+                message_id,
+                sender,
+                recipient,
+                amount,
+            } => self.substrate.submit(BridgeCall::multi_signed_mint(
+                self.config.chain_id,
+                message_id,
+                sender,
+                recipient,
+                self.config.token_id,
+                amount,
+            )),
+            Task::Attest(message) => {
+                let digest = bridge::Module::<Runtime>::attestation_digest(&message);
+                let signature = sign_digest(&self.key, digest);
+                self.substrate
+                    .submit(BridgeCall::approve_transfer(message.message_id, signature))
+            }
+            Task::ConfirmBurn(message_id) => self
+                .substrate
+                .submit(BridgeCall::confirm_transfer(message_id)),
+            Task::Withdraw(message) => self.ethereum.send(ContractCall::WithdrawTransfer {
+                message_id: message.message_id,
+                sender: H256::from_slice(message.substrate_address.as_ref()),
+                recipient: message.eth_address,
+                amount: message.amount.into(),
+            }),
+            Task::ApproveDeposit(message) => self.ethereum.send(ContractCall::ApproveTransfer {
+                message_id: message.message_id,
+                spender: message.eth_address,
+                recipient: H256::from_slice(message.substrate_address.as_ref()),
+                amount: message.amount.into(),
+            }),
+            Task::ConfirmDeposit(message_id) => self
+                .ethereum
+                .send(ContractCall::ConfirmTransfer { message_id }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::MemoryStore;
+    use sp_core::{hashing::keccak_256, U256};
+    use std::collections::HashMap;
+
+    const CHAIN: ChainId = 0;
+    const TOKEN: TokenId = 0;
+
+    #[derive(Default)]
+    struct MockEthereum {
+        head: u64,
+        events: Vec<(u64, EthEvent)>,
+        sent: Vec<ContractCall>,
+        receipts: HashMap<H256, (u64, bool)>,
+        offline: bool,
+        // transactions are sent but never mined
+        dropped: bool,
+    }
+
+    impl EthereumChain for MockEthereum {
+        fn block_number(&mut self) -> Result<u64> {
+            Ok(self.head)
+        }
+
+        fn events(&mut self, from: u64, to: u64) -> Result<Vec<EthEvent>> {
+            assert!(to <= self.head);
+            Ok(self
+                .events
+                .iter()
+                .filter(|(block, _)| (from..=to).contains(block))
+                .map(|(_, event)| event.clone())
+                .collect())
+        }
+
+        fn send(&mut self, call: ContractCall) -> Result<H256> {
+            if self.offline {
+                return Err(Error::Connection("offline".into()));
+            }
+            self.sent.push(call);
+            let hash = H256::from_low_u64_be(self.sent.len() as u64);
+            if !self.dropped {
+                self.receipts.insert(hash, (self.head, true));
+            }
+            Ok(hash)
+        }
+
+        fn receipt(&mut self, hash: H256) -> Result<Option<(u64, bool)>> {
+            Ok(self.receipts.get(&hash).cloned())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockSubstrate {
+        finalized: u64,
+        events: HashMap<u64, Vec<SubEvent>>,
+        messages: HashMap<Hash, Message>,
+        submitted: Vec<BridgeCall<Runtime>>,
+        votes: HashMap<Hash, Vec<Status>>,
+        offline: bool,
+        reject: bool,
+        // extrinsics are submitted but never included
+        dropped: bool,
+    }
+
+    impl SubstrateChain for MockSubstrate {
+        fn finalized_number(&mut self) -> Result<u64> {
+            Ok(self.finalized)
+        }
+
+        fn events(&mut self, number: u64) -> Result<Vec<SubEvent>> {
+            assert!(number <= self.finalized);
+            Ok(self.events.get(&number).cloned().unwrap_or_default())
+        }
+
+        fn transfer_message(&mut self, message_id: Hash) -> Result<Option<Message>> {
+            Ok(self.messages.get(&message_id).cloned())
+        }
+
+        fn signed_votes(&mut self, message_id: Hash) -> Result<Option<Vec<Status>>> {
+            Ok(Some(
+                self.votes.get(&message_id).cloned().unwrap_or_default(),
+            ))
+        }
+
+        fn submit(&mut self, call: BridgeCall<Runtime>) -> Result<Hash> {
+            if self.offline {
+                return Err(Error::Connection("offline".into()));
+            }
+            if self.reject {
+                return Err(Error::Rejected("Priority is too low".into()));
+            }
+            let vote = match &call {
+                BridgeCall::multi_signed_mint(chain_id, message_id, ..) => Some((
+                    bridge::Module::<Runtime>::chain_message_id(*chain_id, *message_id),
+                    Status::Approved,
+                )),
+                BridgeCall::approve_transfer(message_id, _) => {
+                    Some((*message_id, Status::Approved))
+                }
+                BridgeCall::confirm_transfer(message_id) => Some((*message_id, Status::Confirmed)),
+                _ => None,
+            };
+            match vote {
+                Some((message_id, vote)) if !self.dropped => {
+                    self.votes.entry(message_id).or_default().push(vote)
+                }
+                _ => {}
+            }
+            self.submitted.push(call);
+            Ok(H256::from_low_u64_be(self.submitted.len() as u64))
+        }
+    }
+
+    fn key() -> secp256k1::SecretKey {
+        secp256k1::SecretKey::parse(&[7u8; 32]).unwrap()
+    }
+
+    fn relayer() -> Relayer<MockEthereum, MockSubstrate, MemoryStore> {
+        let config = Config {
+            chain_id: CHAIN,
+            token_id: TOKEN,
+            confirmations: 5,
+            max_blocks: 100,
+            ethereum_start: 1,
+            substrate_start: 1,
+        };
+        Relayer::new(
+            MockEthereum::default(),
+            MockSubstrate::default(),
+            MemoryStore::default(),
+            key(),
+            config,
+        )
+    }
+
+    fn message(id: u8, chain: ChainId) -> Message {
+        Message {
+            message_id: H256::repeat_byte(id),
+            token: TOKEN,
+            eth_address: H160::repeat_byte(2),
+            substrate_address: AccountId::from([3u8; 32]),
+            amount: 1000,
+            status: Status::Withdraw,
+            action: Status::Withdraw,
+            chain,
+        }
+    }
+
+    fn deposit(id: u8) -> EthEvent {
+        EthEvent::Relay {
+            message_id: H256::repeat_byte(id),
+            sender: H160::repeat_byte(2),
+            recipient: H256::repeat_byte(3),
+            amount: 1000.into(),
+        }
+    }
+
+    fn mint(id: u8) -> BridgeCall<Runtime> {
+        BridgeCall::multi_signed_mint(
+            CHAIN,
+            H256::repeat_byte(id),
+            H160::repeat_byte(2),
+            AccountId::from([3u8; 32]),
+            TOKEN,
+            1000,
+        )
+    }
+
+    #[test]
+    fn deposits_should_be_minted_after_confirmations() {
+        let mut relayer = relayer();
+        relayer.ethereum.events = vec![(3, deposit(1)), (8, deposit(2))];
+
+        relayer.ethereum.head = 7;
+        assert_eq!(relayer.step(), Ok(()));
+        assert!(relayer.substrate.submitted.is_empty());
+
+        relayer.ethereum.head = 8;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.substrate.submitted, vec![mint(1)]);
+        assert_eq!(relayer.store.get_u64(ETHEREUM_BLOCK), Ok(Some(3)));
+
+        relayer.ethereum.head = 20;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.substrate.submitted, vec![mint(1), mint(2)]);
+        assert_eq!(relayer.store.get_u64(ETHEREUM_BLOCK), Ok(Some(15)));
+    }
+
+    #[test]
+    fn oversized_deposits_should_be_skipped() {
+        let mut relayer = relayer();
+        relayer.ethereum.events = vec![(
+            1,
+            EthEvent::Relay {
+                message_id: H256::repeat_byte(1),
+                sender: H160::repeat_byte(2),
+                recipient: H256::repeat_byte(3),
+                amount: U256::from(u128::max_value()) + 1,
+            },
+        )];
+        relayer.ethereum.head = 10;
+        assert_eq!(relayer.step(), Ok(()));
+        assert!(relayer.substrate.submitted.is_empty());
+    }
+
+    #[test]
+    fn withdrawals_should_be_attested_and_paid_out() {
+        let mut relayer = relayer();
+        let withdrawal = message(1, CHAIN);
+        relayer
+            .substrate
+            .messages
+            .insert(withdrawal.message_id, withdrawal.clone());
+        relayer
+            .substrate
+            .events
+            .insert(1, vec![SubEvent::Relay(withdrawal.message_id)]);
+        relayer
+            .substrate
+            .events
+            .insert(2, vec![SubEvent::Approved(withdrawal.message_id)]);
+        relayer.substrate.finalized = 2;
+        assert_eq!(relayer.step(), Ok(()));
+
+        // the attestation is signed by the relayer Ethereum key
+        let signature = match &relayer.substrate.submitted[..] {
+            [BridgeCall::approve_transfer(id, signature)] => {
+                assert_eq!(*id, withdrawal.message_id);
+                signature.clone()
+            }
+            calls => panic!("unexpected calls {:?}", calls),
+        };
+        let raw: &[u8; 65] = signature.as_ref();
+        let mut rs = [0u8; 64];
+        rs.copy_from_slice(&raw[..64]);
+        let digest = bridge::Module::<Runtime>::attestation_digest(&withdrawal);
+        let public = secp256k1::recover(
+            &secp256k1::Message::parse(&digest),
+            &secp256k1::Signature::parse(&rs),
+            &secp256k1::RecoveryId::parse(raw[64]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            H160::from_slice(&keccak_256(&public.serialize()[1..])[12..]),
+            crate::ethereum::address(&key())
+        );
+
+        assert_eq!(
+            relayer.ethereum.sent,
+            vec![ContractCall::WithdrawTransfer {
+                message_id: withdrawal.message_id,
+                sender: H256::repeat_byte(3),
+                recipient: H160::repeat_byte(2),
+                amount: 1000.into(),
+            }]
+        );
+
+        // payout on Ethereum is confirmed on Substrate
+        relayer.ethereum.events = vec![(
+            1,
+            EthEvent::Withdraw {
+                message_id: withdrawal.message_id,
+            },
+        )];
+        relayer.ethereum.head = 6;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(
+            relayer.substrate.submitted.last(),
+            Some(&BridgeCall::confirm_transfer(withdrawal.message_id))
+        );
+    }
+
+    #[test]
+    fn minted_deposits_should_be_approved_on_ethereum() {
+        let mut relayer = relayer();
+        let deposit = message(1, CHAIN);
+        relayer
+            .substrate
+            .messages
+            .insert(deposit.message_id, deposit.clone());
+        relayer
+            .substrate
+            .events
+            .insert(1, vec![SubEvent::Minted(deposit.message_id)]);
+        relayer.substrate.finalized = 1;
+        relayer.ethereum.events = vec![(
+            1,
+            EthEvent::Approved {
+                message_id: deposit.message_id,
+            },
+        )];
+        relayer.ethereum.head = 6;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(
+            relayer.ethereum.sent,
+            vec![
+                ContractCall::ConfirmTransfer {
+                    message_id: deposit.message_id
+                },
+                ContractCall::ApproveTransfer {
+                    message_id: deposit.message_id,
+                    spender: H160::repeat_byte(2),
+                    recipient: H256::repeat_byte(3),
+                    amount: 1000.into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn other_chains_should_be_ignored() {
+        let mut relayer = relayer();
+        let foreign = message(1, CHAIN + 1);
+        relayer
+            .substrate
+            .messages
+            .insert(foreign.message_id, foreign.clone());
+        relayer.substrate.events.insert(
+            1,
+            vec![
+                SubEvent::Relay(foreign.message_id),
+                SubEvent::Approved(foreign.message_id),
+                // unknown transfer
+                SubEvent::Relay(H256::repeat_byte(9)),
+            ],
+        );
+        relayer.substrate.finalized = 1;
+        assert_eq!(relayer.step(), Ok(()));
+        assert!(relayer.substrate.submitted.is_empty());
+        assert!(relayer.ethereum.sent.is_empty());
+        assert_eq!(relayer.store.get_u64(SUBSTRATE_BLOCK), Ok(Some(1)));
+    }
+
+    #[test]
+    fn unavailable_nodes_should_be_retried() {
+        let mut relayer = relayer();
+        relayer.ethereum.events = vec![(1, deposit(1)), (2, deposit(2))];
+        relayer.ethereum.head = 10;
+
+        relayer.substrate.offline = true;
+        assert!(relayer.step().is_err());
+        assert_eq!(relayer.store.get_u64(ETHEREUM_BLOCK), Ok(None));
+
+        relayer.substrate.offline = false;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.substrate.submitted, vec![mint(1), mint(2)]);
+
+        // a crash after the votes but before the progress is saved doesn't repeat them
+        relayer.store.put_u64(ETHEREUM_BLOCK, 0).unwrap();
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.substrate.submitted, vec![mint(1), mint(2)]);
+    }
+
+    #[test]
+    fn rejected_calls_should_be_retried() {
+        let mut relayer = relayer();
+        relayer.ethereum.events = vec![(1, deposit(1))];
+        relayer.ethereum.head = 10;
+
+        relayer.substrate.reject = true;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.store.get_u64(ETHEREUM_BLOCK), Ok(Some(5)));
+        assert!(relayer.substrate.submitted.is_empty());
+
+        // sent again after a while
+        relayer.substrate.reject = false;
+        assert_eq!(relayer.step(), Ok(()));
+        assert!(relayer.substrate.submitted.is_empty());
+        relayer.substrate.finalized = RESEND_AFTER;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.substrate.submitted, vec![mint(1)]);
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.store.scan_prefix(SENT_TASKS), Ok(vec![]));
+    }
+
+    #[test]
+    fn lost_calls_should_be_sent_until_seen() {
+        let mut relayer = relayer();
+        relayer.ethereum.events = vec![(1, deposit(1))];
+        relayer.ethereum.head = 10;
+
+        relayer.substrate.dropped = true;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.substrate.submitted, vec![mint(1)]);
+
+        relayer.substrate.finalized = RESEND_AFTER;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.substrate.submitted, vec![mint(1), mint(1)]);
+
+        // the vote is finalized, nothing is sent anymore
+        relayer.substrate.dropped = false;
+        relayer.substrate.finalized = 2 * RESEND_AFTER;
+        assert_eq!(relayer.step(), Ok(()));
+        relayer.substrate.finalized = 3 * RESEND_AFTER;
+        assert_eq!(relayer.step(), Ok(()));
+        assert_eq!(relayer.substrate.submitted, vec![mint(1), mint(1), mint(1)]);
+    }
+
+    #[test]
+    fn unconfirmed_contract_calls_should_be_given_up() {
+        let mut relayer = relayer();
+        let deposit = message(1, CHAIN);
+        relayer
+            .substrate
+            .messages
+            .insert(deposit.message_id, deposit.clone());
+        relayer
+            .substrate
+            .events
+            .insert(1, vec![SubEvent::Minted(deposit.message_id)]);
+        relayer.substrate.finalized = 1;
+
+        relayer.ethereum.dropped = true;
+        for attempt in 0..=u64::from(MAX_ATTEMPTS) {
+            relayer.ethereum.head = attempt * RESEND_AFTER;
+            assert_eq!(relayer.step(), Ok(()));
+        }
+        assert_eq!(relayer.ethereum.sent.len(), MAX_ATTEMPTS as usize);
+        assert_eq!(relayer.store.scan_prefix(SENT_TASKS), Ok(vec![]));
+    }
+}
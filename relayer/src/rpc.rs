@@ -0,0 +1,32 @@
+use futures::Future;
+use jsonrpc_core::Params;
+use jsonrpc_core_client::{transports::ws, RawClient};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::runtime::Runtime;
+
+use crate::error::{Error, Result};
+
+/// Blocking JSON-RPC client over WebSocket, used for both chains
+pub struct RpcClient {
+    runtime: Runtime,
+    client: RawClient,
+}
+
+impl RpcClient {
+    pub fn connect(url: &str) -> Result<Self> {
+        let url = url::Url::parse(url).map_err(|e| Error::Other(format!("{}: {}", url, e)))?;
+        let mut runtime = Runtime::new().map_err(|e| Error::Other(e.to_string()))?;
+        let client = runtime.block_on(ws::connect::<RawClient>(&url))?;
+        Ok(RpcClient { runtime, client })
+    }
+
+    pub fn call<T: DeserializeOwned>(&mut self, method: &str, params: Vec<Value>) -> Result<T> {
+        let request = self
+            .client
+            .call_method(method, Params::Array(params))
+            .map_err(Error::from);
+        let response = self.runtime.block_on(request)?;
+        Ok(serde_json::from_value(response)?)
+    }
+}
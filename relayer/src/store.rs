@@ -0,0 +1,125 @@
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// Persistent key-value storage for the relayer progress
+pub trait Store {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>>;
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<()>;
+    fn remove(&mut self, key: &[u8]) -> Result<()>;
+    /// entries with keys starting with the prefix, in the key order
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>>;
+
+    fn get_u64(&self, key: &[u8]) -> Result<Option<u64>> {
+        match self.get(key)? {
+            Some(value) => value[..]
+                .try_into()
+                .map(|bytes| Some(u64::from_le_bytes(bytes)))
+                .map_err(|_| Error::Other(format!("{} bytes stored as a number", value.len()))),
+            None => Ok(None),
+        }
+    }
+
+    fn put_u64(&mut self, key: &[u8], value: u64) -> Result<()> {
+        self.put(key, &value.to_le_bytes())
+    }
+}
+
+pub struct SledStore(sled::Db);
+
+impl SledStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(SledStore(sled::open(path)?))
+    }
+}
+
+impl Store for SledStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.get(key)?.map(|value| value.to_vec()))
+    }
+
+    // flushed on every write, so a crash never loses a sent transaction
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.0.insert(key, value)?;
+        self.0.flush()?;
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Result<()> {
+        self.0.remove(key)?;
+        self.0.flush()?;
+        Ok(())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        self.0
+            .scan_prefix(prefix)
+            .map(|entry| {
+                let (key, value) = entry?;
+                Ok((key.to_vec(), value.to_vec()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryStore(BTreeMap<Vec<u8>, Vec<u8>>);
+
+impl Store for MemoryStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(self.0.get(key).cloned())
+    }
+
+    fn put(&mut self, key: &[u8], value: &[u8]) -> Result<()> {
+        self.0.insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &[u8]) -> Result<()> {
+        self.0.remove(key);
+        Ok(())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        Ok(self
+            .0
+            .range(prefix.to_vec()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn malformed_numbers_should_fail() {
+        let mut store = MemoryStore::default();
+        store.put_u64(b"block", 7).unwrap();
+        assert_eq!(store.get_u64(b"block"), Ok(Some(7)));
+        assert_eq!(store.get_u64(b"other"), Ok(None));
+        store.put(b"block", &[1, 2, 3]).unwrap();
+        assert!(store.get_u64(b"block").is_err());
+    }
+
+    #[test]
+    fn prefix_should_be_scanned_in_order() {
+        let mut store = MemoryStore::default();
+        store.put(b"sent2", &[2]).unwrap();
+        store.put(b"sent1", &[1]).unwrap();
+        store.put(b"task1", &[3]).unwrap();
+        assert_eq!(
+            store.scan_prefix(b"sent"),
+            Ok(vec![
+                (b"sent1".to_vec(), vec![1]),
+                (b"sent2".to_vec(), vec![2])
+            ])
+        );
+        store.remove(b"sent1").unwrap();
+        assert_eq!(store.scan_prefix(b"sent").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,176 @@
+use akropolisos_runtime::{
+    bridge, AccountId, Address, Balance, BridgeCall, Call, ChargeTransactionPaymentInToken, Event,
+    Hash, Header, Index, Runtime, SignedExtra, Status, TransferMessage, UncheckedExtrinsic,
+};
+use codec::{Decode, Encode};
+use serde_json::{json, Value};
+use sp_core::{
+    hashing::{blake2_128, blake2_256, twox_128},
+    sr25519, Bytes, Pair,
+};
+use sp_runtime::generic::Era;
+
+use crate::error::{Error, Result};
+use crate::rpc::RpcClient;
+
+pub type Message = TransferMessage<AccountId, Hash, Balance>;
+
+/// Bridge pallet events the relayer reacts to
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubEvent {
+    /// withdrawal is requested, approve it with an attestation
+    Relay(Hash),
+    /// withdrawal is approved, pay it out on Ethereum
+    Approved(Hash),
+    /// deposit is minted, approve it on Ethereum
+    Minted(Hash),
+}
+
+pub trait SubstrateChain {
+    fn finalized_number(&mut self) -> Result<u64>;
+    fn events(&mut self, number: u64) -> Result<Vec<SubEvent>>;
+    fn transfer_message(&mut self, message_id: Hash) -> Result<Option<Message>>;
+    /// votes of the relayer account for the transfer in the finalized state,
+    /// `None` once the transfer is pruned and can't be voted for
+    fn signed_votes(&mut self, message_id: Hash) -> Result<Option<Vec<Status>>>;
+    /// hash of the submitted extrinsic
+    fn submit(&mut self, call: BridgeCall<Runtime>) -> Result<Hash>;
+}
+
+pub struct SubstrateRpc {
+    rpc: RpcClient,
+    pair: sr25519::Pair,
+    genesis_hash: Hash,
+}
+
+impl SubstrateRpc {
+    pub fn new(mut rpc: RpcClient, pair: sr25519::Pair) -> Result<Self> {
+        let genesis_hash = rpc.call("chain_getBlockHash", vec![json!(0)])?;
+        Ok(SubstrateRpc {
+            rpc,
+            pair,
+            genesis_hash,
+        })
+    }
+
+    fn storage<T: Decode>(&mut self, key: Vec<u8>, at: Option<Hash>) -> Result<Option<T>> {
+        let value: Option<Bytes> = self
+            .rpc
+            .call("state_getStorage", vec![json!(Bytes(key)), json!(at)])?;
+        match value {
+            Some(value) => Ok(Some(T::decode(&mut &value[..])?)),
+            None => Ok(None),
+        }
+    }
+
+    fn spec_version(&mut self) -> Result<u32> {
+        let version: Value = self.rpc.call("state_getRuntimeVersion", vec![])?;
+        version["specVersion"]
+            .as_u64()
+            .map(|version| version as u32)
+            .ok_or_else(|| Error::Other("runtime version without specVersion".into()))
+    }
+}
+
+impl SubstrateChain for SubstrateRpc {
+    fn finalized_number(&mut self) -> Result<u64> {
+        let hash: Hash = self.rpc.call("chain_getFinalizedHead", vec![])?;
+        let header: Header = self.rpc.call("chain_getHeader", vec![json!(hash)])?;
+        Ok(header.number.into())
+    }
+
+    fn events(&mut self, number: u64) -> Result<Vec<SubEvent>> {
+        let hash: Option<Hash> = self.rpc.call("chain_getBlockHash", vec![json!(number)])?;
+        let hash = hash.ok_or_else(|| Error::Other(format!("block {} is unknown", number)))?;
+        let records: Vec<system::EventRecord<Event, Hash>> = self
+            .storage(storage_key(b"System", b"Events"), Some(hash))?
+            .unwrap_or_default();
+        Ok(records
+            .into_iter()
+            .filter_map(|record| match record.event {
+                Event::bridge(bridge::RawEvent::RelayMessage(id)) => Some(SubEvent::Relay(id)),
+                Event::bridge(bridge::RawEvent::ApprovedRelayMessage(id, ..)) => {
+                    Some(SubEvent::Approved(id))
+                }
+                Event::bridge(bridge::RawEvent::MintedMessage(id, _)) => Some(SubEvent::Minted(id)),
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn transfer_message(&mut self, message_id: Hash) -> Result<Option<Message>> {
+        let mut key = storage_key(b"Bridge", b"TransferMessages");
+        key.extend_from_slice(&blake2_256(&message_id.encode()));
+        self.storage(key, None)
+    }
+
+    fn signed_votes(&mut self, message_id: Hash) -> Result<Option<Vec<Status>>> {
+        let finalized: Hash = self.rpc.call("chain_getFinalizedHead", vec![])?;
+        let mut key = storage_key(b"Bridge", b"PrunedMessages");
+        key.extend(blake2_128_concat(&message_id.encode()));
+        if self.storage(key, Some(finalized))?.unwrap_or(false) {
+            return Ok(None);
+        }
+        let account: AccountId = self.pair.public().into();
+        let mut key = storage_key(b"Bridge", b"SignedVotes");
+        key.extend(blake2_128_concat(&message_id.encode()));
+        key.extend(blake2_128_concat(&account.encode()));
+        Ok(Some(
+            self.storage(key, Some(finalized))?.unwrap_or_default(),
+        ))
+    }
+
+    fn submit(&mut self, call: BridgeCall<Runtime>) -> Result<Hash> {
+        let account: AccountId = self.pair.public().into();
+        // the pool aware nonce, so several calls can be sent within a block
+        let nonce: Index = self
+            .rpc
+            .call("system_accountNextIndex", vec![json!(account)])?;
+        let spec_version = self.spec_version()?;
+        let extra: SignedExtra = (
+            system::CheckVersion::new(),
+            system::CheckGenesis::new(),
+            system::CheckEra::from(Era::Immortal),
+            system::CheckNonce::from(nonce),
+            system::CheckWeight::new(),
+            ChargeTransactionPaymentInToken::from(0),
+            Default::default(),
+        );
+        let additional = (
+            spec_version,
+            self.genesis_hash,
+            self.genesis_hash,
+            (),
+            (),
+            (),
+            (),
+        );
+        let raw_payload = (Call::Bridge(call), extra, additional);
+        let signature = raw_payload.using_encoded(|payload| {
+            if payload.len() > 256 {
+                self.pair.sign(&blake2_256(payload)[..])
+            } else {
+                self.pair.sign(payload)
+            }
+        });
+        let (function, extra, _) = raw_payload;
+        let extrinsic =
+            UncheckedExtrinsic::new_signed(function, Address::Id(account), signature.into(), extra);
+        self.rpc.call(
+            "author_submitExtrinsic",
+            vec![json!(Bytes(extrinsic.encode()))],
+        )
+    }
+}
+
+fn storage_key(module: &[u8], item: &[u8]) -> Vec<u8> {
+    let mut key = twox_128(module).to_vec();
+    key.extend_from_slice(&twox_128(item));
+    key
+}
+
+fn blake2_128_concat(data: &[u8]) -> Vec<u8> {
+    let mut key = blake2_128(data).to_vec();
+    key.extend_from_slice(data);
+    key
+}
@@ -0,0 +1,405 @@
+//! RPC interface for the bridge pallet.
+
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use akropolisos_runtime::{
+    api::BridgeApi as BridgeRuntimeApi, ChainId, Status, TokenId, TransferMessage,
+};
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Bytes, H160};
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+/// Validator's signature of the withdrawal to be submitted to the bridge contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationInfo<AccountId> {
+    /// Validator approved the withdrawal.
+    pub validator: AccountId,
+    /// Ethereum address of the validator's key.
+    pub signer: H160,
+    /// 65 bytes `r`, `s`, `v` signature of the `ApprovedRelayMessage` payload.
+    pub signature: Bytes,
+}
+
+/// Bridge transfer between the chains.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(
+    serialize = "AccountId: Serialize, Hash: Serialize, Balance: Display",
+    deserialize = "AccountId: Deserialize<'de>, Hash: Deserialize<'de>, Balance: FromStr"
+))]
+pub struct TransferSummary<AccountId, Hash, Balance> {
+    /// Message id validators vote for.
+    pub message_id: Hash,
+    /// Counterparty chain of the transfer.
+    pub chain_id: ChainId,
+    pub token_id: TokenId,
+    /// `Deposit` for transfers to this chain, `Withdraw` for transfers from it.
+    pub direction: String,
+    /// Sender of deposits or recipient of withdrawals on the counterparty chain.
+    pub eth_address: H160,
+    /// Recipient of deposits or sender of withdrawals on this chain.
+    pub account: AccountId,
+    /// Amount in the smallest units, as a decimal string.
+    #[serde(with = "crate::as_string")]
+    pub amount: Balance,
+    /// Current status, e.g. `Pending` or `Approved`.
+    pub status: String,
+}
+
+/// Status of the transfer set at the block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChange<BlockNumber> {
+    pub status: String,
+    pub block: BlockNumber,
+}
+
+/// Bridge transfer with its votes and status history.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(
+    serialize = "AccountId: Serialize, Hash: Serialize, Balance: Display, BlockNumber: Serialize",
+    deserialize = "AccountId: Deserialize<'de>, Hash: Deserialize<'de>, Balance: FromStr, \
+                   BlockNumber: Deserialize<'de>"
+))]
+pub struct TransferDetails<AccountId, Hash, Balance, BlockNumber> {
+    #[serde(flatten)]
+    pub transfer: TransferSummary<AccountId, Hash, Balance>,
+    /// Transfer is open for validators votes.
+    pub open: bool,
+    /// Votes for the current status.
+    pub votes: u64,
+    /// Votes needed to change the status.
+    pub quorum: u64,
+    /// Block the unfinished transfer expires at.
+    pub deadline: Option<BlockNumber>,
+    /// Statuses in the order they were set.
+    pub history: Vec<StatusChange<BlockNumber>>,
+}
+
+/// Limits of the bridged token along with its current pending volumes,
+/// all amounts are decimal strings.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(serialize = "Balance: Display", deserialize = "Balance: FromStr"))]
+pub struct LimitsInfo<Balance> {
+    #[serde(with = "crate::as_string")]
+    pub max_tx_value: Balance,
+    /// Volume of all transfers within the rolling limit window.
+    #[serde(with = "crate::as_string")]
+    pub window_max_limit: Balance,
+    /// Volume of transfers of one account within the rolling limit window.
+    #[serde(with = "crate::as_string")]
+    pub window_max_limit_for_one_address: Balance,
+    #[serde(with = "crate::as_string")]
+    pub max_pending_tx_limit: Balance,
+    #[serde(with = "crate::as_string")]
+    pub min_tx_value: Balance,
+    /// Volume of withdrawals waiting for validators.
+    #[serde(with = "crate::as_string")]
+    pub pending_burn: Balance,
+    /// Volume of deposits waiting for validators.
+    #[serde(with = "crate::as_string")]
+    pub pending_mint: Balance,
+}
+
+/// Bridge RPC methods.
+#[rpc]
+pub trait BridgeApi<BlockHash, AccountId, Hash, Balance: Display, BlockNumber> {
+    /// Validators signatures of the withdrawal in the order of approval.
+    #[rpc(name = "bridge_attestations")]
+    fn attestations(
+        &self,
+        message_id: Hash,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<AttestationInfo<AccountId>>>;
+
+    /// Transfer by message id with its votes and status history.
+    #[rpc(name = "bridge_transfer")]
+    fn transfer(
+        &self,
+        message_id: Hash,
+        at: Option<BlockHash>,
+    ) -> Result<Option<TransferDetails<AccountId, Hash, Balance, BlockNumber>>>;
+
+    /// Transfers of the token neither confirmed nor canceled yet.
+    #[rpc(name = "bridge_pendingTransfers")]
+    fn pending_transfers(
+        &self,
+        token_id: TokenId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<TransferSummary<AccountId, Hash, Balance>>>;
+
+    /// Unfinished transfers of the account in all tokens.
+    #[rpc(name = "bridge_accountPendingTransfers")]
+    fn account_pending_transfers(
+        &self,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<TransferSummary<AccountId, Hash, Balance>>>;
+
+    /// Current limits and pending volumes of the token bridged to the chain.
+    #[rpc(name = "bridge_limits")]
+    fn limits(
+        &self,
+        chain_id: ChainId,
+        token_id: TokenId,
+        at: Option<BlockHash>,
+    ) -> Result<LimitsInfo<Balance>>;
+
+    /// Amount the account can still transfer within the rolling limit window
+    /// as a decimal string, transfers should be below it.
+    #[rpc(name = "bridge_remainingAllowance")]
+    fn remaining_allowance(
+        &self,
+        chain_id: ChainId,
+        token_id: TokenId,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<String>;
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+    /// The call to runtime failed.
+    RuntimeError,
+}
+
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::RuntimeError => 1,
+        }
+    }
+}
+
+/// An implementation of bridge specific RPC methods.
+pub struct Bridge<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Bridge<C, B> {
+    /// Create new `Bridge` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Bridge {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block> Bridge<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+{
+    fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+        BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+    }
+}
+
+fn status_name(status: &Status) -> String {
+    format!("{:?}", status)
+}
+
+fn summary<AccountId, Hash, Balance>(
+    message: TransferMessage<AccountId, Hash, Balance>,
+) -> TransferSummary<AccountId, Hash, Balance> {
+    TransferSummary {
+        message_id: message.message_id,
+        chain_id: message.chain,
+        token_id: message.token,
+        direction: status_name(&message.action),
+        eth_address: message.eth_address,
+        account: message.substrate_address,
+        amount: message.amount,
+        status: status_name(&message.status),
+    }
+}
+
+fn runtime_error<E: std::fmt::Debug>(e: E) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(Error::RuntimeError.into()),
+        message: "Unable to query bridge data.".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, Block, AccountId, Hash, Balance, BlockNumber>
+    BridgeApi<<Block as BlockT>::Hash, AccountId, Hash, Balance, BlockNumber> for Bridge<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: BridgeRuntimeApi<Block, AccountId, Hash, Balance, BlockNumber>,
+    AccountId: Codec,
+    Hash: Codec,
+    Balance: Codec + Display,
+    BlockNumber: Codec,
+{
+    fn attestations(
+        &self,
+        message_id: Hash,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<AttestationInfo<AccountId>>> {
+        let at = self.block_id(at);
+        let attestations = self
+            .client
+            .runtime_api()
+            .attestations(&at, message_id)
+            .map_err(runtime_error)?;
+
+        Ok(attestations
+            .into_iter()
+            .map(|a| AttestationInfo {
+                validator: a.validator,
+                signer: a.signer,
+                signature: Bytes(AsRef::<[u8]>::as_ref(&a.signature).to_vec()),
+            })
+            .collect())
+    }
+
+    fn transfer(
+        &self,
+        message_id: Hash,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<TransferDetails<AccountId, Hash, Balance, BlockNumber>>> {
+        let at = self.block_id(at);
+        let info = self
+            .client
+            .runtime_api()
+            .transfer(&at, message_id)
+            .map_err(runtime_error)?;
+
+        Ok(info.map(|info| TransferDetails {
+            transfer: summary(info.message),
+            open: info.open,
+            votes: info.votes,
+            quorum: info.quorum,
+            deadline: info.deadline,
+            history: info
+                .history
+                .into_iter()
+                .map(|(status, block)| StatusChange {
+                    status: status_name(&status),
+                    block,
+                })
+                .collect(),
+        }))
+    }
+
+    fn pending_transfers(
+        &self,
+        token_id: TokenId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<TransferSummary<AccountId, Hash, Balance>>> {
+        let at = self.block_id(at);
+        let messages = self
+            .client
+            .runtime_api()
+            .pending_transfers(&at, token_id)
+            .map_err(runtime_error)?;
+        Ok(messages.into_iter().map(summary).collect())
+    }
+
+    fn account_pending_transfers(
+        &self,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<TransferSummary<AccountId, Hash, Balance>>> {
+        let at = self.block_id(at);
+        let messages = self
+            .client
+            .runtime_api()
+            .account_pending_transfers(&at, account)
+            .map_err(runtime_error)?;
+        Ok(messages.into_iter().map(summary).collect())
+    }
+
+    fn limits(
+        &self,
+        chain_id: ChainId,
+        token_id: TokenId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<LimitsInfo<Balance>> {
+        let at = self.block_id(at);
+        let api = self.client.runtime_api();
+        let limits = api.limits(&at, chain_id, token_id).map_err(runtime_error)?;
+        let (pending_burn, pending_mint) = api
+            .pending_volumes(&at, chain_id, token_id)
+            .map_err(runtime_error)?;
+
+        Ok(LimitsInfo {
+            max_tx_value: limits.max_tx_value,
+            window_max_limit: limits.day_max_limit,
+            window_max_limit_for_one_address: limits.day_max_limit_for_one_address,
+            max_pending_tx_limit: limits.max_pending_tx_limit,
+            min_tx_value: limits.min_tx_value,
+            pending_burn,
+            pending_mint,
+        })
+    }
+
+    fn remaining_allowance(
+        &self,
+        chain_id: ChainId,
+        token_id: TokenId,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<String> {
+        let at = self.block_id(at);
+        self.client
+            .runtime_api()
+            .remaining_allowance(&at, chain_id, token_id, account)
+            .map(|allowance| allowance.to_string())
+            .map_err(runtime_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpc_core::serde_json;
+
+    #[test]
+    fn amounts_should_be_serialized_as_strings() {
+        let amount = u64::max_value() as u128 * 1000;
+        let transfer = TransferSummary {
+            message_id: 1u64,
+            chain_id: 0,
+            token_id: 0,
+            direction: "Withdraw".into(),
+            eth_address: H160::zero(),
+            account: 2u64,
+            amount,
+            status: "Pending".into(),
+        };
+        let json = serde_json::to_value(&transfer).unwrap();
+        assert_eq!(json["amount"], "18446744073709551615000");
+        let decoded: TransferSummary<u64, u64, u128> = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, transfer);
+
+        let limits = LimitsInfo {
+            max_tx_value: amount,
+            window_max_limit: 1000u128,
+            window_max_limit_for_one_address: 300,
+            max_pending_tx_limit: 500,
+            min_tx_value: 1,
+            pending_burn: 0,
+            pending_mint: 0,
+        };
+        let json = serde_json::to_value(&limits).unwrap();
+        assert_eq!(json["maxTxValue"], "18446744073709551615000");
+        assert_eq!(json["windowMaxLimit"], "1000");
+        assert_eq!(json["windowMaxLimitForOneAddress"], "300");
+        let decoded: LimitsInfo<u128> = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, limits);
+    }
+}
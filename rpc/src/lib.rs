@@ -14,7 +14,7 @@
 
 use std::{fmt, sync::Arc};
 
-use akropolisos_runtime::{opaque::PrimitiveBlock as Block, BlockNumber, AccountId, Index, Balance};
+use akropolisos_runtime::{opaque::PrimitiveBlock as Block, BlockNumber, AccountId, Index, Balance, Hash};
 use sc_consensus_babe::{Config, Epoch};
 use sc_consensus_babe_rpc::BabeRPCHandler;
 use sc_consensus_epochs::SharedEpochChanges;
@@ -25,6 +25,26 @@ use sp_consensus::SelectChain;
 use sp_consensus_babe::BabeApi;
 use sp_transaction_pool::TransactionPool;
 
+pub mod bridge;
+pub mod token;
+
+/// (De)serializes balances as decimal strings, JSON numbers lose `u128` values above `u64::MAX`.
+pub mod as_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::{fmt::Display, str::FromStr};
+
+    /// Serialize the value as a decimal string.
+    pub fn serialize<S: Serializer, T: Display>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    /// Deserialize the value from a decimal string.
+    pub fn deserialize<'de, D: Deserializer<'de>, T: FromStr>(deserializer: D) -> Result<T, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(|_| D::Error::custom("invalid decimal number"))
+    }
+}
+
 /// Light client extra dependencies.
 pub struct LightDeps<C, F, P> {
     /// The client instance to use.
@@ -69,6 +89,8 @@ where
     C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber>,
     C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance, sp_runtime::OpaqueExtrinsic>,
     C::Api: BabeApi<Block>,
+    C::Api: akropolisos_runtime::api::TokenApi<Block, AccountId, Balance>,
+    C::Api: akropolisos_runtime::api::BridgeApi<Block, AccountId, Hash, Balance, BlockNumber>,
     <C::Api as sp_api::ApiErrorExt>::Error: fmt::Debug,
     P: TransactionPool + 'static,
     M: jsonrpc_core::Metadata + Default,
@@ -77,6 +99,8 @@ where
     use pallet_contracts_rpc::{Contracts, ContractsApi};
     use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
     use substrate_frame_rpc_system::{FullSystem, SystemApi};
+    use bridge::{Bridge, BridgeApi};
+    use token::{Token, TokenApi};
 
     let mut io = jsonrpc_core::IoHandler::default();
     let FullDeps {
@@ -102,6 +126,8 @@ where
     io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
         client.clone(),
     )));
+    io.extend_with(TokenApi::to_delegate(Token::new(client.clone())));
+    io.extend_with(BridgeApi::to_delegate(Bridge::new(client.clone())));
     io.extend_with(sc_consensus_babe_rpc::BabeApi::to_delegate(
         BabeRPCHandler::new(
             client,
@@ -0,0 +1,419 @@
+//! RPC interface for the token pallet.
+
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use akropolisos_runtime::{api::TokenApi as TokenRuntimeApi, SnapshotId, TokenId};
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+/// Token description with human readable symbol.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenInfo {
+    /// Token id.
+    pub id: TokenId,
+    /// Number of decimals used to format amounts.
+    pub decimals: u16,
+    /// Token symbol.
+    pub symbol: String,
+}
+
+/// Raw amount of tokens along with the amount formatted by token decimals.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[serde(bound(serialize = "Balance: Display", deserialize = "Balance: FromStr"))]
+pub struct TokenAmount<Balance> {
+    /// Amount in the smallest units, as a decimal string.
+    #[serde(with = "crate::as_string")]
+    pub amount: Balance,
+    /// Amount divided by `10^decimals`, e.g. `1.5` for `1500000000000000000` with 18 decimals.
+    pub formatted: String,
+}
+
+/// Token RPC methods.
+#[rpc]
+pub trait TokenApi<BlockHash, AccountId, Balance: Display> {
+    /// List all registered tokens.
+    #[rpc(name = "token_tokens")]
+    fn tokens(&self, at: Option<BlockHash>) -> Result<Vec<TokenInfo>>;
+
+    /// Total balance of the account, including locked funds.
+    #[rpc(name = "token_balance")]
+    fn balance(
+        &self,
+        token_id: TokenId,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<TokenAmount<Balance>>;
+
+    /// Balance of the account available for transfers.
+    #[rpc(name = "token_freeBalance")]
+    fn free_balance(
+        &self,
+        token_id: TokenId,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<TokenAmount<Balance>>;
+
+    /// Balance of the account locked by the bridge.
+    #[rpc(name = "token_lockedBalance")]
+    fn locked_balance(
+        &self,
+        token_id: TokenId,
+        account: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<TokenAmount<Balance>>;
+
+    /// Amount `spender` is allowed to transfer from `owner`.
+    #[rpc(name = "token_allowance")]
+    fn allowance(
+        &self,
+        token_id: TokenId,
+        owner: AccountId,
+        spender: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<TokenAmount<Balance>>;
+
+    /// Total supply of the token.
+    #[rpc(name = "token_totalSupply")]
+    fn total_supply(
+        &self,
+        token_id: TokenId,
+        at: Option<BlockHash>,
+    ) -> Result<TokenAmount<Balance>>;
+
+    /// Accounts holding the token with their balances.
+    #[rpc(name = "token_holders")]
+    fn holders(
+        &self,
+        token_id: TokenId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(AccountId, TokenAmount<Balance>)>>;
+
+    /// Balance of the account at the snapshot.
+    #[rpc(name = "token_balanceAt")]
+    fn balance_at(
+        &self,
+        token_id: TokenId,
+        account: AccountId,
+        snapshot_id: SnapshotId,
+        at: Option<BlockHash>,
+    ) -> Result<TokenAmount<Balance>>;
+
+    /// Total supply of the token at the snapshot.
+    #[rpc(name = "token_totalSupplyAt")]
+    fn total_supply_at(
+        &self,
+        token_id: TokenId,
+        snapshot_id: SnapshotId,
+        at: Option<BlockHash>,
+    ) -> Result<TokenAmount<Balance>>;
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+    /// The call to runtime failed.
+    RuntimeError,
+    /// Requested token is not registered.
+    UnknownToken,
+    /// Requested snapshot was not taken.
+    UnknownSnapshot,
+}
+
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::RuntimeError => 1,
+            Error::UnknownToken => 2,
+            Error::UnknownSnapshot => 3,
+        }
+    }
+}
+
+/// An implementation of token specific RPC methods.
+pub struct Token<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Token<C, B> {
+    /// Create new `Token` with the given reference to the client.
+    pub fn new(client: Arc<C>) -> Self {
+        Token {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Format `amount` as a decimal number with `decimals` digits after the point.
+pub fn format_amount<Balance: Display>(amount: &Balance, decimals: u16) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+fn runtime_error<E: std::fmt::Debug>(e: E) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(Error::RuntimeError.into()),
+        message: "Unable to query token data.".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+fn unknown_snapshot(snapshot_id: SnapshotId) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(Error::UnknownSnapshot.into()),
+        message: "Snapshot does not exist.".into(),
+        data: Some(snapshot_id.into()),
+    }
+}
+
+impl<C, Block> Token<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+{
+    fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+        BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+    }
+}
+
+impl<C, Block, AccountId, Balance> Token<C, Block>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: TokenRuntimeApi<Block, AccountId, Balance>,
+    AccountId: Codec,
+    Balance: Codec + Display,
+{
+    fn decimals(&self, at: &BlockId<Block>, token_id: TokenId) -> Result<u16> {
+        self.client
+            .runtime_api()
+            .tokens(at)
+            .map_err(runtime_error)?
+            .into_iter()
+            .find(|t| t.id == token_id)
+            .map(|t| t.decimals)
+            .ok_or_else(|| RpcError {
+                code: ErrorCode::ServerError(Error::UnknownToken.into()),
+                message: "Token is not registered.".into(),
+                data: Some(token_id.into()),
+            })
+    }
+
+    fn with_decimals(
+        &self,
+        at: &BlockId<Block>,
+        token_id: TokenId,
+        amount: Balance,
+    ) -> Result<TokenAmount<Balance>> {
+        let decimals = self.decimals(at, token_id)?;
+        Ok(TokenAmount {
+            formatted: format_amount(&amount, decimals),
+            amount,
+        })
+    }
+}
+
+impl<C, Block, AccountId, Balance> TokenApi<<Block as BlockT>::Hash, AccountId, Balance>
+    for Token<C, Block>
+where
+    Block: BlockT,
+    C: Send + Sync + 'static,
+    C: ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+    C::Api: TokenRuntimeApi<Block, AccountId, Balance>,
+    AccountId: Codec,
+    Balance: Codec + Display,
+{
+    fn tokens(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<TokenInfo>> {
+        let at = self.block_id(at);
+        let tokens = self
+            .client
+            .runtime_api()
+            .tokens(&at)
+            .map_err(runtime_error)?;
+
+        Ok(tokens
+            .into_iter()
+            .map(|t| TokenInfo {
+                id: t.id,
+                decimals: t.decimals,
+                symbol: String::from_utf8_lossy(&t.symbol).into_owned(),
+            })
+            .collect())
+    }
+
+    fn balance(
+        &self,
+        token_id: TokenId,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TokenAmount<Balance>> {
+        let at = self.block_id(at);
+        let amount = self
+            .client
+            .runtime_api()
+            .balance(&at, token_id, account)
+            .map_err(runtime_error)?;
+        self.with_decimals(&at, token_id, amount)
+    }
+
+    fn free_balance(
+        &self,
+        token_id: TokenId,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TokenAmount<Balance>> {
+        let at = self.block_id(at);
+        let amount = self
+            .client
+            .runtime_api()
+            .free_balance(&at, token_id, account)
+            .map_err(runtime_error)?;
+        self.with_decimals(&at, token_id, amount)
+    }
+
+    fn locked_balance(
+        &self,
+        token_id: TokenId,
+        account: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TokenAmount<Balance>> {
+        let at = self.block_id(at);
+        let amount = self
+            .client
+            .runtime_api()
+            .locked_balance(&at, token_id, account)
+            .map_err(runtime_error)?;
+        self.with_decimals(&at, token_id, amount)
+    }
+
+    fn allowance(
+        &self,
+        token_id: TokenId,
+        owner: AccountId,
+        spender: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TokenAmount<Balance>> {
+        let at = self.block_id(at);
+        let amount = self
+            .client
+            .runtime_api()
+            .allowance(&at, token_id, owner, spender)
+            .map_err(runtime_error)?;
+        self.with_decimals(&at, token_id, amount)
+    }
+
+    fn total_supply(
+        &self,
+        token_id: TokenId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TokenAmount<Balance>> {
+        let at = self.block_id(at);
+        let amount = self
+            .client
+            .runtime_api()
+            .total_supply(&at, token_id)
+            .map_err(runtime_error)?;
+        self.with_decimals(&at, token_id, amount)
+    }
+
+    fn holders(
+        &self,
+        token_id: TokenId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(AccountId, TokenAmount<Balance>)>> {
+        let at = self.block_id(at);
+        let holders = self
+            .client
+            .runtime_api()
+            .holders(&at, token_id)
+            .map_err(runtime_error)?;
+        let decimals = self.decimals(&at, token_id)?;
+        Ok(holders
+            .into_iter()
+            .map(|(account, amount)| {
+                let formatted = format_amount(&amount, decimals);
+                (account, TokenAmount { amount, formatted })
+            })
+            .collect())
+    }
+
+    fn balance_at(
+        &self,
+        token_id: TokenId,
+        account: AccountId,
+        snapshot_id: SnapshotId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TokenAmount<Balance>> {
+        let at = self.block_id(at);
+        let amount = self
+            .client
+            .runtime_api()
+            .balance_at(&at, token_id, account, snapshot_id)
+            .map_err(runtime_error)?
+            .ok_or_else(|| unknown_snapshot(snapshot_id))?;
+        self.with_decimals(&at, token_id, amount)
+    }
+
+    fn total_supply_at(
+        &self,
+        token_id: TokenId,
+        snapshot_id: SnapshotId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<TokenAmount<Balance>> {
+        let at = self.block_id(at);
+        let amount = self
+            .client
+            .runtime_api()
+            .total_supply_at(&at, token_id, snapshot_id)
+            .map_err(runtime_error)?
+            .ok_or_else(|| unknown_snapshot(snapshot_id))?;
+        self.with_decimals(&at, token_id, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_amount_works() {
+        assert_eq!(format_amount(&1_500_000_000_000_000_000u128, 18), "1.5");
+        assert_eq!(format_amount(&1_000_000_000_000_000_000u128, 18), "1");
+        assert_eq!(format_amount(&15u128, 18), "0.000000000000000015");
+        assert_eq!(format_amount(&0u128, 18), "0");
+        assert_eq!(format_amount(&42u128, 0), "42");
+    }
+
+    #[test]
+    fn amount_above_u64_should_be_serialized_as_string() {
+        let amount = TokenAmount {
+            amount: u64::max_value() as u128 * 1000,
+            formatted: format_amount(&(u64::max_value() as u128 * 1000), 18),
+        };
+        let json = jsonrpc_core::serde_json::to_string(&amount).unwrap();
+        assert_eq!(
+            json,
+            r#"{"amount":"18446744073709551615000","formatted":"18446.744073709551615"}"#
+        );
+        let decoded: TokenAmount<u128> = jsonrpc_core::serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, amount);
+    }
+}
@@ -0,0 +1,55 @@
+/// Runtime APIs of the Akropolis pallets
+///
+/// Allow clients to read pallets data without computing hashed storage keys.
+///
+use crate::types::{
+    Attestation, ChainId, Limits, SnapshotId, Token, TokenId, TransferInfo, TransferMessage,
+};
+use codec::Codec;
+use sp_std::prelude::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait TokenApi<AccountId, Balance> where
+        AccountId: Codec,
+        Balance: Codec,
+    {
+        /// All registered tokens
+        fn tokens() -> Vec<Token>;
+        /// Total balance of the account, including locked funds
+        fn balance(token_id: TokenId, account: AccountId) -> Balance;
+        /// Balance available for transfers
+        fn free_balance(token_id: TokenId, account: AccountId) -> Balance;
+        /// Balance locked by the bridge
+        fn locked_balance(token_id: TokenId, account: AccountId) -> Balance;
+        fn allowance(token_id: TokenId, owner: AccountId, spender: AccountId) -> Balance;
+        fn total_supply(token_id: TokenId) -> Balance;
+        /// Accounts with non-zero balance of the token
+        fn holders(token_id: TokenId) -> Vec<(AccountId, Balance)>;
+        /// Balance at the snapshot, None if the snapshot doesn't exist
+        fn balance_at(token_id: TokenId, account: AccountId, snapshot_id: SnapshotId) -> Option<Balance>;
+        /// Total supply at the snapshot, None if the snapshot doesn't exist
+        fn total_supply_at(token_id: TokenId, snapshot_id: SnapshotId) -> Option<Balance>;
+    }
+
+    #[api_version(2)]
+    pub trait BridgeApi<AccountId, Hash, Balance, BlockNumber> where
+        AccountId: Codec,
+        Hash: Codec,
+        Balance: Codec,
+        BlockNumber: Codec,
+    {
+        /// Validators signatures of the withdrawal for the bridge contract
+        fn attestations(message_id: Hash) -> Vec<Attestation<AccountId>>;
+        /// Transfer with its votes and status history, None if it doesn't exist
+        fn transfer(message_id: Hash) -> Option<TransferInfo<AccountId, Hash, Balance, BlockNumber>>;
+        /// Transfers of the token neither confirmed nor canceled
+        fn pending_transfers(token_id: TokenId) -> Vec<TransferMessage<AccountId, Hash, Balance>>;
+        /// Unfinished transfers of the account
+        fn account_pending_transfers(account: AccountId) -> Vec<TransferMessage<AccountId, Hash, Balance>>;
+        fn limits(chain_id: ChainId, token_id: TokenId) -> Limits<Balance>;
+        /// Volumes of pending withdrawals and deposits
+        fn pending_volumes(chain_id: ChainId, token_id: TokenId) -> (Balance, Balance);
+        /// Amount the account can still transfer within the rolling limit window
+        fn remaining_allowance(chain_id: ChainId, token_id: TokenId, account: AccountId) -> Balance;
+    }
+}
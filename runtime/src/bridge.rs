@@ -7,26 +7,90 @@
 ///      1 - cDAI
 ///      2 - USDT
 ///      3 - USDC
+///      NATIVE_TOKEN_ID - AKRO in balances module
 ///
 use crate::token;
 use crate::types::*;
-use codec::Encode;
+use codec::{Decode, Encode};
 use frame_support::{
-    decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure, fail,
-    weights::SimpleDispatchInfo, StorageMap, StorageValue,
+    debug, decl_event, decl_module, decl_storage,
+    dispatch::DispatchResult,
+    ensure, fail,
+    storage::{migration::take_storage_value, unhashed},
+    traits::{
+        Currency, EnsureOrigin, ExistenceRequirement, Get, Imbalance, OnUnbalanced,
+        ReservableCurrency,
+    },
+    weights::{DispatchClass, FunctionOf, SimpleDispatchInfo, Weight},
+    IterableStorageDoubleMap, IterableStorageMap, StorageDoubleMap, StorageMap, StorageValue,
 };
-use num_traits::ops::checked::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub};
+use num_traits::ops::checked::{CheckedAdd, CheckedSub};
 use num_traits::Bounded;
-use sp_core::H160;
-use sp_runtime::traits::Hash;
+use simple_json::{self, json::JsonValue};
+use sp_core::{crypto::KeyTypeId, ecdsa, H160};
+use sp_io::{
+    crypto::secp256k1_ecdsa_recover,
+    hashing::{keccak_256, twox_128},
+};
+use sp_runtime::{
+    offchain::{http, storage::StorageValueRef, Duration, StorageKind},
+    traits::{AccountIdConversion, Hash, One, Saturating, Zero},
+    DispatchError, ModuleId, Permill, SaturatedConversion,
+};
 use sp_std::prelude::Vec;
-use system::{self, ensure_signed};
+use system::{self, ensure_root, ensure_signed};
 
 type Result<T> = core::result::Result<T, &'static str>;
+type NegativeImbalanceOf<T> =
+    <balances::Module<T> as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
 
 const MAX_VALIDATORS: u32 = 100_000;
-const DAY_IN_BLOCKS: u32 = 14_400;
-const DAY: u32 = 86_400;
+// expired transfers cancelled in one block, the rest is moved to the next block
+const MAX_EXPIRED_PER_BLOCK: usize = 100;
+// closed messages pruned in one block, the rest waits for the next blocks
+const MAX_PRUNED_PER_BLOCK: u64 = 50;
+// transfers and account entries migrated in one block
+const MIGRATED_PER_BLOCK: u64 = 500;
+const MAX_FEE_BPS: u32 = 10_000;
+const MAX_BATCH_SIZE: usize = 100;
+const VOTE_WEIGHT: Weight = 10_000;
+// prefix added by `eth_sign` to the signed hash
+const ETH_SIGNED_MESSAGE_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n32";
+// account holding the native currency bridged to Ethereum
+const BRIDGE_MODULE_ID: ModuleId = ModuleId(*b"akro/brg");
+
+// offchain local storage keys followed by the chain id:
+// Ethereum JSON-RPC endpoint set by the node operator
+const OFFCHAIN_RPC_URL: &[u8] = b"bridge::rpc_url::";
+// last Ethereum block relayed by the offchain worker
+const OFFCHAIN_LAST_BLOCK: &[u8] = b"bridge::last_block::";
+const OFFCHAIN_CONFIRMATIONS: u64 = 12;
+const OFFCHAIN_MAX_BLOCKS: u64 = 1_000;
+const OFFCHAIN_TIMEOUT_MS: u64 = 10_000;
+const RELAY_MESSAGE_EVENT: &[u8] = b"RelayMessage(bytes32,address,bytes32,uint256)";
+const WITHDRAW_MESSAGE_EVENT: &[u8] = b"WithdrawMessage(bytes32)";
+
+/// Key type validators sign offchain worker transactions with, the same key is
+/// used by the external relayer.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"brdg");
+
+pub mod crypto {
+    pub use super::KEY_TYPE;
+    use sp_runtime::app_crypto::{app_crypto, sr25519};
+    app_crypto!(sr25519, KEY_TYPE);
+}
+
+/// Signs calls of the offchain worker with the local bridge key and sends them to the pool.
+pub trait SubmitSignedCall<T: Trait> {
+    /// Returns false if the node has no bridge key.
+    fn submit_signed(call: Call<T>) -> bool;
+}
+
+impl<T: Trait> SubmitSignedCall<T> for () {
+    fn submit_signed(_call: Call<T>) -> bool {
+        false
+    }
+}
 
 decl_event!(
     pub enum Event<T>
@@ -35,6 +99,7 @@ decl_event!(
         Hash = <T as system::Trait>::Hash,
         Balance = <T as balances::Trait>::Balance,
         Moment = <T as timestamp::Trait>::Moment,
+        BlockNumber = <T as system::Trait>::BlockNumber,
     {
         RelayMessage(Hash),
         ApprovedRelayMessage(Hash, TokenId, AccountId, H160, Balance),
@@ -43,11 +108,63 @@ decl_event!(
         BurnedMessage(Hash, TokenId, AccountId, H160, Balance),
         AccountPausedMessage(Hash, AccountId, Moment, TokenId),
         AccountResumedMessage(Hash, AccountId, Moment, TokenId),
+        ValidatorSetChanged(Hash, u64, Vec<AccountId>),
+        ExpiredTransferMessage(Hash, TokenId),
+        FeeChanged(TokenId, Balance, u32),
+        FeeCharged(Hash, TokenId, AccountId, Balance),
+        FeeRefunded(Hash, TokenId, AccountId, Balance),
+        FeeDistributed(Hash, TokenId, Balance),
+        FeeClaimed(AccountId, TokenId, Balance),
+        ChainRegistered(ChainId, H160),
+        ForcedPause,
+        ForcedResume,
+        ForcedValidatorSet(u64, Vec<AccountId>),
+        ForcedLimits(ChainId, TokenId),
+        BatchVoteSucceeded(AccountId, u32),
+        BatchVoteFailed(AccountId, u32, DispatchError),
+        ValidatorBonded(AccountId, Balance),
+        ValidatorUnbonded(AccountId, Balance),
+        ValidatorSlashed(Hash, AccountId, Balance),
+        EthSignerSet(AccountId, H160),
+        TransferAttested(Hash, AccountId, H160),
+        RetentionChanged(BlockNumber),
+        ReviewThresholdChanged(TokenId, Option<Balance>),
+        AccountListChanged(AccountId, Option<AccountList>),
+        TransferHeld(Hash, TokenId, AccountId, Balance),
+        TransferReleased(Hash),
+        TransferRejected(Hash),
+        MessageCreated(Hash, Kind),
+        // message id, validator, counted votes and the quorum
+        VoteCast(Hash, AccountId, MemberId, u64),
+        StatusChanged(Hash, Kind, Status),
+        LimitsChanged(ChainId, TokenId, Limits<Balance>),
+        BridgePaused(Hash),
+        BridgeResumed(Hash),
     }
 );
 
 pub trait Trait: token::Trait + balances::Trait + system::Trait + timestamp::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    /// Number of blocks after which unfinished transfer is cancelled and its funds are released.
+    /// Should exceed the time needed to confirm the transfer on Ethereum side.
+    type TransferTtl: Get<Self::BlockNumber>;
+    /// Length in blocks of the rolling window of per account volume limits.
+    /// Withdrawals of 75% of the balance at most are allowed within this window after
+    /// the first deposit.
+    type LimitWindow: Get<Self::BlockNumber>;
+    /// Amount of native currency reserved by each validator.
+    type ValidatorBond: Get<Self::Balance>;
+    /// Handler for the slashed bonds of misbehaving validators.
+    type Slash: OnUnbalanced<NegativeImbalanceOf<Self>>;
+    /// Account receiving fees of transfers no validator voted for, such as proven mints.
+    type TreasuryAccount: Get<Self::AccountId>;
+    /// Origin which can pause and resume the bridge, replace validators and set limits
+    /// without validators votes.
+    type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+    /// Origin which reviews held transfers and manages the allow and deny lists.
+    type ComplianceOrigin: EnsureOrigin<Self::Origin>;
+    /// Sender of validator votes for Ethereum events found by the offchain worker.
+    type SubmitTransaction: SubmitSignedCall<Self>;
 }
 
 decl_storage! {
@@ -58,29 +175,90 @@ decl_storage! {
         // limits change history
         LimitMessages get(fn limit_messages): map hasher(opaque_blake2_256) T::Hash  => LimitMessage<T::Hash, T::Balance>;
         CurrentLimits get(fn current_limits) build(|config: &GenesisConfig<T>| {
-            let mut limits_iter = config.current_limits.clone().into_iter();
-            Limits {
-                max_tx_value: limits_iter.next().unwrap(),
-                day_max_limit: limits_iter.next().unwrap(),
-                day_max_limit_for_one_address: limits_iter.next().unwrap(),
-                max_pending_tx_limit: limits_iter.next().unwrap(),
-                min_tx_value: limits_iter.next().unwrap(),
-            }
-        }): Limits<T::Balance>;
+            config.current_limits.clone().into_iter()
+            .map(|(chain_id, token_id, limits): (ChainId, TokenId, Vec<T::Balance>)| {
+                let mut limits_iter = limits.into_iter();
+                (chain_id, token_id, Limits {
+                    max_tx_value: limits_iter.next().unwrap(),
+                    day_max_limit: limits_iter.next().unwrap(),
+                    day_max_limit_for_one_address: limits_iter.next().unwrap(),
+                    max_pending_tx_limit: limits_iter.next().unwrap(),
+                    min_tx_value: limits_iter.next().unwrap(),
+                })
+            }).collect::<Vec<_>>()
+        }): double_map hasher(blake2_128_concat) ChainId, hasher(blake2_128_concat) TokenId => Limits<T::Balance>;
 
         // open transactions
-        CurrentPendingBurn get(fn pending_burn_count): T::Balance;
-        CurrentPendingMint get(fn pending_mint_count): T::Balance;
+        CurrentPendingBurn get(fn pending_burn_count):
+            double_map hasher(blake2_128_concat) ChainId, hasher(blake2_128_concat) TokenId => T::Balance;
+        CurrentPendingMint get(fn pending_mint_count):
+            double_map hasher(blake2_128_concat) ChainId, hasher(blake2_128_concat) TokenId => T::Balance;
+
+        // counterparty chains and tokens bridged to them
+        Chains get(fn chains) build(|config: &GenesisConfig<T>| {
+            config.chains.clone().into_iter()
+            .map(|(chain_id, contract, tokens): (ChainId, H160, Vec<TokenId>)| {
+                (chain_id, Chain { contract, tokens, validators: Vec::new(), quorum: 0 })
+            }).collect::<Vec<_>>()
+        }): map hasher(blake2_128_concat) ChainId => Option<Chain<T::AccountId>>;
+        StorageVersion get(fn storage_version) build(|_| 1u32): u32;
+        StorageMigrationProgress get(fn storage_migration): Option<StorageMigration>;
+
+        // nonces mixed into message ids, see `types`
+        TransferNonces get(fn transfer_nonce): map hasher(blake2_128_concat) T::AccountId => Nonce;
+        KindNonces get(fn kind_nonce): map hasher(blake2_128_concat) Kind => Nonce;
+        LimitsNonces get(fn limits_nonce):
+            double_map hasher(blake2_128_concat) ChainId, hasher(blake2_128_concat) TokenId => Nonce;
 
         BridgeTransfers get(fn transfers): map hasher(opaque_blake2_256) ProposalId => BridgeTransfer<T::Hash>;
         BridgeTransfersCount get(fn bridge_transfers_count): ProposalId;
         TransferMessages get(fn messages): map hasher(opaque_blake2_256) T::Hash  => TransferMessage<T::AccountId, T::Hash, T::Balance>;
         TransferId get(fn transfer_id_by_hash): map hasher(opaque_blake2_256) T::Hash  => ProposalId;
+        // blocks closed messages are kept for, they are never pruned when zero
+        Retention get(fn retention): T::BlockNumber;
+        // closed messages in the order they are pruned: (block closed at, message id, kind)
+        PruneQueue: map hasher(twox_64_concat) u64 => Option<(T::BlockNumber, T::Hash, Kind)>;
+        PruneQueueHead: u64;
+        PruneQueueTail: u64;
+        // ids of pruned messages, they can't be voted for again
+        PrunedMessages get(fn is_pruned): map hasher(blake2_128_concat) T::Hash => bool;
+
+        // transfers of larger amounts are held for the compliance review
+        ReviewThresholds get(fn review_threshold): map hasher(blake2_128_concat) TokenId => Option<T::Balance>;
+        AccountLists get(fn account_list): map hasher(blake2_128_concat) T::AccountId => Option<AccountList>;
+
+        // statuses the transfer went through with the blocks they were set at
+        StatusHistory get(fn status_history): map hasher(blake2_128_concat) T::Hash => Vec<(Status, T::BlockNumber)>;
+        // senders of transfers neither confirmed nor canceled yet
+        PendingTransfers get(fn pending_transfer):
+            double_map hasher(blake2_128_concat) TokenId, hasher(blake2_128_concat) T::Hash => Option<T::AccountId>;
         MessageId get(fn message_id_by_transfer_id): map hasher(opaque_blake2_256) ProposalId  => T::Hash;
 
-        DailyHolds get(fn daily_holds): map hasher(opaque_blake2_256) T::AccountId  => (T::BlockNumber, T::Hash);
-        DailyLimits get(fn daily_limits_by_account): map hasher(opaque_blake2_256) (TokenId, T::AccountId)  => T::Balance;
-        DailyBlocked get(fn daily_blocked): map hasher(opaque_blake2_256) (TokenId, T::Moment)  => Vec<T::AccountId>;
+        // block after which the transfer is expired
+        TransferDeadlines get(fn transfer_deadline): map hasher(opaque_blake2_256) T::Hash => Option<T::BlockNumber>;
+        ExpiringTransfers get(fn expiring_transfers): map hasher(twox_64_concat) T::BlockNumber => Vec<T::Hash>;
+
+        // fees charged for transfers of each token
+        Fees get(fn fees) build(|config: &GenesisConfig<T>| {
+            config.fees.clone().into_iter()
+            .map(|(token_id, flat, bps): (TokenId, T::Balance, u32)| (token_id, BridgeFee { flat, bps }))
+            .collect::<Vec<_>>()
+        }): map hasher(blake2_128_concat) TokenId => BridgeFee<T::Balance>;
+        // fees held until the transfer is finished or charged and not claimed yet
+        FeePot get(fn fee_pot): map hasher(blake2_128_concat) TokenId => T::Balance;
+        TransferFees get(fn transfer_fee): map hasher(opaque_blake2_256) T::Hash => T::Balance;
+        ValidatorRewards get(fn validator_rewards):
+            double_map hasher(blake2_128_concat) TokenId, hasher(blake2_128_concat) T::AccountId => T::Balance;
+
+        // block of the first deposit of each account
+        FirstDeposits get(fn first_deposit): map hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+        // withdrawals volume of the account to the chain in the last two limit windows
+        ChainAccountVolumes get(fn account_volume):
+            double_map hasher(blake2_128_concat) (ChainId, TokenId), hasher(blake2_128_concat) T::AccountId => WindowVolume<T::BlockNumber, T::Balance>;
+        // accounts over the volume limit of the chain are blocked until the block
+        ChainBlockedAccounts get(fn blocked_until):
+            double_map hasher(blake2_128_concat) (ChainId, TokenId), hasher(blake2_128_concat) T::AccountId => Option<T::BlockNumber>;
+        Unblocks get(fn unblocks): map hasher(twox_64_concat) T::BlockNumber => Vec<(ChainId, TokenId, T::AccountId)>;
 
         Quorum get(fn quorum): u64 = 2;
         ValidatorsCount get(fn validators_count) config(): u32 = 3;
@@ -91,10 +269,39 @@ decl_storage! {
             .map(|acc: T::AccountId| (acc, true)).collect::<Vec<_>>()
         }): map hasher(opaque_blake2_256) T::AccountId  => bool;
         ValidatorAccounts get(fn validator_accounts) config(): Vec<T::AccountId>;
+
+        // native currency reserved by validators
+        Bonds get(fn bond_of): map hasher(blake2_128_concat) T::AccountId => T::Balance;
+        // what each validator has signed for the transfer
+        SignedVotes get(fn signed_votes):
+            double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) T::AccountId => Vec<Status>;
+        RejectedMints get(fn rejected_mint): map hasher(blake2_128_concat) T::Hash => bool;
+        Offences get(fn offence):
+            double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) T::AccountId => bool;
+
+        // Ethereum addresses validators sign withdrawals with
+        EthSigners get(fn eth_signer): map hasher(blake2_128_concat) T::AccountId => Option<H160>;
+        Attestations get(fn attestations): map hasher(blake2_128_concat) T::Hash => Vec<Attestation<T::AccountId>>;
     }
 
     add_extra_genesis{
-        config(current_limits): Vec<T::Balance>;
+        // bridge contract and tokens of each chain, all of them use bridge validators
+        config(chains): Vec<(ChainId, H160, Vec<TokenId>)>;
+        // limits of each token of the chain in the order of `Limits` fields
+        config(current_limits): Vec<(ChainId, TokenId, Vec<T::Balance>)>;
+        // flat fee and basis points of each token
+        config(fees): Vec<(TokenId, T::Balance, u32)>;
+
+        build(|config: &GenesisConfig<T>| {
+            // genesis validators without enough funds stay unbonded
+            let bond = T::ValidatorBond::get();
+            for validator in config.validator_accounts.iter() {
+                if <balances::Module<T>>::reserve(validator, bond).is_ok() {
+                    <Bonds<T>>::insert(validator, bond);
+                }
+            }
+            <Module<T>>::endow_account();
+        });
     }
 }
 
@@ -102,19 +309,54 @@ decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
+        // messages and the old daily limits are migrated in chunks by on_finalize,
+        // the bridge is paused until it's done
+        fn on_runtime_upgrade() {
+            Self::endow_account();
+            if Self::storage_version() == 0 {
+                // limits used to be shared by all tokens
+                if let Some(limits) = take_storage_value::<Limits<T::Balance>>(b"Bridge", b"CurrentLimits", &[]) {
+                    for t in <token::Module<T>>::tokens() {
+                        <CurrentLimits<T>>::insert(ETHEREUM_CHAIN_ID, t.id, limits.clone());
+                    }
+                }
+                // pending volumes are summed up again per token from the pending transfers
+                for item in &[&b"CurrentPendingBurn"[..], b"CurrentPendingMint"] {
+                    unhashed::kill(&Self::storage_prefix(item));
+                }
+                Self::migrate_to_chains();
+                <StorageMigrationProgress>::put(StorageMigration {
+                    next: 0,
+                    operational: Self::bridge_is_operational(),
+                });
+                <BridgeIsOperational>::put(false);
+                <StorageVersion>::put(1);
+            }
+        }
+
         // initiate substrate -> ethereum transfer.
         // create transfer and emit the RelayMessage event
         #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
-        pub fn set_transfer(origin, to: H160, token_id: TokenId, #[compact] amount: T::Balance)-> DispatchResult
+        pub fn set_transfer(origin, chain_id: ChainId, to: H160, token_id: TokenId, #[compact] amount: T::Balance)-> DispatchResult
         {
             let from = ensure_signed(origin)?;
             ensure!(Self::bridge_is_operational(), "Bridge is not operational");
 
-            Self::check_amount(amount)?;
-            Self::check_pending_burn(amount)?;
-            Self::check_daily_account_volume(token_id, from.clone(), amount)?;
+            Self::check_chain_token(chain_id, token_id)?;
+            Self::check_amount(chain_id, token_id, amount)?;
+            let held = Self::check_compliance(token_id, &from, amount)?;
+            Self::check_pending_burn(chain_id, token_id, amount)?;
+            Self::check_account_volume(chain_id, token_id, &from, amount)?;
+            Self::check_first_deposit_hold(token_id, &from, amount)?;
+
+            let fee = Self::fee_for(token_id, amount);
+            ensure!(
+                Self::free_balance_of(token_id, &from) >= amount.saturating_add(fee),
+                "Not enough tokens to pay the bridge fee"
+            );
 
-            let transfer_hash = (&from, &to, amount, <timestamp::Module<T>>::get()).using_encoded(<T as system::Trait>::Hashing::hash);
+            let nonce = Self::transfer_nonce(&from);
+            let transfer_hash = (TRANSFER_MESSAGE, chain_id, &from, &to, token_id, amount, nonce).using_encoded(<T as system::Trait>::Hashing::hash);
 
             let message = TransferMessage {
                 message_id: transfer_hash,
@@ -122,26 +364,41 @@ decl_module! {
                 substrate_address: from.clone(),
                 amount,
                 token: token_id,
-                status: Status::Withdraw,
+                status: if held { Status::Held } else { Status::Withdraw },
                 action: Status::Withdraw,
+                chain: chain_id,
             };
             Self::get_transfer_id_checked(transfer_hash, Kind::Transfer)?;
-            Self::deposit_event(RawEvent::RelayMessage(transfer_hash));
+            <TransferNonces<T>>::insert(&from, nonce + 1);
+            Self::deposit_message_event(transfer_hash, RawEvent::RelayMessage(transfer_hash));
+            if held {
+                Self::deposit_message_event(transfer_hash, RawEvent::TransferHeld(transfer_hash, token_id, from.clone(), amount));
+            }
+
+            Self::add_volume(chain_id, token_id, &from, amount);
+            Self::insert_message(message);
+            Self::set_deadline(transfer_hash);
 
-            <DailyLimits<T>>::mutate((token_id, from), |a| *a += amount);
-            <TransferMessages<T>>::insert(transfer_hash, message);
+            if !fee.is_zero() {
+                Self::burn(token_id, from.clone(), fee)?;
+                Self::hold_fee(transfer_hash, token_id, from, fee);
+            }
             Ok(())
         }
 
         // ethereum-side multi-signed mint operation
         #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
-        pub fn multi_signed_mint(origin, message_id: T::Hash, from: H160, to: T::AccountId, token_id: TokenId, #[compact] amount: T::Balance)-> DispatchResult {
+        pub fn multi_signed_mint(origin, chain_id: ChainId, message_id: T::Hash, from: H160, to: T::AccountId, token_id: TokenId, #[compact] amount: T::Balance)-> DispatchResult {
             let validator = ensure_signed(origin)?;
             ensure!(Self::bridge_is_operational(), "Bridge is not operational");
 
-            Self::check_validator(validator.clone())?;
-            Self::check_pending_mint(amount)?;
-            Self::check_amount(amount)?;
+            Self::check_chain_validator(chain_id, &validator)?;
+            Self::check_chain_token(chain_id, token_id)?;
+            Self::check_pending_mint(chain_id, token_id, amount)?;
+            Self::check_amount(chain_id, token_id, amount)?;
+            let held = Self::check_compliance(token_id, &to, amount)?;
+            let message_id = Self::chain_message_id(chain_id, message_id);
+            ensure!(!Self::is_pruned(message_id), "This transfer is already finished");
 
             if !<TransferMessages<T>>::contains_key(message_id) {
                 let message = TransferMessage{
@@ -150,23 +407,33 @@ decl_module! {
                     substrate_address: to,
                     amount,
                     token: token_id,
-                    status: Status::Deposit,
+                    status: if held { Status::Held } else { Status::Deposit },
                     action: Status::Deposit,
+                    chain: chain_id,
                 };
-                <TransferMessages<T>>::insert(message_id, message);
+                Self::insert_message(message);
                 Self::get_transfer_id_checked(message_id, Kind::Transfer)?;
+                // Ethereum funds are locked already, so held deposits wait for the review
+                // and get their deadline when released
+                if held {
+                    Self::deposit_message_event(message_id, RawEvent::TransferHeld(message_id, token_id, to, amount));
+                } else {
+                    Self::set_deadline(message_id);
+                }
             }
+            ensure!(<TransferMessages<T>>::get(message_id).status != Status::Canceled, "This transfer is canceled");
 
             let transfer_id = <TransferId<T>>::get(message_id);
-            Self::_sign(validator, transfer_id)?;
+            Self::_sign(validator.clone(), transfer_id)?;
+            Self::record_vote(message_id, validator, Status::Approved);
             Ok(())
         }
 
-        // change maximum tx limit
+        // change limits of the token
         #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
-        pub fn update_limits(origin, max_tx_value: T::Balance, day_max_limit: T::Balance, day_max_limit_for_one_address: T::Balance, max_pending_tx_limit: T::Balance,min_tx_value: T::Balance)-> DispatchResult {
+        pub fn update_limits(origin, chain_id: ChainId, token_id: TokenId, max_tx_value: T::Balance, day_max_limit: T::Balance, day_max_limit_for_one_address: T::Balance, max_pending_tx_limit: T::Balance,min_tx_value: T::Balance)-> DispatchResult {
             let validator = ensure_signed(origin)?;
-            Self::check_validator(validator.clone())?;
+            Self::check_chain_validator(chain_id, &validator)?;
             let limits = Limits{
                 max_tx_value,
                 day_max_limit,
@@ -175,13 +442,15 @@ decl_module! {
                 min_tx_value,
             };
             Self::check_limits(&limits)?;
-            let id = (limits.clone(), T::BlockNumber::from(0)).using_encoded(<T as system::Trait>::Hashing::hash);
+            let id = (LIMITS_MESSAGE, chain_id, token_id, limits.clone(), Self::limits_nonce(chain_id, token_id)).using_encoded(<T as system::Trait>::Hashing::hash);
 
             if !<LimitMessages<T>>::contains_key(id) {
                 let message = LimitMessage {
                     id,
+                    token: token_id,
                     limits,
                     status: Status::UpdateLimits,
+                    chain: chain_id,
                 };
                 <LimitMessages<T>>::insert(id, message);
                 Self::get_transfer_id_checked(id, Kind::Limits)?;
@@ -192,15 +461,37 @@ decl_module! {
             Ok(())
         }
 
-        // validator`s response to RelayMessage
+        // validator`s response to RelayMessage, signed with validator's Ethereum key
+        // to be relayed to the bridge contract
         #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
-        pub fn approve_transfer(origin, message_id: T::Hash) -> DispatchResult {
+        pub fn approve_transfer(origin, message_id: T::Hash, signature: ecdsa::Signature) -> DispatchResult {
             let validator = ensure_signed(origin)?;
             ensure!(Self::bridge_is_operational(), "Bridge is not operational");
-            Self::check_validator(validator.clone())?;
+            let message = <TransferMessages<T>>::get(message_id);
+            Self::check_chain_validator(message.chain, &validator)?;
+
+            let signer = Self::eth_signer(&validator).ok_or("Validator has no Ethereum signer")?;
+            ensure!(
+                Self::attestation_signer(&message, &signature) == Some(signer),
+                "Invalid attestation signature"
+            );
 
             let id = <TransferId<T>>::get(message_id);
-            Self::_sign(validator, id)?;
+            Self::_sign(validator.clone(), id)?;
+            Self::record_vote(message_id, validator.clone(), Status::Approved);
+            <Attestations<T>>::mutate(message_id, |a| {
+                a.push(Attestation { validator: validator.clone(), signer, signature })
+            });
+            Self::deposit_message_event(message_id, RawEvent::TransferAttested(message_id, validator, signer));
+            Ok(())
+        }
+
+        // Ethereum address the validator signs withdrawals with
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn set_eth_signer(origin, signer: H160) -> DispatchResult {
+            let account = ensure_signed(origin)?;
+            <EthSigners<T>>::insert(&account, signer);
+            Self::deposit_event(RawEvent::EthSignerSet(account, signer));
             Ok(())
         }
 
@@ -209,6 +500,8 @@ decl_module! {
         pub fn update_validator_list(origin, message_id: T::Hash, quorum: u64, new_validator_list: Vec<T::AccountId>) -> DispatchResult {
             let validator = ensure_signed(origin)?;
             Self::check_validator(validator.clone())?;
+            Self::check_validator_set(quorum, &new_validator_list)?;
+            ensure!(!Self::is_pruned(message_id), "This message is already executed");
 
             if !<ValidatorHistory<T>>::contains_key(message_id) {
                 let message = ValidatorMessage {
@@ -234,7 +527,7 @@ decl_module! {
             Self::check_validator(validator.clone())?;
 
             ensure!(Self::bridge_is_operational(), "Bridge is not operational already");
-            let hash = ("pause", T::BlockNumber::from(0)).using_encoded(<T as system::Trait>::Hashing::hash);
+            let hash = (PAUSE_MESSAGE, Self::kind_nonce(Kind::Bridge)).using_encoded(<T as system::Trait>::Hashing::hash);
 
             if !<BridgeMessages<T>>::contains_key(hash) {
                 let message = BridgeMessage {
@@ -257,8 +550,9 @@ decl_module! {
         pub fn resume_bridge(origin) -> DispatchResult {
             let validator = ensure_signed(origin)?;
             Self::check_validator(validator.clone())?;
+            ensure!(Self::storage_migration().is_none(), "Bridge storage is being migrated");
 
-            let hash = ("resume", T::BlockNumber::from(0)).using_encoded(<T as system::Trait>::Hashing::hash);
+            let hash = (RESUME_MESSAGE, Self::kind_nonce(Kind::Bridge)).using_encoded(<T as system::Trait>::Hashing::hash);
 
             if !<BridgeMessages<T>>::contains_key(hash) {
                 let message = BridgeMessage {
@@ -281,7 +575,7 @@ decl_module! {
         pub fn confirm_transfer(origin, message_id: T::Hash) -> DispatchResult {
             let validator = ensure_signed(origin)?;
             ensure!(Self::bridge_is_operational(), "Bridge is not operational");
-            Self::check_validator(validator.clone())?;
+            Self::check_chain_validator(<TransferMessages<T>>::get(message_id).chain, &validator)?;
 
             let id = <TransferId<T>>::get(message_id);
 
@@ -291,7 +585,8 @@ decl_module! {
 
             Self::update_status(message_id, Status::Confirmed, Kind::Transfer)?;
             Self::reopen_for_burn_confirmation(message_id)?;
-            Self::_sign(validator, id)?;
+            Self::_sign(validator.clone(), id)?;
+            Self::record_vote(message_id, validator, Status::Confirmed);
             Ok(())
         }
 
@@ -299,40 +594,319 @@ decl_module! {
         #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
         pub fn cancel_transfer(origin, message_id: T::Hash) -> DispatchResult {
             let validator = ensure_signed(origin)?;
-            Self::check_validator(validator.clone())?;
+            Self::check_chain_validator(<TransferMessages<T>>::get(message_id).chain, &validator)?;
 
             let has_burned = <TransferMessages<T>>::contains_key(message_id) && <TransferMessages<T>>::get(message_id).status == Status::Confirmed;
             ensure!(!has_burned, "Failed to cancel. This transfer is already executed.");
+            ensure!(<TransferMessages<T>>::get(message_id).status != Status::Held, "Transfer is held for the compliance review");
 
             let id = <TransferId<T>>::get(message_id);
+            let message = <TransferMessages<T>>::get(message_id);
+            Self::check_not_attested(&message)?;
+            // votes for the mint are not counted for its rejection
+            let rejects_mint = message.action == Status::Deposit && message.status == Status::Pending;
             Self::update_status(message_id, Status::Canceled, Kind::Transfer)?;
-            Self::reopen_for_burn_confirmation(message_id)?;
-            Self::_sign(validator, id)?;
+            if rejects_mint {
+                Self::reset_votes(id);
+            } else {
+                Self::reopen_for_burn_confirmation(message_id)?;
+            }
+            Self::_sign(validator.clone(), id)?;
+            Self::record_vote(message_id, validator, Status::Canceled);
+            Ok(())
+        }
+
+        // reserve the bond required to join the validator set
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn bond(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let bond = T::ValidatorBond::get();
+            let current = Self::bond_of(&who);
+            ensure!(current < bond, "Already bonded");
+
+            <balances::Module<T>>::reserve(&who, bond - current)?;
+            <Bonds<T>>::insert(&who, bond);
+            Self::deposit_event(RawEvent::ValidatorBonded(who, bond));
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn unbond(origin) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!<Validators<T>>::contains_key(&who), "Validators cannot unbond");
+            ensure!(
+                !<Chains<T>>::iter().any(|(_, chain)| chain.validators.contains(&who)),
+                "Validators cannot unbond"
+            );
+            ensure!(<Bonds<T>>::contains_key(&who), "Not bonded");
+
+            let bond = <Bonds<T>>::take(&who);
+            <balances::Module<T>>::unreserve(&who, bond);
+            Self::deposit_event(RawEvent::ValidatorUnbonded(who, bond));
+            Ok(())
+        }
+
+        // anyone can report the validator who signed conflicting votes for the transfer
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn report_misbehaviour(origin, offender: T::AccountId, message_id: T::Hash) -> DispatchResult {
+            ensure_signed(origin)?;
+            ensure!(<TransferMessages<T>>::contains_key(message_id), "Transfer does not exist");
+            ensure!(!Self::offence(message_id, &offender), "Offence is already reported");
+            ensure!(Self::has_conflicting_votes(message_id, &offender), "No conflicting votes found");
+
+            Self::slash(message_id, offender);
+            Ok(())
+        }
+
+        // several votes in one extrinsic, failed vote doesn't affect the others
+        #[weight = FunctionOf(
+            |args: (&Vec<BridgeVote<T::AccountId, T::Hash, T::Balance>>,)| VOTE_WEIGHT * (args.0.len().max(1) as Weight),
+            DispatchClass::Normal,
+            true
+        )]
+        pub fn batch_sign(origin, votes: Vec<BridgeVote<T::AccountId, T::Hash, T::Balance>>) -> DispatchResult {
+            let validator = ensure_signed(origin)?;
+            ensure!(votes.len() <= MAX_BATCH_SIZE, "Batch is too large");
+
+            for (index, vote) in votes.into_iter().enumerate() {
+                let origin = || -> T::Origin { system::RawOrigin::Signed(validator.clone()).into() };
+                let result = match vote {
+                    BridgeVote::Mint(chain_id, message_id, from, to, token_id, amount) => {
+                        Self::multi_signed_mint(origin(), chain_id, message_id, from, to, token_id, amount)
+                    }
+                    BridgeVote::Approve(message_id, signature) => Self::approve_transfer(origin(), message_id, signature),
+                    BridgeVote::Confirm(message_id) => Self::confirm_transfer(origin(), message_id),
+                    BridgeVote::Cancel(message_id) => Self::cancel_transfer(origin(), message_id),
+                };
+                let event = match result {
+                    Ok(()) => RawEvent::BatchVoteSucceeded(validator.clone(), index as u32),
+                    Err(e) => RawEvent::BatchVoteFailed(validator.clone(), index as u32, e),
+                };
+                Self::deposit_event(event);
+            }
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn force_pause(origin) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(Self::bridge_is_operational(), "Bridge is not operational already");
+
+            <BridgeIsOperational>::put(false);
+            // pending votes of validators are not valid anymore
+            <KindNonces>::mutate(Kind::Bridge, |n| *n += 1);
+            Self::deposit_event(RawEvent::ForcedPause);
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn force_resume(origin) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(!Self::bridge_is_operational(), "Bridge is operational already");
+            ensure!(Self::storage_migration().is_none(), "Bridge storage is being migrated");
+
+            <BridgeIsOperational>::put(true);
+            <KindNonces>::mutate(Kind::Bridge, |n| *n += 1);
+            Self::deposit_event(RawEvent::ForcedResume);
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn force_validator_set(origin, quorum: u64, validators: Vec<T::AccountId>) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            Self::check_validator_set(quorum, &validators)?;
+
+            Self::replace_validators(quorum, validators.clone());
+            Self::deposit_event(RawEvent::ForcedValidatorSet(quorum, validators));
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn force_limits(origin, chain_id: ChainId, token_id: TokenId, max_tx_value: T::Balance, day_max_limit: T::Balance, day_max_limit_for_one_address: T::Balance, max_pending_tx_limit: T::Balance, min_tx_value: T::Balance) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(<Chains<T>>::contains_key(chain_id), "Chain is not registered");
+            let limits = Limits {
+                max_tx_value,
+                day_max_limit,
+                day_max_limit_for_one_address,
+                max_pending_tx_limit,
+                min_tx_value,
+            };
+            Self::check_limits(&limits)?;
+
+            <CurrentLimits<T>>::insert(chain_id, token_id, limits.clone());
+            // pending votes for the limits of the token are not valid anymore
+            <LimitsNonces>::mutate(chain_id, token_id, |n| *n += 1);
+            Self::deposit_event(RawEvent::ForcedLimits(chain_id, token_id));
+            Self::deposit_event(RawEvent::LimitsChanged(chain_id, token_id, limits));
+            Ok(())
+        }
+
+        // add or update counterparty chain, bridge validators vote for it if no validators given
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn register_chain(origin, chain_id: ChainId, contract: H160, tokens: Vec<TokenId>, validators: Vec<T::AccountId>, quorum: u64) -> DispatchResult {
+            ensure_root(origin)?;
+            if !validators.is_empty() {
+                Self::check_validator_set(quorum, &validators)?;
+            }
+
+            <Chains<T>>::insert(chain_id, Chain { contract, tokens, validators, quorum });
+            Self::deposit_event(RawEvent::ChainRegistered(chain_id, contract));
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn set_fee(origin, token_id: TokenId, flat: T::Balance, bps: u32) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            ensure!(bps <= MAX_FEE_BPS, "Fee basis points should not exceed 10000");
+
+            <Fees<T>>::insert(token_id, BridgeFee { flat, bps });
+            Self::deposit_event(RawEvent::FeeChanged(token_id, flat, bps));
+            Ok(())
+        }
+
+        // closed messages are pruned `blocks` after closing, zero keeps them forever
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn set_retention(origin, blocks: T::BlockNumber) -> DispatchResult {
+            T::GovernanceOrigin::ensure_origin(origin)?;
+            // transfers are pruned after their deadlines
+            ensure!(
+                blocks.is_zero() || blocks >= T::TransferTtl::get(),
+                "Retention can't be shorter than the transfer TTL"
+            );
+
+            <Retention<T>>::put(blocks);
+            Self::deposit_event(RawEvent::RetentionChanged(blocks));
+            Ok(())
+        }
+
+        // transfers of the token above the threshold are held, None disables reviews
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn set_review_threshold(origin, token_id: TokenId, threshold: Option<T::Balance>) -> DispatchResult {
+            T::ComplianceOrigin::ensure_origin(origin)?;
+
+            <ReviewThresholds<T>>::mutate(token_id, |t| *t = threshold);
+            Self::deposit_event(RawEvent::ReviewThresholdChanged(token_id, threshold));
+            Ok(())
+        }
+
+        // allowed accounts are never held, denied ones can't use the bridge
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn set_account_list(origin, account: T::AccountId, list: Option<AccountList>) -> DispatchResult {
+            T::ComplianceOrigin::ensure_origin(origin)?;
+
+            <AccountLists<T>>::mutate(&account, |l| *l = list.clone());
+            Self::deposit_event(RawEvent::AccountListChanged(account, list));
+            Ok(())
+        }
+
+        // held transfer is finished at once if validators have voted for it already
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn release_transfer(origin, message_id: T::Hash) -> DispatchResult {
+            T::ComplianceOrigin::ensure_origin(origin)?;
+            ensure!(<TransferMessages<T>>::contains_key(message_id), "Transfer does not exist");
+            let message = <TransferMessages<T>>::get(message_id);
+            ensure!(message.status == Status::Held, "Transfer is not held");
+            if message.action == Status::Deposit {
+                Self::check_mint(message.token, &message.substrate_address, message.amount)?;
+            }
+
+            Self::update_status(message_id, message.action, Kind::Transfer)?;
+            Self::deposit_message_event(message_id, RawEvent::TransferReleased(message_id));
+            Self::execute_released(message_id)?;
+            if message.action == Status::Deposit && !Self::is_finished(message_id) {
+                Self::set_deadline(message_id);
+            }
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn reject_transfer(origin, message_id: T::Hash) -> DispatchResult {
+            T::ComplianceOrigin::ensure_origin(origin)?;
+            ensure!(<TransferMessages<T>>::contains_key(message_id), "Transfer does not exist");
+            ensure!(<TransferMessages<T>>::get(message_id).status == Status::Held, "Transfer is not held");
+
+            Self::cancel_unfinished(message_id)?;
+            Self::deposit_message_event(message_id, RawEvent::TransferRejected(message_id));
+            Ok(())
+        }
+
+        // validator's share of the fees for transfers he voted for
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn claim_fees(origin, token_id: TokenId) -> DispatchResult {
+            let validator = ensure_signed(origin)?;
+            let reward = <ValidatorRewards<T>>::get(token_id, &validator);
+            ensure!(!reward.is_zero(), "Nothing to claim");
+
+            Self::mint(token_id, validator.clone(), reward)?;
+            <ValidatorRewards<T>>::remove(token_id, &validator);
+            <FeePot<T>>::mutate(token_id, |pot| *pot = pot.saturating_sub(reward));
+
+            Self::deposit_event(RawEvent::FeeClaimed(validator, token_id, reward));
+            Ok(())
+        }
+
+        // sender's refund of the withdrawal which wasn't finished in time
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn reclaim(origin, message_id: T::Hash) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            ensure!(<TransferMessages<T>>::contains_key(message_id), "Transfer does not exist");
+
+            let message = <TransferMessages<T>>::get(message_id);
+            ensure!(message.action == Status::Withdraw, "Only withdrawals can be reclaimed");
+            ensure!(message.substrate_address == sender, "Only sender can reclaim the transfer");
+
+            let deadline = Self::transfer_deadline(message_id).ok_or("Transfer has no deadline")?;
+            ensure!(<system::Module<T>>::block_number() >= deadline, "Transfer has not expired yet");
+
+            Self::expire_transfer(message_id)?;
             Ok(())
         }
 
-        //close enough to clear it exactly at UTC 00:00 instead of BlockNumber
         fn on_finalize() {
-            // clear accounts blocked day earlier (e.g. 18759 - 1)
-            let yesterday = Self::get_day_pair().0;
-            let is_first_day = Self::get_day_pair().1 == yesterday;
-            let tokens = <token::Module<T>>::tokens();
-            for t in tokens {
-                if <DailyBlocked<T>>::contains_key((t.id, yesterday)) && !is_first_day {
-                    let blocked_yesterday = <DailyBlocked<T>>::get((t.id, yesterday));
-                blocked_yesterday.iter().for_each(|a| <DailyLimits<T>>::remove((t.id, a)));
-                blocked_yesterday.iter().for_each(|a|{
-                    let now = <timestamp::Module<T>>::get();
-                    let hash = (now.clone(), a.clone()).using_encoded(<T as system::Trait>::Hashing::hash);
-                    Self::deposit_event(RawEvent::AccountResumedMessage(hash, a.clone(), now, t.id));
+            let now = <system::Module<T>>::block_number();
+            if let Some(migration) = Self::storage_migration() {
+                Self::migrate_storage(migration);
+                // transfers are expired once their messages are migrated
+                let expiring = <ExpiringTransfers<T>>::take(now);
+                <ExpiringTransfers<T>>::mutate(now + One::one(), |ids| ids.extend(expiring));
+            }
+            let mut expiring = <ExpiringTransfers<T>>::take(now);
+            if expiring.len() > MAX_EXPIRED_PER_BLOCK {
+                let rest = expiring.split_off(MAX_EXPIRED_PER_BLOCK);
+                <ExpiringTransfers<T>>::mutate(now + One::one(), |ids| ids.extend(rest));
+            }
+            // finished and pruned transfers are skipped
+            for message_id in expiring
+                .into_iter()
+                .filter(|id| !Self::is_finished(*id) && !Self::is_pruned(*id))
+            {
+                if let Err(e) = Self::expire_transfer(message_id) {
+                    debug::warn!("Failed to expire bridge transfer {:?}: {}", message_id, e);
+                }
+            }
+
+            // only accounts blocked a window ago are unblocked, one entry per blocking
+            for (chain_id, token_id, account) in <Unblocks<T>>::take(now) {
+                <ChainBlockedAccounts<T>>::remove((chain_id, token_id), &account);
+                let moment = <timestamp::Module<T>>::get();
+                let hash = (moment, &account).using_encoded(<T as system::Trait>::Hashing::hash);
+                Self::deposit_event(RawEvent::AccountResumedMessage(hash, account, moment, token_id));
+            }
+
+            Self::prune_closed_messages(now);
+        }
+
+        // validators with an Ethereum endpoint in the local storage vote for its events
+        fn offchain_worker(_block: T::BlockNumber) {
+            for (chain_id, chain) in <Chains<T>>::iter() {
+                if let Err(e) = Self::relay_ethereum_events(chain_id, chain) {
+                    debug::warn!("Bridge offchain worker failed for chain {}: {}", chain_id, e);
                 }
-                );
-                    <DailyBlocked<T>>::remove((t.id, yesterday));
             }
         }
     }
 }
-}
 
 impl<T: Trait> Module<T> {
     fn _sign(validator: T::AccountId, transfer_id: ProposalId) -> Result<()> {
@@ -345,9 +919,20 @@ impl<T: Trait> Module<T> {
         let voted = <ValidatorVotes<T>>::get((transfer_id, validator.clone()));
         ensure!(!voted, "This validator has already voted.");
         ensure!(transfer.open, "This transfer is not open");
-        transfer.votes += 1;
+        // votes of validators removed from the set since are not counted
+        let (validators, quorum) = Self::validator_set_of(&transfer);
+        transfer.votes = Self::count_votes(transfer_id, &validators) + 1;
+        let mut executed = false;
+
+        // held transfers wait for the compliance review, votes are counted on release
+        if transfer.kind == Kind::Transfer && message.status == Status::Held {
+            <ValidatorVotes<T>>::insert((transfer_id, validator.clone()), true);
+            Self::deposit_vote_event(&transfer, validator, quorum);
+            <BridgeTransfers<T>>::insert(transfer_id, transfer);
+            return Ok(());
+        }
 
-        if Self::votes_are_enough(transfer.votes) {
+        if Self::votes_are_enough(transfer.votes, quorum) {
             match message.status {
                 Status::Confirmed | Status::Canceled => (), // if burn is confirmed or canceled
                 _ => match transfer.kind {
@@ -364,6 +949,7 @@ impl<T: Trait> Module<T> {
                 Kind::Bridge => Self::manage_bridge(bridge_message)?,
             }
             transfer.open = false;
+            executed = true;
         } else {
             match message.status {
                 Status::Confirmed | Status::Canceled => (),
@@ -371,23 +957,27 @@ impl<T: Trait> Module<T> {
             };
         }
 
-        <ValidatorVotes<T>>::mutate((transfer_id, validator), |a| *a = true);
+        <ValidatorVotes<T>>::mutate((transfer_id, validator.clone()), |a| *a = true);
+        Self::deposit_vote_event(&transfer, validator, quorum);
+        if executed && transfer.kind == Kind::Transfer {
+            Self::settle_fee(transfer_id, transfer.message_id)?;
+        }
+        // transfers are closed once confirmed or canceled, see `update_status`
+        if executed && transfer.kind != Kind::Transfer {
+            Self::queue_for_pruning(transfer.message_id, transfer.kind.clone());
+        }
         <BridgeTransfers<T>>::insert(transfer_id, transfer);
 
         Ok(())
     }
 
-    ///get (yesterday,today) pair
-    fn get_day_pair() -> (T::Moment, T::Moment) {
-        let now = <timestamp::Module<T>>::get();
-        let day = T::Moment::from(DAY);
-        let today = <timestamp::Module<T>>::get() / T::Moment::from(DAY);
-        let yesterday = if now < day {
-            T::Moment::from(0)
-        } else {
-            <timestamp::Module<T>>::get() / day - T::Moment::from(1)
-        };
-        (yesterday, today)
+    fn deposit_vote_event(
+        transfer: &BridgeTransfer<T::Hash>,
+        validator: T::AccountId,
+        quorum: u64,
+    ) {
+        let event = RawEvent::VoteCast(transfer.message_id, validator, transfer.votes, quorum);
+        Self::deposit_message_event(transfer.message_id, event);
     }
 
     ///ensure that such transfer exist
@@ -400,105 +990,205 @@ impl<T: Trait> Module<T> {
 
     ///execute actual mint
     fn deposit(message: TransferMessage<T::AccountId, T::Hash, T::Balance>) -> Result<()> {
+        Self::check_mint(message.token, &message.substrate_address, message.amount)?;
         Self::sub_pending_mint(message.clone())?;
         let to = message.substrate_address;
-        if !<DailyHolds<T>>::contains_key(&to) {
-            <DailyHolds<T>>::insert(to.clone(), (T::BlockNumber::from(0), message.message_id));
+        if !<FirstDeposits<T>>::contains_key(&to) {
+            <FirstDeposits<T>>::insert(&to, <system::Module<T>>::block_number());
         }
 
-        <token::Module<T>>::_mint(message.token, to, message.amount)?;
+        // fee is kept from the minted amount
+        let fee = Self::fee_for(message.token, message.amount).min(message.amount);
+        if message.amount > fee {
+            Self::mint(message.token, to.clone(), message.amount - fee)?;
+        }
+        if !fee.is_zero() {
+            Self::hold_fee(message.message_id, message.token, to, fee);
+        }
 
-        Self::deposit_event(RawEvent::MintedMessage(message.message_id, message.token));
+        Self::deposit_message_event(
+            message.message_id,
+            RawEvent::MintedMessage(message.message_id, message.token),
+        );
         Self::update_status(message.message_id, Status::Confirmed, Kind::Transfer)
     }
 
+    /// mint the deposit proven against Ethereum header, it needs no validators votes
+    pub fn mint_proven(
+        chain_id: ChainId,
+        message_id: T::Hash,
+        from: H160,
+        to: T::AccountId,
+        token_id: TokenId,
+        amount: T::Balance,
+    ) -> Result<()> {
+        ensure!(Self::bridge_is_operational(), "Bridge is not operational");
+        Self::check_chain_token(chain_id, token_id)?;
+        Self::check_amount(chain_id, token_id, amount)?;
+        Self::check_pending_mint(chain_id, token_id, amount)?;
+        let held = Self::check_compliance(token_id, &to, amount)?;
+        Self::check_mint(token_id, &to, amount)?;
+        let message_id = Self::chain_message_id(chain_id, message_id);
+        ensure!(
+            !<TransferMessages<T>>::contains_key(message_id) && !Self::is_pruned(message_id),
+            "This transfer already exists"
+        );
+
+        let message = TransferMessage {
+            message_id,
+            eth_address: from,
+            substrate_address: to.clone(),
+            amount,
+            token: token_id,
+            status: if held { Status::Held } else { Status::Approved },
+            action: Status::Deposit,
+            chain: chain_id,
+        };
+        Self::create_transfer(message_id, Kind::Transfer)?;
+        Self::insert_message(message.clone());
+        // proven deposits need no votes
+        let transfer_id = <TransferId<T>>::get(message_id);
+        <BridgeTransfers<T>>::mutate(transfer_id, |t| t.open = false);
+        if held {
+            Self::deposit_message_event(
+                message_id,
+                RawEvent::TransferHeld(message_id, token_id, to, amount),
+            );
+            return Ok(());
+        }
+
+        Self::add_pending_mint(message.clone())?;
+        Self::deposit(message)?;
+        Self::settle_fee(transfer_id, message_id)
+    }
+
     fn withdraw(message: TransferMessage<T::AccountId, T::Hash, T::Balance>) -> Result<()> {
-        Self::check_daily_holds(message.clone())?;
         Self::sub_pending_burn(message.clone())?;
 
         let to = message.eth_address;
         let from = message.substrate_address.clone();
         Self::lock_for_burn(&message, from.clone())?;
-        Self::deposit_event(RawEvent::ApprovedRelayMessage(
+        Self::deposit_message_event(
             message.message_id,
-            message.token,
-            from,
-            to,
-            message.amount,
-        ));
+            RawEvent::ApprovedRelayMessage(
+                message.message_id,
+                message.token,
+                from,
+                to,
+                message.amount,
+            ),
+        );
         Self::update_status(message.message_id, Status::Approved, Kind::Transfer)
     }
     fn _cancel_transfer(message: TransferMessage<T::AccountId, T::Hash, T::Balance>) -> Result<()> {
-        <token::Module<T>>::unlock(message.token, &message.substrate_address, message.amount)?;
+        match message.action {
+            // nothing was minted for the rejected deposit
+            Status::Deposit => {
+                Self::sub_pending_mint(message.clone())?;
+                <RejectedMints<T>>::insert(message.message_id, true);
+            }
+            _ => Self::unlock(message.token, &message.substrate_address, message.amount)?,
+        }
         Self::update_status(message.message_id, Status::Canceled, Kind::Transfer)
     }
     fn pause_the_bridge(message: BridgeMessage<T::AccountId, T::Hash>) -> Result<()> {
         <BridgeIsOperational>::mutate(|x| *x = false);
+        <KindNonces>::mutate(Kind::Bridge, |n| *n += 1);
+        Self::deposit_message_event(
+            message.message_id,
+            RawEvent::BridgePaused(message.message_id),
+        );
         Self::update_status(message.message_id, Status::Confirmed, Kind::Bridge)
     }
 
     fn resume_the_bridge(message: BridgeMessage<T::AccountId, T::Hash>) -> Result<()> {
         <BridgeIsOperational>::mutate(|x| *x = true);
+        <KindNonces>::mutate(Kind::Bridge, |n| *n += 1);
+        Self::deposit_message_event(
+            message.message_id,
+            RawEvent::BridgeResumed(message.message_id),
+        );
         Self::update_status(message.message_id, Status::Confirmed, Kind::Bridge)
     }
 
     fn _update_limits(message: LimitMessage<T::Hash, T::Balance>) -> Result<()> {
         Self::check_limits(&message.limits)?;
-        <CurrentLimits<T>>::put(message.limits);
+        <CurrentLimits<T>>::insert(message.chain, message.token, message.limits.clone());
+        <LimitsNonces>::mutate(message.chain, message.token, |n| *n += 1);
+        Self::deposit_message_event(
+            message.id,
+            RawEvent::LimitsChanged(message.chain, message.token, message.limits),
+        );
         Self::update_status(message.id, Status::Confirmed, Kind::Limits)
     }
     fn add_pending_burn(message: TransferMessage<T::AccountId, T::Hash, T::Balance>) -> Result<()> {
-        let current = <CurrentPendingBurn<T>>::get();
+        let current = <CurrentPendingBurn<T>>::get(message.chain, message.token);
         let next = current
             .checked_add(&message.amount)
             .ok_or("Overflow adding to new pending burn volume")?;
-        <CurrentPendingBurn<T>>::put(next);
+        <CurrentPendingBurn<T>>::insert(message.chain, message.token, next);
         Ok(())
     }
     fn add_pending_mint(message: TransferMessage<T::AccountId, T::Hash, T::Balance>) -> Result<()> {
-        let current = <CurrentPendingMint<T>>::get();
+        let current = <CurrentPendingMint<T>>::get(message.chain, message.token);
         let next = current
             .checked_add(&message.amount)
             .ok_or("Overflow adding to new pending mint volume")?;
-        <CurrentPendingMint<T>>::put(next);
+        <CurrentPendingMint<T>>::insert(message.chain, message.token, next);
         Ok(())
     }
     fn sub_pending_burn(message: TransferMessage<T::AccountId, T::Hash, T::Balance>) -> Result<()> {
-        let current = <CurrentPendingBurn<T>>::get();
+        let current = <CurrentPendingBurn<T>>::get(message.chain, message.token);
         let next = current
             .checked_sub(&message.amount)
             .ok_or("Overflow subtracting to new pending burn volume")?;
-        <CurrentPendingBurn<T>>::put(next);
+        <CurrentPendingBurn<T>>::insert(message.chain, message.token, next);
         Ok(())
     }
     fn sub_pending_mint(message: TransferMessage<T::AccountId, T::Hash, T::Balance>) -> Result<()> {
-        let current = <CurrentPendingMint<T>>::get();
+        let current = <CurrentPendingMint<T>>::get(message.chain, message.token);
         let next = current
             .checked_sub(&message.amount)
             .ok_or("Overflow subtracting to new pending mint volume")?;
-        <CurrentPendingMint<T>>::put(next);
+        <CurrentPendingMint<T>>::insert(message.chain, message.token, next);
         Ok(())
     }
 
-    /// update validators list
+    /// replace validators list
     fn manage_validator_list(info: ValidatorMessage<T::AccountId, T::Hash>) -> Result<()> {
-        let new_count = info.accounts.clone().len() as u32;
-        ensure!(
-            new_count < MAX_VALIDATORS,
-            "New validator list is exceeding allowed length."
+        Self::check_validator_set(info.quorum, &info.accounts)?;
+        Self::replace_validators(info.quorum, info.accounts.clone());
+
+        Self::deposit_message_event(
+            info.message_id,
+            RawEvent::ValidatorSetChanged(info.message_id, info.quorum, info.accounts),
         );
-        <Quorum>::put(info.quorum);
-        <ValidatorsCount>::put(new_count);
-        info.accounts
-            .clone()
+        Self::update_status(info.message_id, Status::Confirmed, Kind::Validator)
+    }
+
+    fn replace_validators(quorum: u64, accounts: Vec<T::AccountId>) {
+        <ValidatorAccounts<T>>::get()
+            .iter()
+            .for_each(|v| <Validators<T>>::remove(v));
+        accounts
             .iter()
             .for_each(|v| <Validators<T>>::insert(v, true));
-        Self::update_status(info.message_id, Status::Confirmed, Kind::Validator)
+        <ValidatorsCount>::put(accounts.len() as u32);
+        <ValidatorAccounts<T>>::put(accounts);
+        <Quorum>::put(quorum);
     }
 
     /// check votes validity
-    fn votes_are_enough(votes: MemberId) -> bool {
-        votes as f64 / f64::from(Self::validators_count()) >= 0.51
+    fn votes_are_enough(votes: MemberId, quorum: u64) -> bool {
+        votes >= quorum
+    }
+
+    /// votes of the current validators
+    fn count_votes(transfer_id: ProposalId, validators: &[T::AccountId]) -> MemberId {
+        validators
+            .iter()
+            .filter(|v| <ValidatorVotes<T>>::get((transfer_id, *v)))
+            .count() as MemberId
     }
 
     /// lock funds after set_transfer call
@@ -506,27 +1196,142 @@ impl<T: Trait> Module<T> {
         message: &TransferMessage<T::AccountId, T::Hash, T::Balance>,
         account: T::AccountId,
     ) -> Result<()> {
-        <token::Module<T>>::lock(message.token, account, message.amount)?;
+        Self::lock(message.token, account, message.amount)?;
+
+        Ok(())
+    }
 
+    /// tokens which can be bridged, including native currency
+    fn bridged_tokens() -> Vec<TokenId> {
+        let mut tokens = <token::Module<T>>::tokens()
+            .into_iter()
+            .map(|t| t.id)
+            .collect::<Vec<_>>();
+        tokens.push(NATIVE_TOKEN_ID);
+        tokens
+    }
+
+    fn free_balance_of(token_id: TokenId, account: &T::AccountId) -> T::Balance {
+        match token_id {
+            NATIVE_TOKEN_ID => <balances::Module<T>>::free_balance(account),
+            _ => <token::Module<T>>::free_balance_of(token_id, account),
+        }
+    }
+
+    fn total_balance_of(token_id: TokenId, account: &T::AccountId) -> T::Balance {
+        match token_id {
+            NATIVE_TOKEN_ID => <balances::Module<T>>::total_balance(account),
+            _ => <token::Module<T>>::balance_of(token_id, account),
+        }
+    }
+
+    /// account the native currency is kept in while it's on Ethereum side
+    pub fn account_id() -> T::AccountId {
+        BRIDGE_MODULE_ID.into_account()
+    }
+
+    // the bridge account is never reaped, it keeps the existential deposit
+    fn endow_account() {
+        let account = Self::account_id();
+        if <balances::Module<T>>::total_balance(&account).is_zero() {
+            drop(<balances::Module<T>>::deposit_creating(
+                &account,
+                T::ExistentialDeposit::get(),
+            ));
+        }
+    }
+
+    // native currency isn't minted and burned, it's released from the bridge account
+    // and moved there, so its issuance stays the same
+    fn mint(token_id: TokenId, account: T::AccountId, amount: T::Balance) -> Result<()> {
+        match token_id {
+            NATIVE_TOKEN_ID => <balances::Module<T> as Currency<_>>::transfer(
+                &Self::account_id(),
+                &account,
+                amount,
+                ExistenceRequirement::KeepAlive,
+            )
+            .map_err(|_| "Not enough native currency locked by the bridge"),
+            _ => <token::Module<T>>::_mint(token_id, account, amount),
+        }
+    }
+
+    fn burn(token_id: TokenId, account: T::AccountId, amount: T::Balance) -> Result<()> {
+        match token_id {
+            NATIVE_TOKEN_ID => <balances::Module<T> as Currency<_>>::transfer(
+                &account,
+                &Self::account_id(),
+                amount,
+                ExistenceRequirement::KeepAlive,
+            )
+            .map_err(|_| "Not enough balance to burn"),
+            _ => <token::Module<T>>::_burn(token_id, account, amount),
+        }
+    }
+
+    /// native currency the recipient gets has to be in the bridge account,
+    /// and a new account can't get less than the existential deposit
+    fn check_mint(token_id: TokenId, to: &T::AccountId, amount: T::Balance) -> Result<()> {
+        if token_id != NATIVE_TOKEN_ID {
+            return Ok(());
+        }
+        let credited = amount - Self::fee_for(token_id, amount).min(amount);
+        let locked = <balances::Module<T>>::free_balance(&Self::account_id())
+            .saturating_sub(T::ExistentialDeposit::get());
+        ensure!(
+            locked >= credited,
+            "Not enough native currency locked by the bridge"
+        );
+        ensure!(
+            !<balances::Module<T>>::total_balance(to).is_zero()
+                || credited >= T::ExistentialDeposit::get(),
+            "Amount is below the existential deposit"
+        );
         Ok(())
     }
 
+    fn lock(token_id: TokenId, account: T::AccountId, amount: T::Balance) -> Result<()> {
+        match token_id {
+            NATIVE_TOKEN_ID => <balances::Module<T>>::reserve(&account, amount)
+                .map_err(|_| "Not enough balance to lock"),
+            _ => <token::Module<T>>::lock(token_id, account, amount),
+        }
+    }
+
+    fn unlock(token_id: TokenId, account: &T::AccountId, amount: T::Balance) -> Result<()> {
+        match token_id {
+            NATIVE_TOKEN_ID => {
+                <balances::Module<T>>::unreserve(account, amount);
+                Ok(())
+            }
+            _ => <token::Module<T>>::unlock(token_id, account, amount),
+        }
+    }
+
+    fn burn_locked(token_id: TokenId, account: T::AccountId, amount: T::Balance) -> Result<()> {
+        match token_id {
+            NATIVE_TOKEN_ID => {
+                <balances::Module<T>>::unreserve(&account, amount);
+                Self::burn(token_id, account, amount)
+            }
+            _ => {
+                <token::Module<T>>::unlock(token_id, &account, amount)?;
+                <token::Module<T>>::_burn(token_id, account, amount)
+            }
+        }
+    }
+
     fn execute_burn(message_id: T::Hash) -> Result<()> {
         let message = <TransferMessages<T>>::get(message_id);
         let from = message.substrate_address.clone();
         let to = message.eth_address;
 
-        <token::Module<T>>::unlock(message.token, &from, message.amount)?;
-        <token::Module<T>>::_burn(message.token, from.clone(), message.amount)?;
-        <DailyLimits<T>>::mutate((message.token, from.clone()), |a| *a -= message.amount);
+        Self::burn_locked(message.token, from.clone(), message.amount)?;
 
-        Self::deposit_event(RawEvent::BurnedMessage(
+        Self::deposit_message_event(
             message_id,
-            message.token,
-            from,
-            to,
-            message.amount,
-        ));
+            RawEvent::BurnedMessage(message_id, message.token, from, to, message.amount),
+        );
         Ok(())
     }
 
@@ -561,11 +1366,24 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// store the new transfer message and track it until it's finished
+    fn insert_message(message: TransferMessage<T::AccountId, T::Hash, T::Balance>) {
+        let id = message.message_id;
+        let now = <system::Module<T>>::block_number();
+        <StatusHistory<T>>::mutate(id, |history| history.push((message.status.clone(), now)));
+        <PendingTransfers<T>>::insert(message.token, id, &message.substrate_address);
+        <TransferMessages<T>>::insert(id, message);
+    }
+
     fn create_transfer(transfer_hash: T::Hash, kind: Kind) -> Result<()> {
         ensure!(
             !<TransferId<T>>::contains_key(transfer_hash),
             "This transfer already open"
         );
+        ensure!(
+            !Self::is_pruned(transfer_hash),
+            "This transfer is already finished"
+        );
 
         let transfer_id = <BridgeTransfersCount>::get();
         let bridge_transfers_count = <BridgeTransfersCount>::get();
@@ -577,13 +1395,14 @@ impl<T: Trait> Module<T> {
             message_id: transfer_hash,
             open: true,
             votes: 0,
-            kind,
+            kind: kind.clone(),
         };
 
         <BridgeTransfers<T>>::insert(transfer_id, transfer);
         <BridgeTransfersCount>::mutate(|count| *count = new_bridge_transfers_count);
         <TransferId<T>>::insert(transfer_hash, transfer_id);
         <MessageId<T>>::insert(transfer_id, transfer_hash);
+        Self::deposit_message_event(transfer_hash, RawEvent::MessageCreated(transfer_hash, kind));
 
         Ok(())
     }
@@ -591,7 +1410,8 @@ impl<T: Trait> Module<T> {
     fn set_pending(transfer_id: ProposalId, kind: Kind) -> Result<()> {
         let message_id = <MessageId<T>>::get(transfer_id);
         match kind {
-            Kind::Transfer => {
+            // amount is added to pending volume on the first vote only
+            Kind::Transfer if <TransferMessages<T>>::get(message_id).status != Status::Pending => {
                 let message = <TransferMessages<T>>::get(message_id);
                 match message.action {
                     Status::Withdraw => Self::add_pending_burn(message)?,
@@ -604,32 +1424,232 @@ impl<T: Trait> Module<T> {
         Self::update_status(message_id, Status::Pending, kind)
     }
 
-    fn update_status(id: T::Hash, status: Status, kind: Kind) -> Result<()> {
-        match kind {
-            Kind::Transfer => {
-                let mut message = <TransferMessages<T>>::get(id);
-                message.status = status;
-                <TransferMessages<T>>::insert(id, message);
-            }
-            Kind::Validator => {
-                let mut message = <ValidatorHistory<T>>::get(id);
-                message.status = status;
-                <ValidatorHistory<T>>::insert(id, message);
+    fn set_deadline(message_id: T::Hash) {
+        let deadline = <system::Module<T>>::block_number() + T::TransferTtl::get();
+        <TransferDeadlines<T>>::insert(message_id, deadline);
+        <ExpiringTransfers<T>>::mutate(deadline, |ids| ids.push(message_id));
+    }
+
+    fn expire_transfer(message_id: T::Hash) -> Result<()> {
+        let token_id = Self::cancel_unfinished(message_id)?;
+        Self::deposit_message_event(
+            message_id,
+            RawEvent::ExpiredTransferMessage(message_id, token_id),
+        );
+        Ok(())
+    }
+
+    /// cancel unfinished transfer and release its funds
+    fn cancel_unfinished(message_id: T::Hash) -> Result<TokenId> {
+        let message = <TransferMessages<T>>::get(message_id);
+        Self::check_not_attested(&message)?;
+        match (&message.action, &message.status) {
+            (Status::Withdraw, Status::Withdraw) | (Status::Deposit, Status::Deposit) => (),
+            // nothing is pending or locked for the transfer under review
+            (_, Status::Held) => (),
+            (Status::Withdraw, Status::Pending) => Self::sub_pending_burn(message.clone())?,
+            (Status::Deposit, Status::Pending) => Self::sub_pending_mint(message.clone())?,
+            // approved by validators, but not confirmed on Ethereum side
+            (Status::Withdraw, Status::Approved) => {
+                Self::unlock(message.token, &message.substrate_address, message.amount)?
             }
-            Kind::Bridge => {
-                let mut message = <BridgeMessages<T>>::get(id);
-                message.status = status;
+            _ => fail!("Transfer is already finished"),
+        }
+        if message.action == Status::Withdraw {
+            Self::release_volume(
+                message.chain,
+                message.token,
+                &message.substrate_address,
+                message.amount,
+            );
+            Self::refund_fee(&message)?;
+        }
+
+        let transfer_id = <TransferId<T>>::get(message_id);
+        <BridgeTransfers<T>>::mutate(transfer_id, |t| t.open = false);
+        <TransferDeadlines<T>>::remove(message_id);
+        Self::update_status(message_id, Status::Canceled, Kind::Transfer)?;
+        Ok(message.token)
+    }
+
+    /// finish the released transfer the way the last vote for it would
+    fn execute_released(message_id: T::Hash) -> Result<()> {
+        let transfer_id = <TransferId<T>>::get(message_id);
+        let mut transfer = <BridgeTransfers<T>>::get(transfer_id);
+        // proven deposits are closed from the start
+        if !transfer.open {
+            let message = <TransferMessages<T>>::get(message_id);
+            Self::add_pending_mint(message.clone())?;
+            Self::deposit(message)?;
+            return Self::settle_fee(transfer_id, message_id);
+        }
+        let (validators, quorum) = Self::validator_set_of(&transfer);
+        transfer.votes = Self::count_votes(transfer_id, &validators);
+        if transfer.votes == 0 {
+            return Ok(());
+        }
+
+        Self::set_pending(transfer_id, Kind::Transfer)?;
+        if Self::votes_are_enough(transfer.votes, quorum) {
+            let mut message = <TransferMessages<T>>::get(message_id);
+            message.status = Status::Approved;
+            Self::execute_transfer(message)?;
+            transfer.open = false;
+        }
+        let executed = !transfer.open;
+        <BridgeTransfers<T>>::insert(transfer_id, transfer);
+        if executed {
+            Self::settle_fee(transfer_id, message_id)?;
+        }
+        Ok(())
+    }
+
+    /// denied accounts can't bridge, large transfers of accounts not allowed are held
+    fn check_compliance(
+        token_id: TokenId,
+        account: &T::AccountId,
+        amount: T::Balance,
+    ) -> Result<bool> {
+        match Self::account_list(account) {
+            Some(AccountList::Denied) => fail!("Account is denied by compliance"),
+            Some(AccountList::Allowed) => Ok(false),
+            None => {
+                Ok(Self::review_threshold(token_id).map_or(false, |threshold| amount > threshold))
+            }
+        }
+    }
+
+    /// fee for transfer of `amount` of the token
+    pub fn fee_for(token_id: TokenId, amount: T::Balance) -> T::Balance {
+        let fee = <Fees<T>>::get(token_id);
+        let share = Permill::from_parts(fee.bps.min(MAX_FEE_BPS) * 100) * amount;
+        fee.flat.saturating_add(share)
+    }
+
+    fn hold_fee(message_id: T::Hash, token_id: TokenId, payer: T::AccountId, fee: T::Balance) {
+        <FeePot<T>>::mutate(token_id, |pot| *pot = pot.saturating_add(fee));
+        <TransferFees<T>>::insert(message_id, fee);
+        Self::deposit_message_event(
+            message_id,
+            RawEvent::FeeCharged(message_id, token_id, payer, fee),
+        );
+    }
+
+    // fee of finished transfer goes to voted validators, fee of cancelled one is returned
+    fn settle_fee(transfer_id: ProposalId, message_id: T::Hash) -> Result<()> {
+        let message = <TransferMessages<T>>::get(message_id);
+        match message.status {
+            Status::Confirmed => Self::distribute_fee(transfer_id, &message),
+            Status::Canceled => Self::refund_fee(&message),
+            _ => Ok(()),
+        }
+    }
+
+    fn distribute_fee(
+        transfer_id: ProposalId,
+        message: &TransferMessage<T::AccountId, T::Hash, T::Balance>,
+    ) -> Result<()> {
+        let (validators, _) = Self::validator_set_of(&<BridgeTransfers<T>>::get(transfer_id));
+        let voters = validators
+            .into_iter()
+            .filter(|v| <ValidatorVotes<T>>::get((transfer_id, v)))
+            .collect::<Vec<_>>();
+        let fee = <TransferFees<T>>::take(message.message_id);
+        if fee.is_zero() {
+            return Ok(());
+        }
+        if voters.is_empty() {
+            Self::mint(message.token, T::TreasuryAccount::get(), fee)?;
+            <FeePot<T>>::mutate(message.token, |pot| *pot = pot.saturating_sub(fee));
+            Self::deposit_message_event(
+                message.message_id,
+                RawEvent::FeeDistributed(message.message_id, message.token, fee),
+            );
+            return Ok(());
+        }
+
+        let count = T::Balance::from(voters.len() as u32);
+        let share = fee / count;
+        // the first voter in the validator list gets the remainder
+        let remainder = fee - share * count;
+        for (i, validator) in voters.iter().enumerate() {
+            let reward = if i == 0 { share + remainder } else { share };
+            <ValidatorRewards<T>>::mutate(message.token, validator, |r| {
+                *r = r.saturating_add(reward)
+            });
+        }
+
+        Self::deposit_message_event(
+            message.message_id,
+            RawEvent::FeeDistributed(message.message_id, message.token, fee),
+        );
+        Ok(())
+    }
+
+    fn refund_fee(message: &TransferMessage<T::AccountId, T::Hash, T::Balance>) -> Result<()> {
+        let fee = <TransferFees<T>>::take(message.message_id);
+        if fee.is_zero() {
+            return Ok(());
+        }
+        let payer = message.substrate_address.clone();
+        Self::mint(message.token, payer.clone(), fee)?;
+        <FeePot<T>>::mutate(message.token, |pot| *pot = pot.saturating_sub(fee));
+
+        Self::deposit_message_event(
+            message.message_id,
+            RawEvent::FeeRefunded(message.message_id, message.token, payer, fee),
+        );
+        Ok(())
+    }
+
+    fn update_status(id: T::Hash, status: Status, kind: Kind) -> Result<()> {
+        // every vote sets the status, only changes are recorded
+        let previous = match kind {
+            Kind::Transfer => {
+                let mut message = <TransferMessages<T>>::get(id);
+                if message.status != status {
+                    if status == Status::Confirmed || status == Status::Canceled {
+                        <PendingTransfers<T>>::remove(message.token, id);
+                        Self::queue_for_pruning(id, Kind::Transfer);
+                    }
+                    let now = <system::Module<T>>::block_number();
+                    <StatusHistory<T>>::mutate(id, |history| history.push((status.clone(), now)));
+                }
+                let previous = core::mem::replace(&mut message.status, status.clone());
+                <TransferMessages<T>>::insert(id, message);
+                previous
+            }
+            Kind::Validator => {
+                let mut message = <ValidatorHistory<T>>::get(id);
+                let previous = core::mem::replace(&mut message.status, status.clone());
+                <ValidatorHistory<T>>::insert(id, message);
+                previous
+            }
+            Kind::Bridge => {
+                let mut message = <BridgeMessages<T>>::get(id);
+                let previous = core::mem::replace(&mut message.status, status.clone());
                 <BridgeMessages<T>>::insert(id, message);
+                previous
             }
             Kind::Limits => {
                 let mut message = <LimitMessages<T>>::get(id);
-                message.status = status;
+                let previous = core::mem::replace(&mut message.status, status.clone());
                 <LimitMessages<T>>::insert(id, message);
+                previous
             }
+        };
+        if previous != status {
+            Self::deposit_message_event(id, RawEvent::StatusChanged(id, kind, status));
         }
         Ok(())
     }
 
+    /// deposit the event indexed by the message id, so clients can subscribe to one message
+    fn deposit_message_event(message_id: T::Hash, event: Event<T>) {
+        let event: <T as Trait>::Event = event.into();
+        <system::Module<T>>::deposit_event_indexed(&[message_id], event.into());
+    }
+
     // needed because @message_id will be the same as initial
     fn reopen_for_burn_confirmation(message_id: T::Hash) -> Result<()> {
         let message = <TransferMessages<T>>::get(message_id);
@@ -638,16 +1658,136 @@ impl<T: Trait> Module<T> {
         let is_eth_response =
             message.status == Status::Confirmed || message.status == Status::Canceled;
         if !transfer.open && is_eth_response {
-            transfer.votes = 0;
             transfer.open = true;
             <BridgeTransfers<T>>::insert(transfer_id, transfer);
-            let validators = <ValidatorAccounts<T>>::get();
-            validators
-                .iter()
-                .for_each(|a| <ValidatorVotes<T>>::insert((transfer_id, a.clone()), false));
+            Self::reset_votes(transfer_id);
         }
         Ok(())
     }
+
+    fn reset_votes(transfer_id: ProposalId) {
+        <BridgeTransfers<T>>::mutate(transfer_id, |t| t.votes = 0);
+        let (validators, _) = Self::validator_set_of(&<BridgeTransfers<T>>::get(transfer_id));
+        validators
+            .iter()
+            .for_each(|a| <ValidatorVotes<T>>::insert((transfer_id, a.clone()), false));
+    }
+    fn check_validator_set(quorum: u64, accounts: &[T::AccountId]) -> Result<()> {
+        let count = accounts.len() as u32;
+        ensure!(
+            count < MAX_VALIDATORS,
+            "New validator list is exceeding allowed length."
+        );
+        ensure!(
+            quorum > 0 && quorum <= u64::from(count),
+            "Quorum should be between 1 and number of validators."
+        );
+
+        let mut unique = accounts.to_vec();
+        unique.sort();
+        unique.dedup();
+        ensure!(
+            unique.len() == accounts.len(),
+            "New validator list contains duplicates."
+        );
+        let bond = T::ValidatorBond::get();
+        ensure!(
+            accounts.iter().all(|v| Self::bond_of(v) >= bond),
+            "New validator list contains unbonded accounts."
+        );
+        Ok(())
+    }
+
+    fn record_vote(message_id: T::Hash, validator: T::AccountId, vote: Status) {
+        <SignedVotes<T>>::mutate(message_id, validator, |votes| {
+            if !votes.contains(&vote) {
+                votes.push(vote)
+            }
+        });
+    }
+
+    /// ABI encoded `(messageID, spender, substrateAddress, amount)` of `ApprovedRelayMessage`
+    pub fn attestation_payload(
+        message: &TransferMessage<T::AccountId, T::Hash, T::Balance>,
+    ) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(128);
+        payload.extend_from_slice(message.message_id.as_ref());
+        payload.extend_from_slice(&[0u8; 12]);
+        payload.extend_from_slice(message.eth_address.as_bytes());
+        let mut account = message.substrate_address.encode();
+        account.resize(32, 0);
+        payload.extend(account);
+        let amount: u128 = message.amount.saturated_into();
+        payload.extend_from_slice(&[0u8; 16]);
+        payload.extend_from_slice(&amount.to_be_bytes());
+        payload
+    }
+
+    /// hash validators sign, the same as `eth_sign` of the payload hash
+    pub fn attestation_digest(
+        message: &TransferMessage<T::AccountId, T::Hash, T::Balance>,
+    ) -> [u8; 32] {
+        let mut prefixed = ETH_SIGNED_MESSAGE_PREFIX.to_vec();
+        prefixed.extend_from_slice(&keccak_256(&Self::attestation_payload(message)));
+        keccak_256(&prefixed)
+    }
+
+    fn attestation_signer(
+        message: &TransferMessage<T::AccountId, T::Hash, T::Balance>,
+        signature: &ecdsa::Signature,
+    ) -> Option<H160> {
+        let signature: &[u8; 65] = signature.as_ref();
+        let public = secp256k1_ecdsa_recover(signature, &Self::attestation_digest(message)).ok()?;
+        Some(H160::from_slice(&keccak_256(&public)[12..]))
+    }
+
+    /// validator signed votes which can't be true at the same time
+    fn has_conflicting_votes(message_id: T::Hash, validator: &T::AccountId) -> bool {
+        let votes = <SignedVotes<T>>::get(message_id, validator);
+        let signed = |vote: Status| votes.contains(&vote);
+        match <TransferMessages<T>>::get(message_id).action {
+            // approved mint was rejected by the validators
+            Status::Deposit => {
+                signed(Status::Approved)
+                    && (signed(Status::Canceled) || Self::rejected_mint(message_id))
+            }
+            // approved burn is canceled if it fails on Ethereum side, but can't be confirmed as well
+            Status::Withdraw => signed(Status::Confirmed) && signed(Status::Canceled),
+            _ => false,
+        }
+    }
+
+    /// slash the whole bond of the offender and remove it from the validator set
+    fn slash(message_id: T::Hash, offender: T::AccountId) {
+        let bond = <Bonds<T>>::take(&offender);
+        let (imbalance, _) = <balances::Module<T>>::slash_reserved(&offender, bond);
+        let slashed = imbalance.peek();
+        T::Slash::on_unbalanced(imbalance);
+        <Offences<T>>::insert(message_id, &offender, true);
+
+        if <Validators<T>>::contains_key(&offender) {
+            <Validators<T>>::remove(&offender);
+            <ValidatorAccounts<T>>::mutate(|v| v.retain(|a| *a != offender));
+            let count = <ValidatorAccounts<T>>::get().len() as u32;
+            <ValidatorsCount>::put(count);
+            // the rest of validators should still be able to reach the quorum
+            <Quorum>::mutate(|q| *q = (*q).min(u64::from(count)));
+        }
+        let chains = <Chains<T>>::iter()
+            .filter(|(_, chain)| chain.validators.contains(&offender))
+            .collect::<Vec<_>>();
+        for (chain_id, mut chain) in chains {
+            chain.validators.retain(|a| *a != offender);
+            chain.quorum = chain.quorum.min(chain.validators.len() as u64);
+            <Chains<T>>::insert(chain_id, chain);
+        }
+
+        Self::deposit_message_event(
+            message_id,
+            RawEvent::ValidatorSlashed(message_id, offender, slashed),
+        );
+    }
+
     fn check_validator(validator: T::AccountId) -> Result<()> {
         let is_trusted = <Validators<T>>::contains_key(validator);
         ensure!(is_trusted, "Only validators can call this function");
@@ -655,44 +1795,365 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
-    fn check_daily_account_volume(
-        token_id: TokenId,
-        account: T::AccountId,
-        amount: T::Balance,
+    fn check_chain_validator(chain_id: ChainId, validator: &T::AccountId) -> Result<()> {
+        let chain = Self::chains(chain_id).ok_or("Chain is not registered")?;
+        if chain.validators.is_empty() {
+            return Self::check_validator(validator.clone());
+        }
+        ensure!(
+            chain.validators.contains(validator),
+            "Only validators can call this function"
+        );
+        Ok(())
+    }
+
+    fn check_chain_token(chain_id: ChainId, token_id: TokenId) -> Result<()> {
+        let chain = Self::chains(chain_id).ok_or("Chain is not registered")?;
+        ensure!(
+            chain.tokens.contains(&token_id),
+            "Token is not supported by the chain"
+        );
+        Ok(())
+    }
+
+    /// withdrawal attested by the quorum can be executed by the contract at any time,
+    /// so its funds are kept locked until it is confirmed
+    fn check_not_attested(
+        message: &TransferMessage<T::AccountId, T::Hash, T::Balance>,
     ) -> Result<()> {
-        let cur_pending = <DailyLimits<T>>::get((token_id, &account));
-        let cur_pending_account_limit = <CurrentLimits<T>>::get().day_max_limit_for_one_address;
-        let can_burn = cur_pending + amount < cur_pending_account_limit;
+        if message.action != Status::Withdraw {
+            return Ok(());
+        }
+        let attested = <Attestations<T>>::get(message.message_id).len() as u64;
+        let transfer = <BridgeTransfers<T>>::get(<TransferId<T>>::get(message.message_id));
+        let (_, quorum) = Self::validator_set_of(&transfer);
+        ensure!(
+            attested == 0 || !Self::votes_are_enough(attested, quorum),
+            "Transfer is attested and can't be canceled"
+        );
+        Ok(())
+    }
 
-        //store current day (like 18768)
-        let today = Self::get_day_pair().1;
-        let user_blocked = <DailyBlocked<T>>::get((token_id, today))
-            .iter()
-            .any(|a| *a == account);
-
-        if !can_burn {
-            <DailyBlocked<T>>::mutate((token_id, today), |v| {
-                if !v.contains(&account) {
-                    v.push(account.clone());
-                    let now = <timestamp::Module<T>>::get();
-                    let hash = (now.clone(), account.clone())
-                        .using_encoded(<T as system::Trait>::Hashing::hash);
-                    Self::deposit_event(RawEvent::AccountPausedMessage(
-                        hash, account, now, token_id,
-                    ))
+    /// validators voting for the transfer and their quorum
+    fn validator_set_of(transfer: &BridgeTransfer<T::Hash>) -> (Vec<T::AccountId>, u64) {
+        let chain_id = match transfer.kind {
+            Kind::Transfer => Some(<TransferMessages<T>>::get(transfer.message_id).chain),
+            Kind::Limits => Some(<LimitMessages<T>>::get(transfer.message_id).chain),
+            _ => None,
+        };
+        match chain_id {
+            Some(chain_id) => Self::chain_validator_set(chain_id),
+            None => (<ValidatorAccounts<T>>::get(), Self::quorum()),
+        }
+    }
+
+    /// validators of the chain and their quorum, bridge validators vote for chains without own set
+    pub fn chain_validator_set(chain_id: ChainId) -> (Vec<T::AccountId>, u64) {
+        match Self::chains(chain_id) {
+            Some(chain) if !chain.validators.is_empty() => (chain.validators, chain.quorum),
+            _ => (<ValidatorAccounts<T>>::get(), Self::quorum()),
+        }
+    }
+
+    fn queue_for_pruning(message_id: T::Hash, kind: Kind) {
+        let now = <system::Module<T>>::block_number();
+        let tail = <PruneQueueTail>::get();
+        <PruneQueue<T>>::insert(tail, (now, message_id, kind));
+        <PruneQueueTail>::put(tail + 1);
+    }
+
+    /// remove messages closed `Retention` blocks ago, a bounded number per block
+    fn prune_closed_messages(now: T::BlockNumber) {
+        let retention = Self::retention();
+        if retention.is_zero() {
+            return;
+        }
+        let mut head = <PruneQueueHead>::get();
+        let tail = <PruneQueueTail>::get().min(head + MAX_PRUNED_PER_BLOCK);
+        while head < tail {
+            match <PruneQueue<T>>::get(head) {
+                Some((closed_at, _, _)) if closed_at + retention > now => break,
+                Some((_, message_id, kind)) => Self::prune_message(message_id, kind),
+                None => (),
+            }
+            <PruneQueue<T>>::remove(head);
+            head += 1;
+        }
+        <PruneQueueHead>::put(head);
+    }
+
+    /// transfers reopened for the confirmation since are queued again
+    fn prune_message(message_id: T::Hash, kind: Kind) {
+        if !<TransferId<T>>::contains_key(message_id) {
+            return;
+        }
+        let transfer_id = <TransferId<T>>::get(message_id);
+        let transfer = <BridgeTransfers<T>>::get(transfer_id);
+        if transfer.open || (kind == Kind::Transfer && !Self::is_finished(message_id)) {
+            Self::queue_for_pruning(message_id, kind);
+            return;
+        }
+
+        let (validators, _) = Self::validator_set_of(&transfer);
+        for validator in validators {
+            <ValidatorVotes<T>>::remove((transfer_id, validator));
+        }
+        match kind {
+            Kind::Transfer => {
+                <TransferMessages<T>>::remove(message_id);
+                <StatusHistory<T>>::remove(message_id);
+                match <TransferDeadlines<T>>::take(message_id) {
+                    Some(deadline) if <ExpiringTransfers<T>>::contains_key(deadline) => {
+                        <ExpiringTransfers<T>>::mutate(deadline, |ids| {
+                            ids.retain(|id| *id != message_id)
+                        })
+                    }
+                    _ => (),
                 }
-            });
+                <TransferFees<T>>::remove(message_id);
+                <Attestations<T>>::remove(message_id);
+                <RejectedMints<T>>::remove(message_id);
+            }
+            Kind::Limits => <LimitMessages<T>>::remove(message_id),
+            Kind::Validator => <ValidatorHistory<T>>::remove(message_id),
+            Kind::Bridge => <BridgeMessages<T>>::remove(message_id),
+        }
+        <SignedVotes<T>>::remove_prefix(message_id);
+        <Offences<T>>::remove_prefix(message_id);
+        <BridgeTransfers<T>>::remove(transfer_id);
+        <TransferId<T>>::remove(message_id);
+        <MessageId<T>>::remove(transfer_id);
+        <PrunedMessages<T>>::insert(message_id, true);
+    }
+
+    fn is_finished(message_id: T::Hash) -> bool {
+        let status = <TransferMessages<T>>::get(message_id).status;
+        status == Status::Confirmed || status == Status::Canceled
+    }
+
+    /// ids of messages from other chains can repeat, so they are hashed with the chain,
+    /// Ethereum messages keep the ids they had before the chain registry
+    pub fn chain_message_id(chain_id: ChainId, message_id: T::Hash) -> T::Hash {
+        match chain_id {
+            ETHEREUM_CHAIN_ID => message_id,
+            _ => (chain_id, message_id).using_encoded(<T as system::Trait>::Hashing::hash),
+        }
+    }
+
+    fn storage_prefix(item: &[u8]) -> Vec<u8> {
+        let mut prefix = twox_128(b"Bridge").to_vec();
+        prefix.extend_from_slice(&twox_128(item));
+        prefix
+    }
+
+    /// migrates the next `MIGRATED_PER_BLOCK` transfers, then removes the old
+    /// daily limits, the bridge is set back when everything is migrated
+    fn migrate_storage(mut migration: StorageMigration) {
+        let end = <BridgeTransfersCount>::get().min(migration.next + MIGRATED_PER_BLOCK);
+        for transfer_id in migration.next..end {
+            Self::migrate_transfer(transfer_id);
+        }
+        let mut left = MIGRATED_PER_BLOCK - (end - migration.next);
+        migration.next = end;
+
+        // daily limits are replaced by the rolling windows starting empty
+        for item in &[&b"DailyHolds"[..], b"DailyLimits", b"DailyBlocked"] {
+            left -= Self::drain(item, left);
+        }
+
+        if left > 0 {
+            <BridgeIsOperational>::put(migration.operational);
+            <StorageMigrationProgress>::kill();
+        } else {
+            <StorageMigrationProgress>::put(migration);
+        }
+    }
+
+    fn migrate_transfer(transfer_id: ProposalId) {
+        let transfer = <BridgeTransfers<T>>::get(transfer_id);
+        let append = |key: Vec<u8>, field: Vec<u8>| {
+            if let Some(mut message) = unhashed::get_raw(&key) {
+                message.extend(field);
+                unhashed::put_raw(&key, &message);
+            }
+        };
+
+        // everything bridged before the chain registry belongs to Ethereum,
+        // chain id is the last field of the messages
+        match transfer.kind {
+            Kind::Transfer => append(
+                <TransferMessages<T>>::hashed_key_for(transfer.message_id),
+                ETHEREUM_CHAIN_ID.encode(),
+            ),
+            // limits of the older limit messages were applied to every token
+            Kind::Limits => {
+                let token_id = <token::Module<T>>::tokens()
+                    .first()
+                    .map(|t| t.id)
+                    .unwrap_or_default();
+                append(
+                    <LimitMessages<T>>::hashed_key_for(transfer.message_id),
+                    (token_id, ETHEREUM_CHAIN_ID).encode(),
+                );
+            }
+            _ => (),
+        }
+
+        match transfer.kind {
+            // pending volumes of each token are summed up from the pending transfers,
+            // messages made before `PendingTransfers` and `PruneQueue` are tracked from the upgrade
+            Kind::Transfer if !Self::is_finished(transfer.message_id) => {
+                let message = <TransferMessages<T>>::get(transfer.message_id);
+                if message.status == Status::Pending {
+                    let add =
+                        |volume: &mut T::Balance| *volume = volume.saturating_add(message.amount);
+                    match message.action {
+                        Status::Withdraw => {
+                            <CurrentPendingBurn<T>>::mutate(message.chain, message.token, add)
+                        }
+                        Status::Deposit => {
+                            <CurrentPendingMint<T>>::mutate(message.chain, message.token, add)
+                        }
+                        _ => (),
+                    }
+                }
+                <PendingTransfers<T>>::insert(
+                    message.token,
+                    message.message_id,
+                    message.substrate_address,
+                );
+            }
+            _ if !transfer.open => Self::queue_for_pruning(transfer.message_id, transfer.kind),
+            _ => (),
+        }
+    }
+
+    /// removes up to `limit` entries of the item, returns the number of removed entries
+    fn drain(item: &[u8], limit: u64) -> u64 {
+        let prefix = Self::storage_prefix(item);
+        let mut removed = 0;
+        while removed < limit {
+            match sp_io::storage::next_key(&prefix).filter(|k| k.starts_with(&prefix)) {
+                Some(key) => unhashed::kill(&key),
+                None => break,
+            }
+            removed += 1;
+        }
+        removed
+    }
+
+    /// the chain registry starts with Ethereum and its tokens
+    fn migrate_to_chains() {
+        // contract address should be set with register_chain
+        <Chains<T>>::insert(
+            ETHEREUM_CHAIN_ID,
+            Chain {
+                contract: H160::zero(),
+                tokens: Self::bridged_tokens(),
+                validators: Vec::new(),
+                quorum: 0,
+            },
+        );
+    }
+
+    /// volume of the account with the window rolled over to the current block
+    fn current_volume(
+        chain_id: ChainId,
+        token_id: TokenId,
+        account: &T::AccountId,
+    ) -> WindowVolume<T::BlockNumber, T::Balance> {
+        let window = <system::Module<T>>::block_number() / T::LimitWindow::get();
+        let mut volume = <ChainAccountVolumes<T>>::get((chain_id, token_id), account);
+        if volume.window != window {
+            volume.previous = if volume.window + One::one() == window {
+                volume.current
+            } else {
+                Zero::zero()
+            };
+            volume.current = Zero::zero();
+            volume.window = window;
         }
+        volume
+    }
+
+    /// volume of the last `LimitWindow` blocks, the previous window is weighted
+    /// by its part still within the rolling window
+    pub fn rolling_volume(
+        chain_id: ChainId,
+        token_id: TokenId,
+        account: &T::AccountId,
+    ) -> T::Balance {
+        let volume = Self::current_volume(chain_id, token_id, account);
+        let length = T::LimitWindow::get();
+        let elapsed = <system::Module<T>>::block_number() % length;
+        let previous =
+            Permill::from_rational_approximation(length - elapsed, length) * volume.previous;
+        volume.current.saturating_add(previous)
+    }
+
+    fn add_volume(
+        chain_id: ChainId,
+        token_id: TokenId,
+        account: &T::AccountId,
+        amount: T::Balance,
+    ) {
+        let mut volume = Self::current_volume(chain_id, token_id, account);
+        volume.current = volume.current.saturating_add(amount);
+        <ChainAccountVolumes<T>>::insert((chain_id, token_id), account, volume);
+    }
+
+    /// cancelled transfers don't count towards the limit
+    fn release_volume(
+        chain_id: ChainId,
+        token_id: TokenId,
+        account: &T::AccountId,
+        amount: T::Balance,
+    ) {
+        let mut volume = Self::current_volume(chain_id, token_id, account);
+        let from_current = volume.current.min(amount);
+        volume.current -= from_current;
+        volume.previous = volume.previous.saturating_sub(amount - from_current);
+        <ChainAccountVolumes<T>>::insert((chain_id, token_id), account, volume);
+    }
+
+    /// block the account for a window once it reaches the volume limit
+    fn check_account_volume(
+        chain_id: ChainId,
+        token_id: TokenId,
+        account: &T::AccountId,
+        amount: T::Balance,
+    ) -> Result<()> {
+        let now = <system::Module<T>>::block_number();
+        let blocked =
+            Self::blocked_until((chain_id, token_id), account).map_or(false, |until| until > now);
         ensure!(
-            can_burn && !user_blocked,
+            !blocked,
             "Transfer declined, user blocked due to daily volume limit."
         );
 
+        let limit = <CurrentLimits<T>>::get(chain_id, token_id).day_max_limit_for_one_address;
+        if Self::rolling_volume(chain_id, token_id, account).saturating_add(amount) >= limit {
+            let until = now + T::LimitWindow::get();
+            <ChainBlockedAccounts<T>>::insert((chain_id, token_id), account, until);
+            <Unblocks<T>>::mutate(until, |accounts| {
+                accounts.push((chain_id, token_id, account.clone()))
+            });
+            let moment = <timestamp::Module<T>>::get();
+            let hash = (moment, account).using_encoded(<T as system::Trait>::Hashing::hash);
+            Self::deposit_event(RawEvent::AccountPausedMessage(
+                hash,
+                account.clone(),
+                moment,
+                token_id,
+            ));
+            fail!("Transfer declined, user blocked due to daily volume limit.");
+        }
         Ok(())
     }
-    fn check_amount(amount: T::Balance) -> Result<()> {
-        let max = <CurrentLimits<T>>::get().max_tx_value;
-        let min = <CurrentLimits<T>>::get().min_tx_value;
+    fn check_amount(chain_id: ChainId, token_id: TokenId, amount: T::Balance) -> Result<()> {
+        let limits = <CurrentLimits<T>>::get(chain_id, token_id);
+        let max = limits.max_tx_value;
+        let min = limits.min_tx_value;
 
         ensure!(
             amount > min,
@@ -705,20 +2166,22 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
     //open transactions check
-    fn check_pending_burn(amount: T::Balance) -> Result<()> {
-        let new_pending_volume = <CurrentPendingBurn<T>>::get()
+    fn check_pending_burn(chain_id: ChainId, token_id: TokenId, amount: T::Balance) -> Result<()> {
+        let new_pending_volume = <CurrentPendingBurn<T>>::get(chain_id, token_id)
             .checked_add(&amount)
             .ok_or("Overflow adding to new pending burn volume")?;
-        let can_burn = new_pending_volume < <CurrentLimits<T>>::get().max_pending_tx_limit;
+        let can_burn =
+            new_pending_volume < <CurrentLimits<T>>::get(chain_id, token_id).max_pending_tx_limit;
         ensure!(can_burn, "Too many pending burn transactions.");
         Ok(())
     }
 
-    fn check_pending_mint(amount: T::Balance) -> Result<()> {
-        let new_pending_volume = <CurrentPendingMint<T>>::get()
+    fn check_pending_mint(chain_id: ChainId, token_id: TokenId, amount: T::Balance) -> Result<()> {
+        let new_pending_volume = <CurrentPendingMint<T>>::get(chain_id, token_id)
             .checked_add(&amount)
             .ok_or("Overflow adding to new pending mint volume")?;
-        let can_burn = new_pending_volume < <CurrentLimits<T>>::get().max_pending_tx_limit;
+        let can_burn =
+            new_pending_volume < <CurrentLimits<T>>::get(chain_id, token_id).max_pending_tx_limit;
         ensure!(can_burn, "Too many pending mint transactions.");
         Ok(())
     }
@@ -740,64 +2203,392 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
-    fn check_daily_holds(
-        message: TransferMessage<T::AccountId, T::Hash, T::Balance>,
+    /// withdrawals are limited to 75% of the balance within a window after the first deposit
+    fn check_first_deposit_hold(
+        token_id: TokenId,
+        from: &T::AccountId,
+        amount: T::Balance,
     ) -> Result<()> {
-        let from = message.substrate_address;
-        let first_tx = <DailyHolds<T>>::get(from.clone());
-        let daily_hold = T::BlockNumber::from(DAY_IN_BLOCKS);
-        let day_passed = first_tx.0 + daily_hold < T::BlockNumber::from(0);
-
-        if !day_passed {
-            let account_balance = <token::Module<T>>::balance_of((message.token, from));
-            // 75% of potentially really big numbers
-            let allowed_amount = account_balance
-                .checked_div(&T::Balance::from(100))
-                .expect("Failed to calculate allowed withdraw amount")
-                .checked_mul(&T::Balance::from(75))
-                .expect("Failed to calculate allowed withdraw amount");
-
-            if message.amount > allowed_amount {
-                Self::update_status(message.message_id, Status::Canceled, Kind::Transfer)?;
-                fail!("Cannot withdraw more that 75% of first day deposit.");
-            }
+        let held = Self::first_deposit(from).map_or(false, |first| {
+            first + T::LimitWindow::get() > <system::Module<T>>::block_number()
+        });
+
+        if held {
+            let allowed_amount = Permill::from_percent(75) * Self::total_balance_of(token_id, from);
+            ensure!(
+                amount <= allowed_amount,
+                "Cannot withdraw more that 75% of first day deposit."
+            );
         }
 
         Ok(())
     }
 }
 
-/// tests for this module
-#[cfg(test)]
-mod tests {
-    use super::*;
-    //TODO: fix limits after adding them into config
-    use crate::types::Token;
-    use frame_support::{
-        assert_noop, assert_ok, impl_outer_origin, parameter_types,
-        traits::{Get, OnFinalize},
-        weights::Weight,
-    };
-    use sp_core::{H160, H256};
-    use sp_runtime::{
-        testing::Header,
-        traits::{BlakeTwo256, IdentityLookup},
-        DispatchError, Perbill,
-    };
-    use std::cell::RefCell;
+// runtime API
+impl<T: Trait> Module<T> {
+    /// transfer message with its votes and status changes
+    pub fn transfer_info(
+        message_id: T::Hash,
+    ) -> Option<TransferInfo<T::AccountId, T::Hash, T::Balance, T::BlockNumber>> {
+        if !<TransferMessages<T>>::contains_key(message_id) {
+            return None;
+        }
+        let transfer = <BridgeTransfers<T>>::get(<TransferId<T>>::get(message_id));
+        let (_, quorum) = Self::validator_set_of(&transfer);
+        Some(TransferInfo {
+            message: <TransferMessages<T>>::get(message_id),
+            open: transfer.open,
+            votes: transfer.votes,
+            quorum,
+            deadline: Self::transfer_deadline(message_id),
+            history: Self::status_history(message_id),
+        })
+    }
 
-    pub type BlockNumber = u64;
-    pub type Balance = u128;
+    /// transfers of the token neither confirmed nor canceled
+    pub fn pending_transfers(
+        token_id: TokenId,
+    ) -> Vec<TransferMessage<T::AccountId, T::Hash, T::Balance>> {
+        <PendingTransfers<T>>::iter(token_id)
+            .map(|(message_id, _)| <TransferMessages<T>>::get(message_id))
+            .collect()
+    }
 
-    thread_local! {
-        static EXISTENTIAL_DEPOSIT: RefCell<u128> = RefCell::new(500);
+    /// unfinished transfers of the account in all tokens
+    pub fn account_pending_transfers(
+        account: &T::AccountId,
+    ) -> Vec<TransferMessage<T::AccountId, T::Hash, T::Balance>> {
+        Self::bridged_tokens()
+            .into_iter()
+            .flat_map(|token_id| <PendingTransfers<T>>::iter(token_id))
+            .filter(|(_, sender)| sender == account)
+            .map(|(message_id, _)| <TransferMessages<T>>::get(message_id))
+            .collect()
     }
 
-    impl_outer_origin! {
-        pub enum Origin for Test {}
+    /// amount the account can still transfer within the rolling window,
+    /// transfers should be below it
+    pub fn remaining_allowance(
+        chain_id: ChainId,
+        token_id: TokenId,
+        account: &T::AccountId,
+    ) -> T::Balance {
+        let now = <system::Module<T>>::block_number();
+        if Self::blocked_until((chain_id, token_id), account).map_or(false, |until| until > now) {
+            return Zero::zero();
+        }
+        <CurrentLimits<T>>::get(chain_id, token_id)
+            .day_max_limit_for_one_address
+            .saturating_sub(Self::rolling_volume(chain_id, token_id, account))
     }
-    pub struct ExistentialDeposit;
-    impl Get<u128> for ExistentialDeposit {
+}
+
+// offchain worker
+impl<T: Trait> Module<T> {
+    /// Vote for the contract events of the confirmed Ethereum blocks since the last run.
+    /// The first run only remembers the current block, earlier events are relayed by others.
+    fn relay_ethereum_events(chain_id: ChainId, chain: Chain<T::AccountId>) -> Result<()> {
+        let url = match sp_io::offchain::local_storage_get(
+            StorageKind::PERSISTENT,
+            &offchain_key(OFFCHAIN_RPC_URL, chain_id),
+        ) {
+            Some(url) => url,
+            None => return Ok(()),
+        };
+        // DAIBridge relays a single token, the first one of the chain
+        let token_id = *chain.tokens.first().ok_or("Chain has no tokens")?;
+
+        let head = hex_quantity(Self::eth_rpc(&url, b"eth_blockNumber", b"[]")?)
+            .ok_or("Invalid Ethereum block number")?;
+        let safe = head.saturating_sub(OFFCHAIN_CONFIRMATIONS);
+        let last_block_key = offchain_key(OFFCHAIN_LAST_BLOCK, chain_id);
+        let last_block = StorageValueRef::persistent(&last_block_key);
+        let last = match last_block.get::<u64>() {
+            Some(Some(last)) => last,
+            _ => {
+                last_block.set(&safe);
+                return Ok(());
+            }
+        };
+        if safe <= last {
+            return Ok(());
+        }
+        let to = safe.min(last + OFFCHAIN_MAX_BLOCKS);
+
+        // claim the blocks, so the worker of the next block doesn't vote for them again
+        let claimed = last_block.mutate(|stored: Option<Option<u64>>| match stored {
+            Some(Some(stored)) if stored == last => Ok(to),
+            _ => Err(()),
+        });
+        if claimed != Ok(Ok(to)) {
+            return Ok(());
+        }
+
+        let result = Self::eth_logs(&url, chain.contract, last + 1, to).and_then(|logs| {
+            logs.into_iter()
+                .filter_map(|log| Self::eth_event_call(chain_id, token_id, log))
+                .try_for_each(|call| {
+                    ensure!(
+                        T::SubmitTransaction::submit_signed(call),
+                        "No local bridge key to sign with"
+                    );
+                    Ok(())
+                })
+        });
+        if result.is_err() {
+            // give the blocks back to the next run
+            let _ = last_block.mutate(|stored: Option<Option<u64>>| match stored {
+                Some(Some(stored)) if stored == to => Ok(last),
+                _ => Err(()),
+            });
+        }
+        result
+    }
+
+    fn eth_logs(url: &[u8], contract: H160, from: u64, to: u64) -> Result<Vec<JsonValue>> {
+        match Self::eth_rpc(url, b"eth_getLogs", &eth_logs_params(contract, from, to))? {
+            JsonValue::Array(logs) => Ok(logs),
+            _ => Err("Invalid Ethereum logs"),
+        }
+    }
+
+    /// `multi_signed_mint` for RelayMessage and `confirm_transfer` for WithdrawMessage
+    fn eth_event_call(chain_id: ChainId, token_id: TokenId, log: JsonValue) -> Option<Call<T>> {
+        let mut topic = None;
+        let mut data = None;
+        if let JsonValue::Object(fields) = log {
+            for (key, value) in fields {
+                match (&json_key(&key)[..], value) {
+                    (b"topics", JsonValue::Array(topics)) => {
+                        topic = topics.into_iter().next().and_then(hex_data)
+                    }
+                    (b"data", value) => data = hex_data(value),
+                    // log of a block removed by reorganization
+                    (b"removed", JsonValue::Boolean(true)) => return None,
+                    _ => {}
+                }
+            }
+        }
+        let (topic, data) = (topic?, data?);
+        let word = |index: usize| data.get(index * 32..(index + 1) * 32);
+        let message_id = T::Hash::decode(&mut word(0)?).ok()?;
+
+        if topic[..] == keccak_256(RELAY_MESSAGE_EVENT)[..] {
+            let from = H160::from_slice(&word(1)?[12..]);
+            let to = T::AccountId::decode(&mut word(2)?).ok()?;
+            let amount = word(3)?;
+            if amount[..16].iter().any(|byte| *byte != 0) {
+                return None;
+            }
+            let mut raw = [0u8; 16];
+            raw.copy_from_slice(&amount[16..]);
+            let raw = u128::from_be_bytes(raw);
+            let amount: T::Balance = raw.saturated_into();
+            if amount.saturated_into::<u128>() != raw {
+                return None;
+            }
+            Some(Call::multi_signed_mint(
+                chain_id, message_id, from, to, token_id, amount,
+            ))
+        } else if topic[..] == keccak_256(WITHDRAW_MESSAGE_EVENT)[..] {
+            Some(Call::confirm_transfer(message_id))
+        } else {
+            None
+        }
+    }
+
+    fn eth_rpc(url: &[u8], method: &[u8], params: &[u8]) -> Result<JsonValue> {
+        let url = core::str::from_utf8(url).map_err(|_| "Invalid Ethereum RPC url")?;
+        let mut body = Vec::new();
+        body.push(eth_rpc_request(method, params));
+        let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(OFFCHAIN_TIMEOUT_MS));
+        let pending = http::Request::post(url, body)
+            .add_header("Content-Type", "application/json")
+            .deadline(deadline)
+            .send()
+            .map_err(|_| "Error in sending Ethereum RPC request")?;
+        let response = pending
+            .try_wait(deadline)
+            .map_err(|_| "Ethereum RPC request timed out")?
+            .map_err(|_| "Error in waiting Ethereum RPC response")?;
+        ensure!(
+            response.code == 200,
+            "Non-200 status code returned from Ethereum RPC"
+        );
+
+        let response = response.body().collect::<Vec<u8>>();
+        let json = simple_json::parse_json(
+            core::str::from_utf8(&response).map_err(|_| "Ethereum RPC response is not a string")?,
+        )
+        .map_err(|_| "JSON parsing error")?;
+        if let JsonValue::Object(fields) = json {
+            for (key, value) in fields {
+                if json_key(&key) == b"result" {
+                    return Ok(value);
+                }
+            }
+        }
+        Err("Ethereum RPC returned an error")
+    }
+}
+
+fn offchain_key(prefix: &[u8], chain_id: ChainId) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    key.extend(chain_id.encode());
+    key
+}
+
+fn eth_rpc_request(method: &[u8], params: &[u8]) -> Vec<u8> {
+    let mut request = b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"".to_vec();
+    request.extend_from_slice(method);
+    request.extend_from_slice(b"\",\"params\":");
+    request.extend_from_slice(params);
+    request.push(b'}');
+    request
+}
+
+fn eth_logs_params(contract: H160, from: u64, to: u64) -> Vec<u8> {
+    let mut params = b"[{\"address\":\"".to_vec();
+    params.extend(hex_encode(contract.as_bytes()));
+    params.extend_from_slice(b"\",\"fromBlock\":\"");
+    params.extend(hex_encode_quantity(from));
+    params.extend_from_slice(b"\",\"toBlock\":\"");
+    params.extend(hex_encode_quantity(to));
+    params.extend_from_slice(b"\",\"topics\":[[\"");
+    params.extend(hex_encode(&keccak_256(RELAY_MESSAGE_EVENT)));
+    params.extend_from_slice(b"\",\"");
+    params.extend(hex_encode(&keccak_256(WITHDRAW_MESSAGE_EVENT)));
+    params.extend_from_slice(b"\"]]}]");
+    params
+}
+
+// JSON keys are ASCII
+fn json_key(key: &[char]) -> Vec<u8> {
+    key.iter().map(|c| *c as u8).collect()
+}
+
+fn hex_digits(value: JsonValue) -> Option<Vec<u8>> {
+    match value {
+        JsonValue::String(chars) => {
+            let chars = json_key(&chars);
+            if !chars.starts_with(b"0x") {
+                return None;
+            }
+            chars[2..]
+                .iter()
+                .map(|c| (*c as char).to_digit(16).map(|digit| digit as u8))
+                .collect()
+        }
+        _ => None,
+    }
+}
+
+fn hex_quantity(value: JsonValue) -> Option<u64> {
+    hex_digits(value)?
+        .into_iter()
+        .try_fold(0u64, |number, digit| {
+            number.checked_mul(16)?.checked_add(digit.into())
+        })
+}
+
+fn hex_data(value: JsonValue) -> Option<Vec<u8>> {
+    let digits = hex_digits(value)?;
+    if digits.len() % 2 != 0 {
+        return None;
+    }
+    Some(
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect(),
+    )
+}
+
+const HEX_CHARS: &[u8] = b"0123456789abcdef";
+
+fn hex_encode(bytes: &[u8]) -> Vec<u8> {
+    let mut hex = b"0x".to_vec();
+    for byte in bytes {
+        hex.push(HEX_CHARS[(byte >> 4) as usize]);
+        hex.push(HEX_CHARS[(byte & 0xf) as usize]);
+    }
+    hex
+}
+
+fn hex_encode_quantity(number: u64) -> Vec<u8> {
+    let hex = hex_encode(&number.to_be_bytes());
+    // quantities have no leading zeroes
+    let start = hex[2..]
+        .iter()
+        .position(|c| *c != b'0')
+        .map(|position| position + 2)
+        .unwrap_or(hex.len() - 1);
+    let mut quantity = b"0x".to_vec();
+    quantity.extend_from_slice(&hex[start..]);
+    quantity
+}
+
+/// Ethereum keys of test validators
+#[cfg(test)]
+pub(crate) mod test_signer {
+    use super::*;
+
+    fn secret(account: u64) -> secp256k1::SecretKey {
+        secp256k1::SecretKey::parse(&[account as u8 + 1; 32]).unwrap()
+    }
+
+    pub fn address(account: u64) -> H160 {
+        let public = secp256k1::PublicKey::from_secret_key(&secret(account)).serialize();
+        H160::from_slice(&keccak_256(&public[1..])[12..])
+    }
+
+    pub fn sign(account: u64, digest: [u8; 32]) -> ecdsa::Signature {
+        let message = secp256k1::Message::parse(&digest);
+        let (signature, recovery_id) = secp256k1::sign(&message, &secret(account));
+        let mut raw = [0u8; 65];
+        raw[..64].copy_from_slice(&signature.serialize());
+        raw[64] = recovery_id.serialize();
+        ecdsa::Signature::from_raw(raw)
+    }
+}
+
+/// tests for this module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    //TODO: fix limits after adding them into config
+    use crate::types::Token;
+    use frame_support::{
+        assert_noop, assert_ok, impl_outer_origin, parameter_types,
+        traits::{Get, OnFinalize},
+        weights::Weight,
+    };
+    use sp_core::{
+        offchain::{testing, OffchainExt},
+        H160, H256,
+    };
+    use sp_runtime::{
+        testing::{Header, TestSignature, UintAuthorityId},
+        traits::{BlakeTwo256, IdentityLookup, OffchainWorker},
+        DispatchError, Perbill,
+    };
+    use std::cell::RefCell;
+
+    pub type BlockNumber = u64;
+    pub type Balance = u128;
+
+    thread_local! {
+        static EXISTENTIAL_DEPOSIT: RefCell<u128> = RefCell::new(500);
+        static SUBMITTED_CALLS: RefCell<Vec<Call<Test>>> = RefCell::new(Vec::new());
+        static HAS_BRIDGE_KEY: RefCell<bool> = RefCell::new(true);
+    }
+
+    impl_outer_origin! {
+        pub enum Origin for Test {}
+    }
+    pub struct ExistentialDeposit;
+    impl Get<u128> for ExistentialDeposit {
         fn get() -> u128 {
             EXISTENTIAL_DEPOSIT.with(|v| *v.borrow())
         }
@@ -854,15 +2645,45 @@ mod tests {
     }
     impl token::Trait for Test {
         type Event = ();
+        type Signature = TestSignature;
+        type Signer = UintAuthorityId;
+        type SnapshotOrigin = system::EnsureRoot<u64>;
+    }
+    parameter_types! {
+        pub const TransferTtl: BlockNumber = TRANSFER_TTL;
+        pub const LimitWindow: BlockNumber = DAY_IN_BLOCKS;
+        pub const ValidatorBond: Balance = VALIDATOR_BOND;
+        pub const TreasuryAccount: u64 = 100;
     }
     impl Trait for Test {
         type Event = ();
+        type TransferTtl = TransferTtl;
+        type LimitWindow = LimitWindow;
+        type ValidatorBond = ValidatorBond;
+        type Slash = ();
+        type TreasuryAccount = TreasuryAccount;
+        type GovernanceOrigin = system::EnsureRoot<u64>;
+        type ComplianceOrigin = system::EnsureRoot<u64>;
+        type SubmitTransaction = TestSubmitter;
+    }
+
+    // records offchain worker calls instead of sending them
+    pub struct TestSubmitter;
+    impl SubmitSignedCall<Test> for TestSubmitter {
+        fn submit_signed(call: Call<Test>) -> bool {
+            HAS_BRIDGE_KEY.with(|has_key| *has_key.borrow())
+                && SUBMITTED_CALLS.with(|calls| {
+                    calls.borrow_mut().push(call);
+                    true
+                })
+        }
     }
 
     type BridgeModule = Module<Test>;
     type TokenModule = token::Module<Test>;
     type TimestampModule = timestamp::Module<Test>;
     type System = system::Module<Test>;
+    type Balances = balances::Module<Test>;
 
     const ETH_MESSAGE_ID: &[u8; 32] = b"0x5617efe391571b5dc8230db92ba65b";
     const ETH_MESSAGE_ID1: &[u8; 32] = b"0x5617iru391571b5dc8230db92ba65b";
@@ -888,6 +2709,11 @@ mod tests {
     const USER8: u64 = 12;
     const USER9: u64 = 13;
     const TOKEN_ID: u32 = 0;
+    const CHAIN_ID: ChainId = ETHEREUM_CHAIN_ID;
+    const OTHER_CHAIN_ID: ChainId = 1;
+    const TRANSFER_TTL: BlockNumber = 100;
+    const DAY_IN_BLOCKS: BlockNumber = 14_400;
+    const VALIDATOR_BOND: Balance = 1000;
 
     pub struct ExtBuilder {
         existential_deposit: u128,
@@ -916,6 +2742,7 @@ mod tests {
                     (V1, 100000),
                     (V2, 100000),
                     (V3, 100000),
+                    (V4, 100000),
                     (USER1, 100000),
                     (USER2, 300000),
                 ],
@@ -934,15 +2761,46 @@ mod tests {
             let _ = GenesisConfig::<Test> {
                 validators_count: 3u32,
                 validator_accounts: vec![V1, V2, V3],
-                current_limits: vec![100, 200, 50, 400, 1],
+                chains: vec![(
+                    CHAIN_ID,
+                    H160::from(ETH_ADDRESS),
+                    vec![TOKEN_ID, NATIVE_TOKEN_ID],
+                )],
+                current_limits: vec![
+                    (CHAIN_ID, TOKEN_ID, vec![100, 200, 50, 400, 1]),
+                    (
+                        CHAIN_ID,
+                        NATIVE_TOKEN_ID,
+                        vec![10000, 20000, 5000, 40000, 500],
+                    ),
+                ],
+                fees: vec![],
             }
             .assimilate_storage(&mut storage);
 
-            let ext = sp_io::TestExternalities::from(storage);
+            let mut ext = sp_io::TestExternalities::from(storage);
+            ext.execute_with(|| {
+                for validator in &[V1, V2, V3, V4] {
+                    <EthSigners<Test>>::insert(validator, test_signer::address(*validator));
+                }
+            });
             ext
         }
     }
 
+    fn attestation(validator: u64, message_id: H256) -> ecdsa::Signature {
+        let digest = BridgeModule::attestation_digest(&BridgeModule::messages(message_id));
+        test_signer::sign(validator, digest)
+    }
+
+    fn approve_transfer(validator: u64, message_id: H256) -> DispatchResult {
+        BridgeModule::approve_transfer(
+            Origin::signed(validator),
+            message_id,
+            attestation(validator, message_id),
+        )
+    }
+
     //fast forward approximately
     fn run_to_block(n: u64) {
         while System::block_number() < n {
@@ -970,83 +2828,868 @@ mod tests {
             let token = TokenModule::token_map(0);
             println!("{:?}", token);
 
-            //substrate <----- ETH
+            //substrate <----- ETH
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V2),
+                CHAIN_ID,
+                message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                amount
+            ));
+            let mut message = BridgeModule::messages(message_id);
+            assert_eq!(message.status, Status::Pending);
+
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V1),
+                CHAIN_ID,
+                message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                amount
+            ));
+            message = BridgeModule::messages(message_id);
+            assert_eq!(message.status, Status::Confirmed);
+
+            let transfer = BridgeModule::transfers(0);
+            assert_eq!(transfer.open, false);
+
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), amount);
+            assert_eq!(TokenModule::total_supply(TOKEN_ID), amount);
+        })
+    }
+    #[test]
+    fn token_eth2sub_closed_transfer_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            let message_id = H256::from(ETH_MESSAGE_ID);
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 99;
+
+            //substrate <----- ETH
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V2),
+                CHAIN_ID,
+                message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                amount
+            ));
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V1),
+                CHAIN_ID,
+                message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                amount
+            ));
+            assert_noop!(
+                BridgeModule::multi_signed_mint(
+                    Origin::signed(V3),
+                    CHAIN_ID,
+                    message_id,
+                    eth_address,
+                    USER2,
+                    TOKEN_ID,
+                    amount
+                ),
+                "This transfer is not open"
+            );
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), amount);
+            assert_eq!(TokenModule::total_supply(TOKEN_ID), amount);
+            let transfer = BridgeModule::transfers(0);
+            assert_eq!(transfer.open, false);
+
+            let message = BridgeModule::messages(message_id);
+            assert_eq!(message.status, Status::Confirmed);
+        })
+    }
+
+    #[test]
+    fn token_sub2eth_burn_works() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount1 = 600;
+            let amount2 = 49;
+
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, amount1);
+
+            //substrate ----> ETH
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount2
+            ));
+            //RelayMessage(message_id) event emitted
+
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
+            let get_message = || BridgeModule::messages(sub_message_id);
+
+            let mut message = get_message();
+            assert_eq!(message.status, Status::Withdraw);
+
+            //approval
+            assert_eq!(TokenModule::locked(0, USER2), 0);
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_ok!(approve_transfer(V2, sub_message_id));
+
+            message = get_message();
+            assert_eq!(message.status, Status::Approved);
+
+            // at this point transfer is in Approved status and are waiting for confirmation
+            // from ethereum side to burn. Funds are locked.
+            assert_eq!(TokenModule::locked(0, USER2), amount2);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), amount1);
+            // once it happends, validators call confirm_transfer
+
+            assert_ok!(BridgeModule::confirm_transfer(
+                Origin::signed(V2),
+                sub_message_id
+            ));
+
+            message = get_message();
+            let transfer = BridgeModule::transfers(1);
+            assert_eq!(message.status, Status::Confirmed);
+            assert_eq!(transfer.open, true);
+            assert_ok!(BridgeModule::confirm_transfer(
+                Origin::signed(V1),
+                sub_message_id
+            ));
+            // assert_ok!(BridgeModule::confirm_transfer(Origin::signed(USER1), sub_message_id));
+            //BurnedMessage(Hash, AccountId, H160, u64) event emitted
+            let tokens_left = amount1 - amount2;
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), tokens_left);
+            assert_eq!(TokenModule::total_supply(TOKEN_ID), tokens_left);
+        })
+    }
+    #[test]
+    fn transfer_info_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 40;
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+            assert_eq!(
+                BridgeModule::remaining_allowance(CHAIN_ID, TOKEN_ID, &USER2),
+                50
+            );
+
+            System::set_block_number(2);
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount
+            ));
+            let message_id = BridgeModule::message_id_by_transfer_id(0);
+            assert_eq!(
+                BridgeModule::remaining_allowance(CHAIN_ID, TOKEN_ID, &USER2),
+                10
+            );
+            assert_eq!(BridgeModule::pending_transfers(TOKEN_ID).len(), 1);
+            assert_eq!(BridgeModule::account_pending_transfers(&USER2).len(), 1);
+            assert!(BridgeModule::account_pending_transfers(&USER1).is_empty());
+
+            System::set_block_number(3);
+            assert_ok!(approve_transfer(V1, message_id));
+            System::set_block_number(4);
+            assert_ok!(approve_transfer(V2, message_id));
+            assert_ok!(BridgeModule::confirm_transfer(
+                Origin::signed(V2),
+                message_id
+            ));
+
+            let info = BridgeModule::transfer_info(message_id).unwrap();
+            assert_eq!(info.message.status, Status::Confirmed);
+            assert_eq!((info.votes, info.quorum), (1, 2));
+            assert_eq!(
+                info.history,
+                vec![
+                    (Status::Withdraw, 2),
+                    (Status::Pending, 3),
+                    (Status::Approved, 4),
+                    (Status::Confirmed, 4)
+                ]
+            );
+            assert!(BridgeModule::pending_transfers(TOKEN_ID).is_empty());
+            assert!(BridgeModule::transfer_info(H256::from(ETH_MESSAGE_ID)).is_none());
+        })
+    }
+    #[test]
+    fn closed_transfers_should_be_pruned() {
+        ExtBuilder::default().build().execute_with(|| {
+            let message_id = H256::from(ETH_MESSAGE_ID);
+            let eth_address = H160::from(ETH_ADDRESS);
+            let mint = |validator| {
+                BridgeModule::multi_signed_mint(
+                    Origin::signed(validator),
+                    CHAIN_ID,
+                    message_id,
+                    eth_address,
+                    USER2,
+                    TOKEN_ID,
+                    40,
+                )
+            };
+            assert_noop!(
+                BridgeModule::set_retention(Origin::signed(V1), TRANSFER_TTL),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                BridgeModule::set_retention(system::RawOrigin::Root.into(), 10),
+                "Retention can't be shorter than the transfer TTL"
+            );
+            assert_ok!(BridgeModule::set_retention(
+                system::RawOrigin::Root.into(),
+                TRANSFER_TTL
+            ));
+
+            System::set_block_number(1);
+            assert_ok!(mint(V1));
+            assert_ok!(mint(V2));
+            run_to_block(50);
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                30
+            ));
+            let transfer_id = BridgeModule::transfer_id_by_hash(message_id);
+            let withdrawal_id = BridgeModule::message_id_by_transfer_id(1);
+
+            run_to_block(101);
+            assert!(<TransferMessages<Test>>::contains_key(message_id));
+
+            run_to_block(102);
+            assert!(!<TransferMessages<Test>>::contains_key(message_id));
+            assert!(!<TransferId<Test>>::contains_key(message_id));
+            assert!(!<BridgeTransfers<Test>>::contains_key(transfer_id));
+            assert!(!BridgeModule::validator_votes((transfer_id, V1)));
+            assert!(BridgeModule::status_history(message_id).is_empty());
+            assert!(BridgeModule::is_pruned(message_id));
+            // unfinished transfers are kept
+            assert!(<TransferMessages<Test>>::contains_key(withdrawal_id));
+
+            // the deposit can't be minted again
+            assert_eq!(
+                mint(V1),
+                Err(DispatchError::Other("This transfer is already finished"))
+            );
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 40);
+
+            // pruned transfer is never expired
+            <ExpiringTransfers<Test>>::mutate(103, |ids| ids.push(message_id));
+            run_to_block(104);
+            assert!(!<TransferMessages<Test>>::contains_key(message_id));
+            assert!(!<BridgeTransfers<Test>>::contains_key(transfer_id));
+            assert!(BridgeModule::status_history(message_id).is_empty());
+        })
+    }
+    #[test]
+    fn large_withdrawals_should_be_held_for_review() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 40;
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+            assert_noop!(
+                BridgeModule::set_review_threshold(Origin::signed(V1), TOKEN_ID, Some(30)),
+                DispatchError::BadOrigin
+            );
+            assert_ok!(BridgeModule::set_review_threshold(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID,
+                Some(30)
+            ));
+
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount
+            ));
+            let message_id = BridgeModule::message_id_by_transfer_id(0);
+            assert_eq!(BridgeModule::messages(message_id).status, Status::Held);
+
+            // validators vote, but can't finish it
+            assert_ok!(approve_transfer(V1, message_id));
+            assert_ok!(approve_transfer(V2, message_id));
+            assert_eq!(BridgeModule::messages(message_id).status, Status::Held);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), 0);
+            assert_eq!(
+                BridgeModule::cancel_transfer(Origin::signed(V1), message_id),
+                Err(DispatchError::Other(
+                    "Transfer is held for the compliance review"
+                ))
+            );
+
+            assert_noop!(
+                BridgeModule::release_transfer(Origin::signed(V1), message_id),
+                DispatchError::BadOrigin
+            );
+            assert_ok!(BridgeModule::release_transfer(
+                system::RawOrigin::Root.into(),
+                message_id
+            ));
+            assert_eq!(BridgeModule::messages(message_id).status, Status::Approved);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), amount);
+            assert_eq!(BridgeModule::pending_burn_count(CHAIN_ID, TOKEN_ID), 0);
+            assert_noop!(
+                BridgeModule::release_transfer(system::RawOrigin::Root.into(), message_id),
+                "Transfer is not held"
+            );
+        })
+    }
+    #[test]
+    fn held_deposits_should_not_expire() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let mint = |validator, message_id| {
+                BridgeModule::multi_signed_mint(
+                    Origin::signed(validator),
+                    CHAIN_ID,
+                    message_id,
+                    eth_address,
+                    USER2,
+                    TOKEN_ID,
+                    40,
+                )
+            };
+            assert_ok!(BridgeModule::set_review_threshold(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID,
+                Some(30)
+            ));
+            let approved_id = H256::from(ETH_MESSAGE_ID);
+            let voted_id = H256::from(ETH_MESSAGE_ID1);
+            assert_ok!(mint(V1, approved_id));
+            assert_ok!(mint(V2, approved_id));
+            assert_ok!(mint(V1, voted_id));
+
+            run_to_block(TRANSFER_TTL + 10);
+            assert_eq!(BridgeModule::messages(approved_id).status, Status::Held);
+            assert_eq!(BridgeModule::messages(voted_id).status, Status::Held);
+            assert_eq!(BridgeModule::transfer_deadline(approved_id), None);
+
+            assert_ok!(BridgeModule::release_transfer(
+                system::RawOrigin::Root.into(),
+                approved_id
+            ));
+            assert_eq!(
+                BridgeModule::messages(approved_id).status,
+                Status::Confirmed
+            );
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 40);
+
+            // released deposit waiting for votes expires as usual
+            assert_ok!(BridgeModule::release_transfer(
+                system::RawOrigin::Root.into(),
+                voted_id
+            ));
+            assert_eq!(BridgeModule::messages(voted_id).status, Status::Pending);
+            assert_eq!(
+                BridgeModule::transfer_deadline(voted_id),
+                Some(2 * TRANSFER_TTL + 10)
+            );
+        })
+    }
+    #[test]
+    fn held_deposits_should_be_rejected() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let mint = |validator, message_id, to| {
+                BridgeModule::multi_signed_mint(
+                    Origin::signed(validator),
+                    CHAIN_ID,
+                    message_id,
+                    eth_address,
+                    to,
+                    TOKEN_ID,
+                    40,
+                )
+            };
+            assert_ok!(BridgeModule::set_review_threshold(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID,
+                Some(30)
+            ));
+
+            let held_id = H256::from(ETH_MESSAGE_ID);
+            assert_ok!(mint(V1, held_id, USER2));
+            assert_ok!(mint(V2, held_id, USER2));
+            assert_eq!(BridgeModule::messages(held_id).status, Status::Held);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 0);
+
+            assert_ok!(BridgeModule::reject_transfer(
+                system::RawOrigin::Root.into(),
+                held_id
+            ));
+            assert_eq!(BridgeModule::messages(held_id).status, Status::Canceled);
+            assert_eq!(BridgeModule::transfers(0).open, false);
+            assert_eq!(BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID), 0);
+
+            // allowed accounts are not reviewed
+            assert_ok!(BridgeModule::set_account_list(
+                system::RawOrigin::Root.into(),
+                USER3,
+                Some(AccountList::Allowed)
+            ));
+            let allowed_id = H256::from(ETH_MESSAGE_ID1);
+            assert_ok!(mint(V1, allowed_id, USER3));
+            assert_ok!(mint(V2, allowed_id, USER3));
+            assert_eq!(BridgeModule::messages(allowed_id).status, Status::Confirmed);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER3), 40);
+
+            // denied accounts can't use the bridge
+            assert_ok!(BridgeModule::set_account_list(
+                system::RawOrigin::Root.into(),
+                USER3,
+                Some(AccountList::Denied)
+            ));
+            assert_noop!(
+                mint(V1, H256::from(ETH_MESSAGE_ID2), USER3),
+                "Account is denied by compliance"
+            );
+            assert_noop!(
+                BridgeModule::set_transfer(
+                    Origin::signed(USER3),
+                    CHAIN_ID,
+                    eth_address,
+                    TOKEN_ID,
+                    10
+                ),
+                "Account is denied by compliance"
+            );
+        })
+    }
+    #[test]
+    fn native_bridge_works() {
+        ExtBuilder {
+            existential_deposit: 1000,
+        }
+        .build()
+        .execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 1000;
+            let issuance = Balances::total_issuance();
+            let bridge = BridgeModule::account_id();
+            assert_eq!(Balances::free_balance(bridge), 1000);
+            let mint = |validator, id: u8, to, amount| {
+                BridgeModule::multi_signed_mint(
+                    Origin::signed(validator),
+                    CHAIN_ID,
+                    H256::repeat_byte(id),
+                    eth_address,
+                    to,
+                    NATIVE_TOKEN_ID,
+                    amount,
+                )
+            };
+
+            //substrate ----> ETH
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER1),
+                CHAIN_ID,
+                eth_address,
+                NATIVE_TOKEN_ID,
+                amount
+            ));
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
+            for validator in &[V1, V2] {
+                assert_ok!(approve_transfer(*validator, sub_message_id));
+            }
+            assert_eq!(Balances::reserved_balance(USER1), amount);
+            assert_eq!(Balances::free_balance(USER1), 100000 - amount);
+
+            for validator in &[V1, V2] {
+                assert_ok!(BridgeModule::confirm_transfer(
+                    Origin::signed(*validator),
+                    sub_message_id
+                ));
+            }
+            assert_eq!(
+                BridgeModule::messages(sub_message_id).status,
+                Status::Confirmed
+            );
+            assert_eq!(Balances::reserved_balance(USER1), 0);
+            assert_eq!(Balances::total_balance(&USER1), 100000 - amount);
+            assert_eq!(Balances::free_balance(bridge), 1000 + amount);
+            assert_eq!(Balances::total_issuance(), issuance);
+
+            //substrate <----- ETH
+            // nothing is credited to a new account below the existential deposit
+            assert_ok!(mint(V1, 1, USER3, 600));
+            assert_noop!(
+                mint(V2, 1, USER3, 600),
+                "Amount is below the existential deposit"
+            );
+            assert_ok!(Balances::transfer(Origin::signed(USER1), USER3, 1000));
+            assert_ok!(mint(V2, 1, USER3, 600));
+            assert_eq!(Balances::free_balance(USER3), 1600);
+            assert_eq!(Balances::free_balance(bridge), 1400);
+
+            // only the native currency sent to Ethereum is released
+            assert_ok!(mint(V1, 2, USER2, 500));
+            assert_noop!(
+                mint(V2, 2, USER2, 500),
+                "Not enough native currency locked by the bridge"
+            );
+            assert_eq!(
+                BridgeModule::messages(H256::repeat_byte(2)).status,
+                Status::Pending
+            );
+            assert_eq!(Balances::free_balance(USER2), 300000);
+            assert_eq!(Balances::total_issuance(), issuance);
+        })
+    }
+    #[test]
+    fn counterparty_chains_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            let message_id = H256::from(ETH_MESSAGE_ID);
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 99;
+
+            assert_noop!(
+                BridgeModule::set_transfer(
+                    Origin::signed(USER2),
+                    OTHER_CHAIN_ID,
+                    eth_address,
+                    TOKEN_ID,
+                    amount
+                ),
+                "Chain is not registered"
+            );
+            assert_noop!(
+                BridgeModule::register_chain(
+                    Origin::signed(V1),
+                    OTHER_CHAIN_ID,
+                    eth_address,
+                    vec![TOKEN_ID],
+                    vec![V3, V4],
+                    2
+                ),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                BridgeModule::register_chain(
+                    system::RawOrigin::Root.into(),
+                    OTHER_CHAIN_ID,
+                    eth_address,
+                    vec![TOKEN_ID],
+                    vec![V3, V4],
+                    2
+                ),
+                "New validator list contains unbonded accounts."
+            );
+            assert_ok!(BridgeModule::bond(Origin::signed(V4)));
+            assert_ok!(BridgeModule::register_chain(
+                system::RawOrigin::Root.into(),
+                OTHER_CHAIN_ID,
+                eth_address,
+                vec![TOKEN_ID],
+                vec![V3, V4],
+                2
+            ));
+            // validator of the chain only keeps its bond too
+            assert_noop!(
+                BridgeModule::unbond(Origin::signed(V4)),
+                "Validators cannot unbond"
+            );
+            assert_noop!(
+                BridgeModule::set_transfer(
+                    Origin::signed(USER1),
+                    OTHER_CHAIN_ID,
+                    eth_address,
+                    NATIVE_TOKEN_ID,
+                    1000
+                ),
+                "Token is not supported by the chain"
+            );
+
+            // the chain has its own validators and limits
+            assert_noop!(
+                BridgeModule::update_limits(
+                    Origin::signed(V1),
+                    OTHER_CHAIN_ID,
+                    TOKEN_ID,
+                    100,
+                    200,
+                    50,
+                    400,
+                    1
+                ),
+                "Only validators can call this function"
+            );
+            for validator in &[V3, V4] {
+                assert_ok!(BridgeModule::update_limits(
+                    Origin::signed(*validator),
+                    OTHER_CHAIN_ID,
+                    TOKEN_ID,
+                    100,
+                    200,
+                    50,
+                    400,
+                    1
+                ));
+            }
+            assert_eq!(
+                BridgeModule::current_limits(OTHER_CHAIN_ID, TOKEN_ID).max_tx_value,
+                100
+            );
+
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V3),
+                OTHER_CHAIN_ID,
+                message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                amount
+            ));
+            assert_eq!(
+                BridgeModule::pending_mint_count(OTHER_CHAIN_ID, TOKEN_ID),
+                amount
+            );
+            assert_eq!(BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID), 0);
+
+            // the same id from another chain is another message
             assert_ok!(BridgeModule::multi_signed_mint(
-                Origin::signed(V2),
+                Origin::signed(V1),
+                CHAIN_ID,
                 message_id,
                 eth_address,
                 USER2,
                 TOKEN_ID,
                 amount
             ));
-            let mut message = BridgeModule::messages(message_id);
-            assert_eq!(message.status, Status::Pending);
+            assert_eq!(BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID), amount);
 
             assert_ok!(BridgeModule::multi_signed_mint(
-                Origin::signed(V1),
+                Origin::signed(V4),
+                OTHER_CHAIN_ID,
                 message_id,
                 eth_address,
                 USER2,
                 TOKEN_ID,
                 amount
             ));
-            message = BridgeModule::messages(message_id);
-            assert_eq!(message.status, Status::Confirmed);
+            let other_message_id = BridgeModule::chain_message_id(OTHER_CHAIN_ID, message_id);
+            assert_eq!(
+                BridgeModule::messages(other_message_id).status,
+                Status::Confirmed
+            );
+            assert_eq!(
+                BridgeModule::messages(other_message_id).chain,
+                OTHER_CHAIN_ID
+            );
+            assert_eq!(BridgeModule::messages(message_id).status, Status::Pending);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), amount);
+            assert_eq!(
+                BridgeModule::pending_mint_count(OTHER_CHAIN_ID, TOKEN_ID),
+                0
+            );
+        })
+    }
+    #[test]
+    fn chains_migration_works() {
+        use frame_support::{storage::migration::put_storage_value, traits::OnRuntimeUpgrade};
 
-            let transfer = BridgeModule::transfers(0);
-            assert_eq!(transfer.open, false);
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 49;
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
 
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), amount);
-            assert_eq!(TokenModule::total_supply(TOKEN_ID), amount);
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount
+            ));
+            let message_id = BridgeModule::message_id_by_transfer_id(0);
+            // messages had no chain id before
+            let key = <TransferMessages<Test>>::hashed_key_for(message_id);
+            let message = unhashed::get_raw(&key).unwrap();
+            unhashed::put_raw(&key, &message[..message.len() - 4]);
+            assert_eq!(BridgeModule::messages(message_id).amount, 0);
+
+            put_storage_value(b"Bridge", b"CurrentPendingBurn", &[], 42u128);
+            // daily limits are removed over several blocks
+            put_storage_value(
+                b"Bridge",
+                b"DailyHolds",
+                &USER2.encode(),
+                (0u64, message_id),
+            );
+            for i in 0..MIGRATED_PER_BLOCK {
+                put_storage_value(b"Bridge", b"DailyLimits", &i.encode(), 10u128);
+            }
+            <Chains<Test>>::remove(CHAIN_ID);
+            StorageVersion::put(0);
+
+            BridgeModule::on_runtime_upgrade();
+            assert!(!BridgeModule::bridge_is_operational());
+            BridgeModule::on_finalize(System::block_number());
+            assert!(BridgeModule::storage_migration().is_some());
+            assert!(!BridgeModule::bridge_is_operational());
+            BridgeModule::on_finalize(System::block_number());
+
+            assert_eq!(BridgeModule::storage_version(), 1);
+            assert_eq!(BridgeModule::storage_migration(), None);
+            assert!(BridgeModule::bridge_is_operational());
+            assert_eq!(BridgeModule::pending_burn_count(CHAIN_ID, TOKEN_ID), 0);
+            for item in &[&b"DailyHolds"[..], b"DailyLimits"] {
+                let prefix = BridgeModule::storage_prefix(item);
+                assert_eq!(
+                    sp_io::storage::next_key(&prefix).filter(|k| k.starts_with(&prefix)),
+                    None
+                );
+            }
+            assert_eq!(
+                BridgeModule::chains(CHAIN_ID).unwrap().tokens,
+                vec![TOKEN_ID, NATIVE_TOKEN_ID]
+            );
+            let message = BridgeModule::messages(message_id);
+            assert_eq!(message.amount, amount);
+            assert_eq!(message.chain, CHAIN_ID);
+            assert_eq!(message.status, Status::Withdraw);
         })
     }
     #[test]
-    fn token_eth2sub_closed_transfer_fail() {
+    fn shared_limits_migration_works() {
+        use frame_support::{storage::migration::put_storage_value, traits::OnRuntimeUpgrade};
+
         ExtBuilder::default().build().execute_with(|| {
-            let message_id = H256::from(ETH_MESSAGE_ID);
             let eth_address = H160::from(ETH_ADDRESS);
-            let amount = 99;
-
-            //substrate <----- ETH
+            let mint_id = H256::from(ETH_MESSAGE_ID);
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
-                message_id,
+                CHAIN_ID,
+                mint_id,
                 eth_address,
                 USER2,
                 TOKEN_ID,
-                amount
+                99
             ));
-            assert_ok!(BridgeModule::multi_signed_mint(
+            assert_ok!(BridgeModule::update_limits(
                 Origin::signed(V1),
-                message_id,
+                CHAIN_ID,
+                TOKEN_ID,
+                100,
+                200,
+                50,
+                400,
+                1
+            ));
+            let limits_id = BridgeModule::message_id_by_transfer_id(1);
+
+            // messages had neither token of the limits nor chain id before
+            let cut = |key: Vec<u8>, len: usize| {
+                let message = unhashed::get_raw(&key).unwrap();
+                unhashed::put_raw(&key, &message[..message.len() - len]);
+            };
+            cut(<TransferMessages<Test>>::hashed_key_for(mint_id), 4);
+            cut(<LimitMessages<Test>>::hashed_key_for(limits_id), 8);
+            <CurrentPendingMint<Test>>::remove(CHAIN_ID, TOKEN_ID);
+            put_storage_value(b"Bridge", b"CurrentPendingMint", &[], 1000u128);
+            let shared = Limits {
+                max_tx_value: 500,
+                day_max_limit: 1000,
+                day_max_limit_for_one_address: 300,
+                max_pending_tx_limit: 2000,
+                min_tx_value: 2,
+            };
+            put_storage_value(b"Bridge", b"CurrentLimits", &[], shared.clone());
+            StorageVersion::put(0);
+
+            BridgeModule::on_runtime_upgrade();
+            BridgeModule::on_finalize(System::block_number());
+
+            assert_eq!(BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID), 99);
+            assert_eq!(BridgeModule::current_limits(CHAIN_ID, TOKEN_ID), shared);
+            let message = BridgeModule::limit_messages(limits_id);
+            assert_eq!(message.limits.max_tx_value, 100);
+            assert_eq!(message.token, TOKEN_ID);
+            assert_eq!(message.chain, CHAIN_ID);
+            assert_eq!(BridgeModule::messages(mint_id).amount, 99);
+        })
+    }
+    #[test]
+    fn token_sub2eth_burn_skipped_approval_should_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount1 = 600;
+            let amount2 = 49;
+
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, amount1);
+
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), amount1);
+            assert_eq!(TokenModule::total_supply(TOKEN_ID), amount1);
+
+            //substrate ----> ETH
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
                 eth_address,
-                USER2,
                 TOKEN_ID,
-                amount
+                amount2
             ));
+            //RelayMessage(message_id) event emitted
+
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
+            let message = BridgeModule::messages(sub_message_id);
+            assert_eq!(message.status, Status::Withdraw);
+
+            assert_eq!(TokenModule::locked(0, USER2), 0);
+            // lets say validators blacked out and we
+            // try to confirm without approval anyway
             assert_noop!(
-                BridgeModule::multi_signed_mint(
-                    Origin::signed(V3),
-                    message_id,
-                    eth_address,
-                    USER2,
-                    TOKEN_ID,
-                    amount
-                ),
-                "This transfer is not open"
+                BridgeModule::confirm_transfer(Origin::signed(V1), sub_message_id),
+                "This transfer must be approved first."
             );
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), amount);
-            assert_eq!(TokenModule::total_supply(TOKEN_ID), amount);
-            let transfer = BridgeModule::transfers(0);
-            assert_eq!(transfer.open, false);
-
-            let message = BridgeModule::messages(message_id);
-            assert_eq!(message.status, Status::Confirmed);
         })
     }
+    #[test]
+    fn token_sub2eth_attested_burn_cancel_should_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount1 = 600;
+            let amount2 = 49;
+
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, amount1);
+
+            //substrate ----> ETH
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount2
+            ));
 
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_ok!(approve_transfer(V2, sub_message_id));
+            let mut message = BridgeModule::messages(sub_message_id);
+            // funds are locked and the contract can execute the withdrawal
+            assert_eq!(message.status, Status::Approved);
+            assert_noop!(
+                BridgeModule::cancel_transfer(Origin::signed(V2), sub_message_id),
+                "Transfer is attested and can't be canceled"
+            );
+            message = BridgeModule::messages(sub_message_id);
+            assert_eq!(message.status, Status::Approved);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), amount2);
+        })
+    }
     #[test]
-    fn token_sub2eth_burn_works() {
+    fn burn_cancel_should_fail() {
         ExtBuilder::default().build().execute_with(|| {
             let eth_address = H160::from(ETH_ADDRESS);
             let amount1 = 600;
@@ -1057,11 +3700,11 @@ mod tests {
             //substrate ----> ETH
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER2),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
-            //RelayMessage(message_id) event emitted
 
             let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
             let get_message = || BridgeModule::messages(sub_message_id);
@@ -1070,23 +3713,17 @@ mod tests {
             assert_eq!(message.status, Status::Withdraw);
 
             //approval
-            assert_eq!(TokenModule::locked((0, USER2)), 0);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V2),
-                sub_message_id
-            ));
+            assert_eq!(TokenModule::locked(0, USER2), 0);
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_ok!(approve_transfer(V2, sub_message_id));
 
             message = get_message();
             assert_eq!(message.status, Status::Approved);
 
             // at this point transfer is in Approved status and are waiting for confirmation
             // from ethereum side to burn. Funds are locked.
-            assert_eq!(TokenModule::locked((0, USER2)), amount2);
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), amount1);
+            assert_eq!(TokenModule::locked(0, USER2), amount2);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), amount1);
             // once it happends, validators call confirm_transfer
 
             assert_ok!(BridgeModule::confirm_transfer(
@@ -1105,177 +3742,234 @@ mod tests {
             // assert_ok!(BridgeModule::confirm_transfer(Origin::signed(USER1), sub_message_id));
             //BurnedMessage(Hash, AccountId, H160, u64) event emitted
             let tokens_left = amount1 - amount2;
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), tokens_left);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), tokens_left);
             assert_eq!(TokenModule::total_supply(TOKEN_ID), tokens_left);
+            assert_noop!(
+                BridgeModule::cancel_transfer(Origin::signed(V2), sub_message_id),
+                "Failed to cancel. This transfer is already executed."
+            );
         })
     }
     #[test]
-    fn token_sub2eth_burn_skipped_approval_should_fail() {
+    fn update_validator_list_should_work() {
         ExtBuilder::default().build().execute_with(|| {
-            let eth_address = H160::from(ETH_ADDRESS);
-            let amount1 = 600;
-            let amount2 = 49;
-
-            let _ = TokenModule::_mint(TOKEN_ID, USER2, amount1);
-
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), amount1);
-            assert_eq!(TokenModule::total_supply(TOKEN_ID), amount1);
+            let eth_message_id = H256::from(ETH_MESSAGE_ID);
+            const QUORUM: u64 = 3;
 
-            //substrate ----> ETH
-            assert_ok!(BridgeModule::set_transfer(
-                Origin::signed(USER2),
-                eth_address,
-                TOKEN_ID,
-                amount2
+            assert_ok!(BridgeModule::bond(Origin::signed(V4)));
+            assert_ok!(BridgeModule::update_validator_list(
+                Origin::signed(V2),
+                eth_message_id,
+                QUORUM,
+                vec![V1, V2, V3, V4]
             ));
-            //RelayMessage(message_id) event emitted
-
-            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
-            let message = BridgeModule::messages(sub_message_id);
-            assert_eq!(message.status, Status::Withdraw);
+            let id = BridgeModule::message_id_by_transfer_id(0);
+            let mut message = BridgeModule::validator_history(id);
+            assert_eq!(message.status, Status::Pending);
 
-            assert_eq!(TokenModule::locked((0, USER2)), 0);
-            // lets say validators blacked out and we
-            // try to confirm without approval anyway
-            assert_noop!(
-                BridgeModule::confirm_transfer(Origin::signed(V1), sub_message_id),
-                "This transfer must be approved first."
-            );
+            assert_ok!(BridgeModule::update_validator_list(
+                Origin::signed(V1),
+                eth_message_id,
+                QUORUM,
+                vec![V1, V2, V3, V4]
+            ));
+            message = BridgeModule::validator_history(id);
+            assert_eq!(message.status, Status::Confirmed);
+            assert_eq!(BridgeModule::validators_count(), 4);
         })
     }
     #[test]
-    fn token_sub2eth_burn_cancel_works() {
+    fn update_validator_list_should_replace_validators() {
         ExtBuilder::default().build().execute_with(|| {
+            let eth_message_id = H256::from(ETH_MESSAGE_ID);
+            let mint_message_id = H256::from(ETH_MESSAGE_ID1);
             let eth_address = H160::from(ETH_ADDRESS);
-            let amount1 = 600;
-            let amount2 = 49;
-
-            let _ = TokenModule::_mint(TOKEN_ID, USER2, amount1);
+            let amount = 99;
 
-            //substrate ----> ETH
-            assert_ok!(BridgeModule::set_transfer(
-                Origin::signed(USER2),
+            // V1 votes for the mint before being removed from the set
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V1),
+                CHAIN_ID,
+                mint_message_id,
                 eth_address,
+                USER2,
                 TOKEN_ID,
-                amount2
+                amount
             ));
 
-            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V2),
-                sub_message_id
-            ));
-            let mut message = BridgeModule::messages(sub_message_id);
-            // funds are locked and waiting for confirmation
-            assert_eq!(message.status, Status::Approved);
-            assert_ok!(BridgeModule::cancel_transfer(
-                Origin::signed(V2),
-                sub_message_id
+            assert_ok!(BridgeModule::bond(Origin::signed(V4)));
+            for validator in &[V1, V2] {
+                assert_ok!(BridgeModule::update_validator_list(
+                    Origin::signed(*validator),
+                    eth_message_id,
+                    2,
+                    vec![V2, V3, V4]
+                ));
+            }
+            assert_eq!(BridgeModule::validator_accounts(), vec![V2, V3, V4]);
+            assert_eq!(BridgeModule::validators_count(), 3);
+            assert!(!BridgeModule::validators(V1));
+            assert!(BridgeModule::validators(V4));
+            assert_noop!(
+                approve_transfer(V1, mint_message_id),
+                "Only validators can call this function"
+            );
+
+            // vote of V1 is not counted anymore
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V4),
+                CHAIN_ID,
+                mint_message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                amount
             ));
-            assert_ok!(BridgeModule::cancel_transfer(
+            assert_eq!(
+                BridgeModule::messages(mint_message_id).status,
+                Status::Pending
+            );
+            assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V3),
-                sub_message_id
+                CHAIN_ID,
+                mint_message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                amount
             ));
-            message = BridgeModule::messages(sub_message_id);
-            assert_eq!(message.status, Status::Canceled);
+            assert_eq!(
+                BridgeModule::messages(mint_message_id).status,
+                Status::Confirmed
+            );
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), amount);
+        })
+    }
+    #[test]
+    fn update_validator_list_should_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_message_id = H256::from(ETH_MESSAGE_ID);
+
+            assert_noop!(
+                BridgeModule::update_validator_list(
+                    Origin::signed(V1),
+                    eth_message_id,
+                    4,
+                    vec![V1, V2, V3]
+                ),
+                "Quorum should be between 1 and number of validators."
+            );
+            assert_noop!(
+                BridgeModule::update_validator_list(
+                    Origin::signed(V1),
+                    eth_message_id,
+                    2,
+                    vec![V1, V2, V2]
+                ),
+                "New validator list contains duplicates."
+            );
+            assert_noop!(
+                BridgeModule::update_validator_list(
+                    Origin::signed(V1),
+                    eth_message_id,
+                    2,
+                    vec![V1, V2, V4]
+                ),
+                "New validator list contains unbonded accounts."
+            );
         })
     }
     #[test]
-    fn burn_cancel_should_fail() {
+    fn bond_and_unbond_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            // genesis validators are bonded
+            assert_eq!(BridgeModule::bond_of(V1), VALIDATOR_BOND);
+            assert_eq!(Balances::reserved_balance(V1), VALIDATOR_BOND);
+
+            assert_ok!(BridgeModule::bond(Origin::signed(USER1)));
+            assert_eq!(BridgeModule::bond_of(USER1), VALIDATOR_BOND);
+            assert_eq!(Balances::free_balance(USER1), 100000 - VALIDATOR_BOND);
+            assert_noop!(BridgeModule::bond(Origin::signed(USER1)), "Already bonded");
+
+            assert_ok!(BridgeModule::unbond(Origin::signed(USER1)));
+            assert_eq!(BridgeModule::bond_of(USER1), 0);
+            assert_eq!(Balances::free_balance(USER1), 100000);
+            assert_noop!(BridgeModule::unbond(Origin::signed(USER1)), "Not bonded");
+            assert_noop!(
+                BridgeModule::unbond(Origin::signed(V1)),
+                "Validators cannot unbond"
+            );
+        })
+    }
+    #[test]
+    fn report_misbehaviour_should_work() {
         ExtBuilder::default().build().execute_with(|| {
+            let message_id = H256::from(ETH_MESSAGE_ID);
             let eth_address = H160::from(ETH_ADDRESS);
-            let amount1 = 600;
-            let amount2 = 49;
-
-            let _ = TokenModule::_mint(TOKEN_ID, USER2, amount1);
+            let amount = 99;
 
-            //substrate ----> ETH
-            assert_ok!(BridgeModule::set_transfer(
-                Origin::signed(USER2),
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V1),
+                CHAIN_ID,
+                message_id,
                 eth_address,
+                USER2,
                 TOKEN_ID,
-                amount2
-            ));
-
-            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
-            let get_message = || BridgeModule::messages(sub_message_id);
-
-            let mut message = get_message();
-            assert_eq!(message.status, Status::Withdraw);
-
-            //approval
-            assert_eq!(TokenModule::locked((0, USER2)), 0);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V2),
-                sub_message_id
+                amount
             ));
+            assert_noop!(
+                BridgeModule::report_misbehaviour(Origin::signed(USER1), V1, message_id),
+                "No conflicting votes found"
+            );
 
-            message = get_message();
-            assert_eq!(message.status, Status::Approved);
-
-            // at this point transfer is in Approved status and are waiting for confirmation
-            // from ethereum side to burn. Funds are locked.
-            assert_eq!(TokenModule::locked((0, USER2)), amount2);
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), amount1);
-            // once it happends, validators call confirm_transfer
-
-            assert_ok!(BridgeModule::confirm_transfer(
+            // other validators reject the mint
+            assert_ok!(BridgeModule::cancel_transfer(
                 Origin::signed(V2),
-                sub_message_id
+                message_id
             ));
-
-            message = get_message();
-            let transfer = BridgeModule::transfers(1);
-            assert_eq!(message.status, Status::Confirmed);
-            assert_eq!(transfer.open, true);
-            assert_ok!(BridgeModule::confirm_transfer(
-                Origin::signed(V1),
-                sub_message_id
+            assert_eq!(BridgeModule::messages(message_id).status, Status::Canceled);
+            assert_eq!(BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID), amount);
+            assert_ok!(BridgeModule::cancel_transfer(
+                Origin::signed(V3),
+                message_id
             ));
-            // assert_ok!(BridgeModule::confirm_transfer(Origin::signed(USER1), sub_message_id));
-            //BurnedMessage(Hash, AccountId, H160, u64) event emitted
-            let tokens_left = amount1 - amount2;
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), tokens_left);
-            assert_eq!(TokenModule::total_supply(TOKEN_ID), tokens_left);
+            assert!(BridgeModule::rejected_mint(message_id));
+            assert_eq!(BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID), 0);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 0);
             assert_noop!(
-                BridgeModule::cancel_transfer(Origin::signed(V2), sub_message_id),
-                "Failed to cancel. This transfer is already executed."
+                BridgeModule::multi_signed_mint(
+                    Origin::signed(V1),
+                    CHAIN_ID,
+                    message_id,
+                    eth_address,
+                    USER2,
+                    TOKEN_ID,
+                    amount
+                ),
+                "This transfer is canceled"
             );
-        })
-    }
-    #[test]
-    fn update_validator_list_should_work() {
-        ExtBuilder::default().build().execute_with(|| {
-            let eth_message_id = H256::from(ETH_MESSAGE_ID);
-            const QUORUM: u64 = 3;
 
-            assert_ok!(BridgeModule::update_validator_list(
-                Origin::signed(V2),
-                eth_message_id,
-                QUORUM,
-                vec![V1, V2, V3, V4]
+            assert_noop!(
+                BridgeModule::report_misbehaviour(Origin::signed(USER1), V2, message_id),
+                "No conflicting votes found"
+            );
+            assert_ok!(BridgeModule::report_misbehaviour(
+                Origin::signed(USER1),
+                V1,
+                message_id
             ));
-            let id = BridgeModule::message_id_by_transfer_id(0);
-            let mut message = BridgeModule::validator_history(id);
-            assert_eq!(message.status, Status::Pending);
+            assert_eq!(BridgeModule::bond_of(V1), 0);
+            assert_eq!(Balances::reserved_balance(V1), 0);
+            assert_eq!(Balances::free_balance(V1), 100000 - VALIDATOR_BOND);
+            assert!(!BridgeModule::validators(V1));
+            assert_eq!(BridgeModule::validator_accounts(), vec![V2, V3]);
+            assert_eq!(BridgeModule::validators_count(), 2);
+            assert_eq!(BridgeModule::quorum(), 2);
 
-            assert_ok!(BridgeModule::update_validator_list(
-                Origin::signed(V1),
-                eth_message_id,
-                QUORUM,
-                vec![V1, V2, V3, V4]
-            ));
-            message = BridgeModule::validator_history(id);
-            assert_eq!(message.status, Status::Confirmed);
-            assert_eq!(BridgeModule::validators_count(), 4);
+            assert_noop!(
+                BridgeModule::report_misbehaviour(Origin::signed(USER1), V1, message_id),
+                "Offence is already reported"
+            );
         })
     }
     #[test]
@@ -1296,6 +3990,24 @@ mod tests {
         })
     }
     #[test]
+    fn message_events_should_be_indexed() {
+        ExtBuilder::default().build().execute_with(|| {
+            // events are not recorded at genesis
+            System::set_block_number(1);
+            let id = (PAUSE_MESSAGE, BridgeModule::kind_nonce(Kind::Bridge))
+                .using_encoded(<Test as system::Trait>::Hashing::hash);
+
+            // created, pending and the vote
+            assert_ok!(BridgeModule::pause_bridge(Origin::signed(V2)));
+            assert_eq!(System::event_topics(&id).len(), 3);
+
+            // paused, confirmed and the vote
+            assert_ok!(BridgeModule::pause_bridge(Origin::signed(V1)));
+            assert_eq!(System::event_topics(&id).len(), 6);
+            assert_eq!(BridgeModule::bridge_is_operational(), false);
+        })
+    }
+    #[test]
     fn extrinsics_restricted_should_fail() {
         ExtBuilder::default().build().execute_with(|| {
             let eth_message_id = H256::from(ETH_MESSAGE_ID);
@@ -1308,6 +4020,7 @@ mod tests {
             assert_noop!(
                 BridgeModule::multi_signed_mint(
                     Origin::signed(V2),
+                    CHAIN_ID,
                     eth_message_id,
                     eth_address,
                     USER2,
@@ -1344,6 +4057,229 @@ mod tests {
         })
     }
     #[test]
+    fn identical_transfers_should_not_collide() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 10;
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+
+            for _ in 0..2 {
+                assert_ok!(BridgeModule::set_transfer(
+                    Origin::signed(USER2),
+                    CHAIN_ID,
+                    eth_address,
+                    TOKEN_ID,
+                    amount
+                ));
+            }
+            assert_eq!(BridgeModule::transfer_nonce(USER2), 2);
+            for nonce in 0..2 {
+                let message_id = (
+                    TRANSFER_MESSAGE,
+                    CHAIN_ID,
+                    USER2,
+                    eth_address,
+                    TOKEN_ID,
+                    amount,
+                    nonce,
+                )
+                    .using_encoded(BlakeTwo256::hash);
+                assert_eq!(BridgeModule::message_id_by_transfer_id(nonce), message_id);
+                assert_eq!(BridgeModule::messages(message_id).amount, amount);
+            }
+        })
+    }
+    #[test]
+    fn repeated_bridge_messages_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            for _ in 0..2 {
+                assert_ok!(BridgeModule::pause_bridge(Origin::signed(V1)));
+                assert_ok!(BridgeModule::pause_bridge(Origin::signed(V2)));
+                assert_eq!(BridgeModule::bridge_is_operational(), false);
+                assert_ok!(BridgeModule::resume_bridge(Origin::signed(V1)));
+                assert_ok!(BridgeModule::resume_bridge(Origin::signed(V2)));
+                assert_eq!(BridgeModule::bridge_is_operational(), true);
+            }
+            assert_eq!(BridgeModule::kind_nonce(Kind::Bridge), 4);
+
+            for max_tx_value in &[90, 80, 90] {
+                for validator in &[V1, V2] {
+                    assert_ok!(BridgeModule::update_limits(
+                        Origin::signed(*validator),
+                        CHAIN_ID,
+                        TOKEN_ID,
+                        *max_tx_value,
+                        200,
+                        50,
+                        400,
+                        1
+                    ));
+                }
+                assert_eq!(
+                    BridgeModule::current_limits(CHAIN_ID, TOKEN_ID).max_tx_value,
+                    *max_tx_value
+                );
+            }
+            assert_eq!(BridgeModule::limits_nonce(CHAIN_ID, TOKEN_ID), 3);
+            assert_eq!(BridgeModule::limits_nonce(CHAIN_ID, NATIVE_TOKEN_ID), 0);
+        })
+    }
+    #[test]
+    fn governance_override_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            let root = || -> Origin { system::RawOrigin::Root.into() };
+
+            assert_noop!(
+                BridgeModule::force_pause(Origin::signed(V1)),
+                DispatchError::BadOrigin
+            );
+            assert_ok!(BridgeModule::pause_bridge(Origin::signed(V1)));
+            assert_ok!(BridgeModule::force_pause(root()));
+            assert_eq!(BridgeModule::bridge_is_operational(), false);
+            assert_noop!(
+                BridgeModule::force_pause(root()),
+                "Bridge is not operational already"
+            );
+            assert_noop!(
+                BridgeModule::force_resume(Origin::signed(V1)),
+                DispatchError::BadOrigin
+            );
+            assert_ok!(BridgeModule::force_resume(root()));
+            assert_eq!(BridgeModule::bridge_is_operational(), true);
+            assert_noop!(
+                BridgeModule::force_resume(root()),
+                "Bridge is operational already"
+            );
+
+            assert_noop!(
+                BridgeModule::force_validator_set(root(), 2, vec![V2]),
+                "Quorum should be between 1 and number of validators."
+            );
+            assert_ok!(BridgeModule::force_validator_set(root(), 1, vec![V2]));
+            assert_eq!(BridgeModule::validator_accounts(), vec![V2]);
+            assert!(!BridgeModule::validators(V1));
+            assert_eq!(BridgeModule::quorum(), 1);
+            assert_ok!(BridgeModule::pause_bridge(Origin::signed(V2)));
+            assert_eq!(BridgeModule::bridge_is_operational(), false);
+
+            assert_noop!(
+                BridgeModule::force_limits(root(), OTHER_CHAIN_ID, TOKEN_ID, 90, 200, 50, 400, 1),
+                "Chain is not registered"
+            );
+            assert_ok!(BridgeModule::force_limits(
+                root(),
+                CHAIN_ID,
+                TOKEN_ID,
+                90,
+                200,
+                50,
+                400,
+                1
+            ));
+            assert_eq!(
+                BridgeModule::current_limits(CHAIN_ID, TOKEN_ID).max_tx_value,
+                90
+            );
+            assert_eq!(BridgeModule::limits_nonce(CHAIN_ID, TOKEN_ID), 1);
+        })
+    }
+    #[test]
+    fn batch_sign_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            let message_id1 = H256::from(ETH_MESSAGE_ID1);
+            let message_id2 = H256::from(ETH_MESSAGE_ID2);
+            let eth_address = H160::from(ETH_ADDRESS);
+            let mint = |message_id, amount| {
+                BridgeVote::Mint(CHAIN_ID, message_id, eth_address, USER2, TOKEN_ID, amount)
+            };
+
+            // the second mint is above the limit
+            assert_ok!(BridgeModule::batch_sign(
+                Origin::signed(V1),
+                vec![mint(message_id1, 99), mint(message_id2, 1000)]
+            ));
+            assert_eq!(BridgeModule::messages(message_id1).status, Status::Pending);
+            assert!(!<TransferMessages<Test>>::contains_key(message_id2));
+
+            assert_ok!(BridgeModule::batch_sign(
+                Origin::signed(V2),
+                vec![mint(message_id1, 99), mint(message_id2, 10)]
+            ));
+            assert_eq!(
+                BridgeModule::messages(message_id1).status,
+                Status::Confirmed
+            );
+            assert_eq!(BridgeModule::messages(message_id2).status, Status::Pending);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 99);
+
+            assert_noop!(
+                BridgeModule::batch_sign(
+                    Origin::signed(V3),
+                    vec![
+                        BridgeVote::Approve(message_id2, attestation(V3, message_id2));
+                        MAX_BATCH_SIZE + 1
+                    ]
+                ),
+                "Batch is too large"
+            );
+        })
+    }
+    #[test]
+    fn attestations_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                40
+            ));
+            let message_id = BridgeModule::message_id_by_transfer_id(0);
+
+            let payload = BridgeModule::attestation_payload(&BridgeModule::messages(message_id));
+            assert_eq!(payload.len(), 128);
+            assert_eq!(&payload[..32], message_id.as_bytes());
+            assert_eq!(&payload[44..64], eth_address.as_bytes());
+            assert_eq!(&payload[64..72], &USER2.to_le_bytes());
+            assert_eq!(payload[127], 40);
+
+            // signed with another validator's key
+            assert_noop!(
+                BridgeModule::approve_transfer(
+                    Origin::signed(V1),
+                    message_id,
+                    attestation(V2, message_id)
+                ),
+                "Invalid attestation signature"
+            );
+            <EthSigners<Test>>::remove(V1);
+            assert_noop!(
+                approve_transfer(V1, message_id),
+                "Validator has no Ethereum signer"
+            );
+            assert_ok!(BridgeModule::set_eth_signer(
+                Origin::signed(V1),
+                test_signer::address(V1)
+            ));
+
+            assert_ok!(approve_transfer(V1, message_id));
+            assert_ok!(BridgeModule::batch_sign(
+                Origin::signed(V2),
+                vec![BridgeVote::Approve(message_id, attestation(V2, message_id))]
+            ));
+            assert_eq!(BridgeModule::messages(message_id).status, Status::Approved);
+
+            let attestations = BridgeModule::attestations(message_id);
+            assert_eq!(attestations.len(), 2);
+            assert_eq!(attestations[0].validator, V1);
+            assert_eq!(attestations[0].signer, test_signer::address(V1));
+            assert_eq!(attestations[1].validator, V2);
+            assert_eq!(attestations[1].signature, attestation(V2, message_id));
+        })
+    }
+    #[test]
     fn double_vote_should_fail() {
         ExtBuilder::default().build().execute_with(|| {
             assert_eq!(BridgeModule::bridge_is_operational(), true);
@@ -1359,12 +4295,13 @@ mod tests {
         ExtBuilder::default().build().execute_with(|| {
             let eth_message_id = H256::from(ETH_MESSAGE_ID);
             let eth_address = H160::from(ETH_ADDRESS);
-            let amount1 = 99;
+            let amount1 = 60;
             let amount2 = 49;
 
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id,
                 eth_address,
                 USER2,
@@ -1373,6 +4310,7 @@ mod tests {
             ));
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V1),
+                CHAIN_ID,
                 eth_message_id,
                 eth_address,
                 USER2,
@@ -1380,35 +4318,17 @@ mod tests {
                 amount1
             ));
             //substrate ----> ETH
-            assert_ok!(BridgeModule::set_transfer(
-                Origin::signed(USER2),
-                eth_address,
-                TOKEN_ID,
-                amount2
-            ));
-            //RelayMessage(message_id) event emitted
-            let sub_message_id = BridgeModule::message_id_by_transfer_id(1);
-            let get_message = || BridgeModule::messages(sub_message_id);
-            let mut message = get_message();
-            assert_eq!(message.status, Status::Withdraw);
-            //approval
-            assert_eq!(TokenModule::locked((0, USER2)), 0);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
-            // assert_noop BUG: fails through different root hashes
-            // solution: use assert_eq!(expr, Err(DispatchError::Other("Error string")) explicitly
-
-            assert_eq!(
-                BridgeModule::approve_transfer(Origin::signed(V2), sub_message_id),
-                Err(DispatchError::Other(
-                    "Cannot withdraw more that 75% of first day deposit."
-                ))
+            // rejected before anything is charged or counted
+            assert_noop!(
+                BridgeModule::set_transfer(
+                    Origin::signed(USER2),
+                    CHAIN_ID,
+                    eth_address,
+                    TOKEN_ID,
+                    amount2
+                ),
+                "Cannot withdraw more that 75% of first day deposit."
             );
-
-            message = get_message();
-            assert_eq!(message.status, Status::Canceled);
         })
     }
     #[test]
@@ -1420,9 +4340,14 @@ mod tests {
             let max_pending_tx_limit = 40;
             let min_tx_value = 1;
 
-            assert_eq!(BridgeModule::current_limits().max_tx_value, 100);
+            assert_eq!(
+                BridgeModule::current_limits(CHAIN_ID, TOKEN_ID).max_tx_value,
+                100
+            );
             assert_ok!(BridgeModule::update_limits(
                 Origin::signed(V2),
+                CHAIN_ID,
+                TOKEN_ID,
                 max_tx_value,
                 day_max_limit,
                 day_max_limit_for_one_address,
@@ -1431,6 +4356,8 @@ mod tests {
             ));
             assert_ok!(BridgeModule::update_limits(
                 Origin::signed(V1),
+                CHAIN_ID,
+                TOKEN_ID,
                 max_tx_value,
                 day_max_limit,
                 day_max_limit_for_one_address,
@@ -1438,7 +4365,42 @@ mod tests {
                 min_tx_value,
             ));
 
-            assert_eq!(BridgeModule::current_limits().max_tx_value, 10);
+            assert_eq!(
+                BridgeModule::current_limits(CHAIN_ID, TOKEN_ID).max_tx_value,
+                10
+            );
+        })
+    }
+    #[test]
+    fn change_limits_of_one_token_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            const OTHER_TOKEN_ID: TokenId = 1;
+            assert_eq!(
+                BridgeModule::current_limits(CHAIN_ID, OTHER_TOKEN_ID).max_tx_value,
+                0
+            );
+
+            for validator in &[V1, V2] {
+                assert_ok!(BridgeModule::update_limits(
+                    Origin::signed(*validator),
+                    CHAIN_ID,
+                    OTHER_TOKEN_ID,
+                    10,
+                    20,
+                    5,
+                    40,
+                    1,
+                ));
+            }
+
+            assert_eq!(
+                BridgeModule::current_limits(CHAIN_ID, OTHER_TOKEN_ID).max_tx_value,
+                10
+            );
+            assert_eq!(
+                BridgeModule::current_limits(CHAIN_ID, TOKEN_ID).max_tx_value,
+                100
+            );
         })
     }
     #[test]
@@ -1453,6 +4415,8 @@ mod tests {
             assert_noop!(
                 BridgeModule::update_limits(
                     Origin::signed(V1),
+                    CHAIN_ID,
+                    TOKEN_ID,
                     MORE_THAN_MAX,
                     day_max_limit,
                     day_max_limit_for_one_address,
@@ -1482,96 +4446,89 @@ mod tests {
             //1
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER2),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER3),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(1);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER4),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(2);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER5),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(3);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER6),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(4);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER7),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(5);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER8),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(6);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER9),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(7);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
 
-            assert_eq!(BridgeModule::pending_burn_count(), amount2 * 8);
+            assert_eq!(
+                BridgeModule::pending_burn_count(CHAIN_ID, TOKEN_ID),
+                amount2 * 8
+            );
             assert_noop!(
-                BridgeModule::set_transfer(Origin::signed(USER1), eth_address, TOKEN_ID, amount2),
+                BridgeModule::set_transfer(
+                    Origin::signed(USER1),
+                    CHAIN_ID,
+                    eth_address,
+                    TOKEN_ID,
+                    amount2
+                ),
                 "Too many pending burn transactions."
             );
         })
@@ -1594,6 +4551,7 @@ mod tests {
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id,
                 eth_address,
                 USER2,
@@ -1604,6 +4562,7 @@ mod tests {
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id2,
                 eth_address,
                 USER3,
@@ -1614,6 +4573,7 @@ mod tests {
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id3,
                 eth_address,
                 USER4,
@@ -1624,6 +4584,7 @@ mod tests {
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id4,
                 eth_address,
                 USER5,
@@ -1633,6 +4594,7 @@ mod tests {
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id5,
                 eth_address,
                 USER6,
@@ -1642,6 +4604,7 @@ mod tests {
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id6,
                 eth_address,
                 USER7,
@@ -1651,6 +4614,7 @@ mod tests {
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id7,
                 eth_address,
                 USER8,
@@ -1660,18 +4624,23 @@ mod tests {
             //substrate <----- ETH
             assert_ok!(BridgeModule::multi_signed_mint(
                 Origin::signed(V2),
+                CHAIN_ID,
                 eth_message_id8,
                 eth_address,
                 USER9,
                 TOKEN_ID,
                 amount1
             ));
-            assert_eq!(BridgeModule::pending_mint_count(), amount1 * 8);
+            assert_eq!(
+                BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID),
+                amount1 * 8
+            );
 
             //substrate <----- ETH
             assert_noop!(
                 BridgeModule::multi_signed_mint(
                     Origin::signed(V2),
+                    CHAIN_ID,
                     eth_message_id1,
                     eth_address,
                     USER1,
@@ -1691,22 +4660,23 @@ mod tests {
             let _ = TokenModule::_mint(TOKEN_ID, USER2, amount1);
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER2),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V2),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_ok!(approve_transfer(V2, sub_message_id));
 
             assert_eq!(
-                BridgeModule::set_transfer(Origin::signed(USER2), eth_address, TOKEN_ID, amount2),
+                BridgeModule::set_transfer(
+                    Origin::signed(USER2),
+                    CHAIN_ID,
+                    eth_address,
+                    TOKEN_ID,
+                    amount2
+                ),
                 Err(DispatchError::Other(
                     "Transfer declined, user blocked due to daily volume limit."
                 ))
@@ -1714,50 +4684,519 @@ mod tests {
         })
     }
     #[test]
+    fn pending_transfer_should_expire() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 49;
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount
+            ));
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_eq!(BridgeModule::pending_burn_count(CHAIN_ID, TOKEN_ID), amount);
+            assert_eq!(
+                BridgeModule::rolling_volume(CHAIN_ID, TOKEN_ID, &USER2),
+                amount
+            );
+
+            run_to_block(TRANSFER_TTL + 1);
+
+            let message = BridgeModule::messages(sub_message_id);
+            assert_eq!(message.status, Status::Canceled);
+            assert_eq!(BridgeModule::transfers(0).open, false);
+            assert_eq!(BridgeModule::pending_burn_count(CHAIN_ID, TOKEN_ID), 0);
+            assert_eq!(BridgeModule::rolling_volume(CHAIN_ID, TOKEN_ID, &USER2), 0);
+            assert_noop!(
+                approve_transfer(V2, sub_message_id),
+                "This transfer is not open"
+            );
+        })
+    }
+    #[test]
+    fn expired_transfers_over_block_limit_should_wait() {
+        ExtBuilder::default().build().execute_with(|| {
+            let ids = (0..MAX_EXPIRED_PER_BLOCK as u64 + 5)
+                .map(H256::from_low_u64_be)
+                .collect::<Vec<_>>();
+            <ExpiringTransfers<Test>>::insert(5, ids.clone());
+
+            BridgeModule::on_finalize(5);
+            assert_eq!(
+                BridgeModule::expiring_transfers(6),
+                ids[MAX_EXPIRED_PER_BLOCK..].to_vec()
+            );
+        })
+    }
+    #[test]
+    fn attested_transfer_should_not_expire() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 49;
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount
+            ));
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_ok!(approve_transfer(V2, sub_message_id));
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), amount);
+
+            run_to_block(TRANSFER_TTL + 1);
+
+            // the contract accepts the attestations, so the funds stay locked
+            let message = BridgeModule::messages(sub_message_id);
+            assert_eq!(message.status, Status::Approved);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), amount);
+            assert_noop!(
+                BridgeModule::reclaim(Origin::signed(USER2), sub_message_id),
+                "Transfer is attested and can't be canceled"
+            );
+        })
+    }
+    #[test]
+    fn reclaim_should_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 49;
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount
+            ));
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_noop!(
+                BridgeModule::reclaim(Origin::signed(USER2), sub_message_id),
+                "Transfer has not expired yet"
+            );
+
+            // deadline has passed, but block hooks didn't process the transfer
+            System::set_block_number(TRANSFER_TTL + 1);
+            assert_noop!(
+                BridgeModule::reclaim(Origin::signed(USER3), sub_message_id),
+                "Only sender can reclaim the transfer"
+            );
+            assert_ok!(BridgeModule::reclaim(Origin::signed(USER2), sub_message_id));
+
+            let message = BridgeModule::messages(sub_message_id);
+            assert_eq!(message.status, Status::Canceled);
+            assert_eq!(BridgeModule::pending_burn_count(CHAIN_ID, TOKEN_ID), 0);
+            assert_noop!(
+                BridgeModule::reclaim(Origin::signed(USER2), sub_message_id),
+                "Transfer has no deadline"
+            );
+        })
+    }
+    #[test]
+    fn withdrawal_fee_should_be_distributed() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 40;
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+            assert_ok!(BridgeModule::set_fee(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID,
+                1,
+                1000
+            ));
+
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                amount
+            ));
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
+            // 1 + 10% of 40
+            assert_eq!(BridgeModule::transfer_fee(sub_message_id), 5);
+            assert_eq!(BridgeModule::fee_pot(TOKEN_ID), 5);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 595);
+
+            for validator in &[V1, V2] {
+                assert_ok!(approve_transfer(*validator, sub_message_id));
+            }
+            for validator in &[V2, V1] {
+                assert_ok!(BridgeModule::confirm_transfer(
+                    Origin::signed(*validator),
+                    sub_message_id
+                ));
+            }
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 555);
+            assert_eq!(BridgeModule::validator_rewards(TOKEN_ID, V1), 3);
+            assert_eq!(BridgeModule::validator_rewards(TOKEN_ID, V2), 2);
+            assert_eq!(BridgeModule::validator_rewards(TOKEN_ID, V3), 0);
+
+            assert_ok!(BridgeModule::claim_fees(Origin::signed(V1), TOKEN_ID));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, V1), 3);
+            assert_eq!(BridgeModule::fee_pot(TOKEN_ID), 2);
+            assert_noop!(
+                BridgeModule::claim_fees(Origin::signed(V1), TOKEN_ID),
+                "Nothing to claim"
+            );
+        })
+    }
+    #[test]
+    fn deposit_fee_should_be_distributed() {
+        ExtBuilder::default().build().execute_with(|| {
+            let message_id = H256::from(ETH_MESSAGE_ID);
+            let eth_address = H160::from(ETH_ADDRESS);
+            let amount = 90;
+            assert_ok!(BridgeModule::set_fee(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID,
+                1,
+                1000
+            ));
+
+            for validator in &[V2, V1] {
+                assert_ok!(BridgeModule::multi_signed_mint(
+                    Origin::signed(*validator),
+                    CHAIN_ID,
+                    message_id,
+                    eth_address,
+                    USER2,
+                    TOKEN_ID,
+                    amount
+                ));
+            }
+
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 80);
+            assert_eq!(BridgeModule::fee_pot(TOKEN_ID), 10);
+            assert_eq!(BridgeModule::validator_rewards(TOKEN_ID, V1), 5);
+            assert_eq!(BridgeModule::validator_rewards(TOKEN_ID, V2), 5);
+        })
+    }
+    #[test]
+    fn fee_of_expired_transfer_should_be_refunded() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+            assert_ok!(BridgeModule::set_fee(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID,
+                1,
+                1000
+            ));
+
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                40
+            ));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 595);
+
+            run_to_block(TRANSFER_TTL + 1);
+
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 600);
+            assert_eq!(BridgeModule::fee_pot(TOKEN_ID), 0);
+        })
+    }
+    #[test]
+    fn set_fee_should_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_noop!(
+                BridgeModule::set_fee(Origin::signed(V1), TOKEN_ID, 1, 1000),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                BridgeModule::set_fee(system::RawOrigin::Root.into(), TOKEN_ID, 1, 10_001),
+                "Fee basis points should not exceed 10000"
+            );
+        })
+    }
+    #[test]
     fn blocked_account_unblocked_next_day_should_work() {
         ExtBuilder::default().build().execute_with(|| {
             let eth_address = H160::from(ETH_ADDRESS);
             let amount1 = 600;
             let amount2 = 49;
-            run_to_block(DAY_IN_BLOCKS.into());
+            run_to_block(DAY_IN_BLOCKS);
 
             let _ = TokenModule::_mint(TOKEN_ID, USER2, amount1);
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER2),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
             let sub_message_id = BridgeModule::message_id_by_transfer_id(0);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V2),
-                sub_message_id
-            ));
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_ok!(approve_transfer(V2, sub_message_id));
             assert_eq!(
-                BridgeModule::set_transfer(Origin::signed(USER2), eth_address, TOKEN_ID, amount2),
+                BridgeModule::set_transfer(
+                    Origin::signed(USER2),
+                    CHAIN_ID,
+                    eth_address,
+                    TOKEN_ID,
+                    amount2
+                ),
                 Err(DispatchError::Other(
                     "Transfer declined, user blocked due to daily volume limit."
                 ))
             );
 
-            //user added to blocked vec
-            let blocked_vec: Vec<u64> = vec![USER2];
-            assert_eq!(BridgeModule::daily_blocked((TOKEN_ID, 1)), blocked_vec);
+            // blocked for a window
+            assert_eq!(
+                BridgeModule::blocked_until((CHAIN_ID, TOKEN_ID), USER2),
+                Some(DAY_IN_BLOCKS * 2)
+            );
 
-            run_to_block((DAY_IN_BLOCKS * 2).into());
-            run_to_block((DAY_IN_BLOCKS * 3).into());
+            run_to_block(DAY_IN_BLOCKS * 2);
+            assert!(BridgeModule::blocked_until((CHAIN_ID, TOKEN_ID), USER2).is_some());
+            run_to_block(DAY_IN_BLOCKS * 3);
+            assert_eq!(
+                BridgeModule::blocked_until((CHAIN_ID, TOKEN_ID), USER2),
+                None
+            );
 
             //try again
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER2),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 amount2
             ));
         })
     }
+
+    #[test]
+    fn account_volume_should_slide_with_the_window() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_address = H160::from(ETH_ADDRESS);
+            let _ = TokenModule::_mint(TOKEN_ID, USER2, 600);
+            let transfer = |amount| {
+                BridgeModule::set_transfer(
+                    Origin::signed(USER2),
+                    CHAIN_ID,
+                    eth_address,
+                    TOKEN_ID,
+                    amount,
+                )
+            };
+            System::set_block_number(DAY_IN_BLOCKS);
+            assert_ok!(transfer(40));
+
+            // half of the previous window is still within the rolling one
+            System::set_block_number(DAY_IN_BLOCKS * 2 + DAY_IN_BLOCKS / 2);
+            assert_eq!(BridgeModule::rolling_volume(CHAIN_ID, TOKEN_ID, &USER2), 20);
+            assert_ok!(transfer(20));
+            assert_eq!(BridgeModule::rolling_volume(CHAIN_ID, TOKEN_ID, &USER2), 40);
+            assert_eq!(
+                transfer(10),
+                Err(DispatchError::Other(
+                    "Transfer declined, user blocked due to daily volume limit."
+                ))
+            );
+            assert_eq!(
+                BridgeModule::blocked_until((CHAIN_ID, TOKEN_ID), USER2),
+                Some(DAY_IN_BLOCKS * 3 + DAY_IN_BLOCKS / 2)
+            );
+        })
+    }
+    #[test]
+    fn first_deposit_hold_should_expire() {
+        ExtBuilder::default().build().execute_with(|| {
+            let eth_message_id = H256::from(ETH_MESSAGE_ID);
+            let eth_address = H160::from(ETH_ADDRESS);
+
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V2),
+                CHAIN_ID,
+                eth_message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                60
+            ));
+            assert_ok!(BridgeModule::multi_signed_mint(
+                Origin::signed(V1),
+                CHAIN_ID,
+                eth_message_id,
+                eth_address,
+                USER2,
+                TOKEN_ID,
+                60
+            ));
+            let first_deposit = System::block_number();
+            assert_eq!(BridgeModule::first_deposit(USER2), Some(first_deposit));
+
+            System::set_block_number(first_deposit + DAY_IN_BLOCKS);
+            assert_ok!(BridgeModule::set_transfer(
+                Origin::signed(USER2),
+                CHAIN_ID,
+                eth_address,
+                TOKEN_ID,
+                49
+            ));
+            let sub_message_id = BridgeModule::message_id_by_transfer_id(1);
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_ok!(approve_transfer(V2, sub_message_id));
+            assert_eq!(
+                BridgeModule::messages(sub_message_id).status,
+                Status::Approved
+            );
+        })
+    }
+
+    const ETH_RPC_URL: &str = "http://localhost:8545";
+
+    fn expect_eth_rpc(
+        state: &mut testing::OffchainState,
+        id: u16,
+        method: &[u8],
+        params: &[u8],
+        result: &str,
+    ) {
+        state.expect_request(
+            id,
+            testing::PendingRequest {
+                method: "POST".into(),
+                uri: ETH_RPC_URL.into(),
+                headers: vec![("Content-Type".into(), "application/json".into())],
+                body: eth_rpc_request(method, params),
+                response: Some(
+                    format!(r#"{{"jsonrpc":"2.0","id":1,"result":{}}}"#, result).into_bytes(),
+                ),
+                sent: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    fn eth_log(event: &[u8], words: &[[u8; 32]]) -> String {
+        let hex = |bytes: &[u8]| String::from_utf8(hex_encode(bytes)).unwrap();
+        format!(
+            r#"{{"topics":["{}"],"data":"{}","removed":false}}"#,
+            hex(&keccak_256(event)),
+            hex(&words.concat())
+        )
+    }
+
+    #[test]
+    fn offchain_worker_should_vote_for_ethereum_events() {
+        let (offchain, state) = testing::TestOffchainExt::new();
+        let mut ext = ExtBuilder::default().build();
+        ext.register_extension(OffchainExt::new(offchain));
+        ext.execute_with(|| {
+            let submitted = || SUBMITTED_CALLS.with(|calls| calls.borrow().clone());
+            let last_block_key = offchain_key(OFFCHAIN_LAST_BLOCK, CHAIN_ID);
+            let last_block = || StorageValueRef::persistent(&last_block_key).get::<u64>();
+            let contract = H160::from(ETH_ADDRESS);
+
+            // nodes without the endpoint don't relay
+            BridgeModule::offchain_worker(1);
+            assert_eq!(last_block(), None);
+
+            sp_io::offchain::local_storage_set(
+                StorageKind::PERSISTENT,
+                &offchain_key(OFFCHAIN_RPC_URL, CHAIN_ID),
+                ETH_RPC_URL.as_bytes(),
+            );
+            // the first run starts from the confirmed head
+            expect_eth_rpc(
+                &mut state.write(),
+                0,
+                b"eth_blockNumber",
+                b"[]",
+                r#""0x20""#,
+            );
+            BridgeModule::offchain_worker(2);
+            assert_eq!(last_block(), Some(Some(20)));
+            assert!(submitted().is_empty());
+
+            let mint_id = H256::from(ETH_MESSAGE_ID);
+            let burn_id = H256::from(ETH_MESSAGE_ID1);
+            let sender = H160::repeat_byte(7);
+            let mut sender_word = [0u8; 32];
+            sender_word[12..].copy_from_slice(sender.as_bytes());
+            let mut recipient = [0u8; 32];
+            recipient[0] = USER1 as u8;
+            let mut amount = [0u8; 32];
+            amount[31] = 10;
+            let logs = format!(
+                "[{},{}]",
+                eth_log(
+                    RELAY_MESSAGE_EVENT,
+                    &[mint_id.to_fixed_bytes(), sender_word, recipient, amount]
+                ),
+                eth_log(WITHDRAW_MESSAGE_EVENT, &[burn_id.to_fixed_bytes()])
+            );
+            expect_eth_rpc(
+                &mut state.write(),
+                1,
+                b"eth_blockNumber",
+                b"[]",
+                r#""0x30""#,
+            );
+            expect_eth_rpc(
+                &mut state.write(),
+                2,
+                b"eth_getLogs",
+                &eth_logs_params(contract, 21, 36),
+                &logs,
+            );
+            BridgeModule::offchain_worker(3);
+            assert_eq!(
+                submitted(),
+                vec![
+                    Call::multi_signed_mint(CHAIN_ID, mint_id, sender, USER1, TOKEN_ID, 10),
+                    Call::confirm_transfer(burn_id),
+                ]
+            );
+            assert_eq!(last_block(), Some(Some(36)));
+
+            // blocks are given back when the node can't sign
+            HAS_BRIDGE_KEY.with(|has_key| *has_key.borrow_mut() = false);
+            expect_eth_rpc(
+                &mut state.write(),
+                3,
+                b"eth_blockNumber",
+                b"[]",
+                r#""0x40""#,
+            );
+            expect_eth_rpc(
+                &mut state.write(),
+                4,
+                b"eth_getLogs",
+                &eth_logs_params(contract, 37, 52),
+                &logs,
+            );
+            BridgeModule::offchain_worker(4);
+            assert_eq!(last_block(), Some(Some(36)));
+            assert_eq!(submitted().len(), 2);
+        })
+    }
+
+    #[test]
+    fn hex_encoding_works() {
+        assert_eq!(hex_encode(&[0, 0xab, 0x1f]), b"0x00ab1f".to_vec());
+        assert_eq!(hex_encode_quantity(0), b"0x0".to_vec());
+        assert_eq!(hex_encode_quantity(0x1a2b), b"0x1a2b".to_vec());
+        let string = |s: &str| JsonValue::String(s.chars().collect());
+        assert_eq!(hex_quantity(string("0x1a2b")), Some(0x1a2b));
+        assert_eq!(hex_quantity(string("1a2b")), None);
+        assert_eq!(hex_data(string("0x00ab1f")), Some(vec![0, 0xab, 0x1f]));
+        assert_eq!(hex_data(string("0xab1")), None);
+        assert_eq!(hex_data(string("0xzz")), None);
+    }
 }
@@ -704,7 +704,7 @@ mod tests {
     };
     use sp_core::{H160, H256};
     use sp_runtime::{
-        testing::{Header, TestXt},
+        testing::{Header, TestSignature, TestXt, UintAuthorityId},
         traits::{BlakeTwo256, IdentityLookup},
         Perbill,
     };
@@ -790,9 +790,26 @@ mod tests {
     }
     impl token::Trait for Test {
         type Event = ();
+        type Signature = TestSignature;
+        type Signer = UintAuthorityId;
+        type SnapshotOrigin = system::EnsureRoot<u64>;
+    }
+    parameter_types! {
+        pub const TransferTtl: BlockNumber = 100;
+        pub const LimitWindow: BlockNumber = 14_400;
+        pub const ValidatorBond: u128 = 1000;
+        pub const TreasuryAccount: u64 = 100;
     }
     impl bridge::Trait for Test {
         type Event = ();
+        type TransferTtl = TransferTtl;
+        type LimitWindow = LimitWindow;
+        type ValidatorBond = ValidatorBond;
+        type Slash = ();
+        type TreasuryAccount = TreasuryAccount;
+        type GovernanceOrigin = system::EnsureRoot<u64>;
+        type ComplianceOrigin = system::EnsureRoot<u64>;
+        type SubmitTransaction = ();
     }
 
     pub type Extrinsic = TestXt<Call, ()>;
@@ -813,6 +830,7 @@ mod tests {
         //   Then you need to manucally kickoff pricefetch
         type GracePeriod = GracePeriod;
         type BlockFetchPeriod = BlockFetchPeriod;
+        type SourceOrigin = system::EnsureRoot<u64>;
     }
 
     impl Trait for Test {
@@ -850,6 +868,7 @@ mod tests {
     const VERY_SMALL_VOTE_TIMEOUT: u32 = MINIMUM_VOTE_TIOMEOUT - 1;
     const VERY_BIG_VOTE_TIMEOUT: u32 = MAXIMUM_VOTE_TIMEOUT + 1;
     const TOKEN_ID: TokenId = 0;
+    const CHAIN_ID: ChainId = ETHEREUM_CHAIN_ID;
     const PROPOSAL_ID: ProposalId = 0;
     const YES: bool = true;
     const NO: bool = false;
@@ -896,13 +915,19 @@ mod tests {
             let _ = bridge::GenesisConfig::<Test> {
                 validators_count: 3u32,
                 validator_accounts: vec![V1, V2, V3],
-                current_limits: vec![
-                    100 * 10u128.pow(18),
-                    200 * 10u128.pow(18),
-                    50 * 10u128.pow(18),
-                    400 * 10u128.pow(18),
-                    10 * 10u128.pow(18),
-                ],
+                chains: vec![(CHAIN_ID, H160::default(), vec![TOKEN_ID])],
+                current_limits: vec![(
+                    CHAIN_ID,
+                    TOKEN_ID,
+                    vec![
+                        100 * 10u128.pow(18),
+                        200 * 10u128.pow(18),
+                        50 * 10u128.pow(18),
+                        400 * 10u128.pow(18),
+                        10 * 10u128.pow(18),
+                    ],
+                )],
+                fees: vec![],
             }
             .assimilate_storage(&mut storage);
 
@@ -2364,7 +2389,10 @@ mod tests {
 
             let min_limit = 10 * 10u128.pow(18);
             let value = 15 * 10u128.pow(18);
-            assert_eq!(BridgeModule::current_limits().min_tx_value, min_limit);
+            assert_eq!(
+                BridgeModule::current_limits(CHAIN_ID, TOKEN_ID).min_tx_value,
+                min_limit
+            );
 
             // create dao
             assert_eq!(DaoModule::daos_count(), 0);
@@ -2412,7 +2440,7 @@ mod tests {
                 GET_LOAN,
                 YES
             ));
-            let token_amount = TokenModule::balance_of((TOKEN_ID, USER));
+            let token_amount = TokenModule::balance_of(TOKEN_ID, USER);
 
             // withdraw
             let eth_address = H160::from(ETH_ADDRESS);
@@ -2421,6 +2449,7 @@ mod tests {
             // substrate ----> ETH
             assert_ok!(BridgeModule::set_transfer(
                 Origin::signed(USER2),
+                CHAIN_ID,
                 eth_address,
                 TOKEN_ID,
                 token_amount
@@ -2434,23 +2463,29 @@ mod tests {
             assert_eq!(message.status, Status::Withdraw);
 
             // approval
-            assert_eq!(TokenModule::locked((0, USER2)), 0);
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V1),
-                sub_message_id
-            ));
-            assert_ok!(BridgeModule::approve_transfer(
-                Origin::signed(V2),
-                sub_message_id
-            ));
+            assert_eq!(TokenModule::locked(0, USER2), 0);
+            let approve_transfer = |validator: u64, message_id: H256| {
+                let digest = BridgeModule::attestation_digest(&BridgeModule::messages(message_id));
+                assert_ok!(BridgeModule::set_eth_signer(
+                    Origin::signed(validator),
+                    bridge::test_signer::address(validator)
+                ));
+                BridgeModule::approve_transfer(
+                    Origin::signed(validator),
+                    message_id,
+                    bridge::test_signer::sign(validator, digest),
+                )
+            };
+            assert_ok!(approve_transfer(V1, sub_message_id));
+            assert_ok!(approve_transfer(V2, sub_message_id));
 
             message = get_message();
             assert_eq!(message.status, Status::Approved);
 
             // at this point transfer is in Approved status and are waiting for confirmation
             // from ethereum side to burn. Funds are locked.
-            assert_eq!(TokenModule::locked((0, USER2)), token_amount);
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 0);
+            assert_eq!(TokenModule::locked(0, USER2), token_amount);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 0);
             // once it happends, validators call confirm_transfer
 
             assert_ok!(BridgeModule::confirm_transfer(
@@ -2469,7 +2504,7 @@ mod tests {
             // assert_ok!(BridgeModule::confirm_transfer(Origin::signed(USER1), sub_message_id));
             //BurnedMessage(Hash, AccountId, H160, u64) event emitted
             let tokens_left = amount1 - token_amount;
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), tokens_left);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), tokens_left);
             assert_eq!(TokenModule::total_supply(TOKEN_ID), tokens_left);
 
             assert_eq!(Balances::free_balance(DAO), 6000);
@@ -0,0 +1,1138 @@
+/// Ethereum light client for trust-minimised bridge mints
+///
+/// Relayers submit Ethereum block headers and the chain with the biggest total difficulty
+/// is canonical. Ethash seals are not verified on-chain, so a header counts for finality
+/// only once the quorum of the Ethereum chain validators attests it: the attested header
+/// becomes canonical with its ancestors, and those buried under `ConfirmationDepth`
+/// of its blocks are final.
+///
+/// Anyone can mint the `RelayMessage` deposit of the bridge contract registered for
+/// Ethereum in the bridge chains with the Merkle-Patricia proof of its receipt in
+/// a final header, validators don't have to vote for such deposits.
+///
+use crate::bridge;
+use crate::types::*;
+use codec::Decode;
+use frame_support::{
+    decl_event, decl_module, decl_storage,
+    dispatch::DispatchResult,
+    ensure, fail,
+    traits::{EnsureOrigin, Get},
+    weights::{DispatchClass, FunctionOf, SimpleDispatchInfo, Weight},
+    StorageMap, StorageValue,
+};
+use sp_core::{H160, H256, U256};
+use sp_io::hashing::keccak_256;
+use sp_runtime::SaturatedConversion;
+use sp_std::{prelude::Vec, slice::Iter};
+use system::{self, ensure_signed};
+
+type Result<T> = core::result::Result<T, &'static str>;
+
+const MAX_HEADERS_PER_CALL: usize = 100;
+const HEADER_WEIGHT: Weight = 10_000;
+// event RelayMessage(bytes32 messageID, address sender, bytes32 recipient, uint amount)
+const RELAY_MESSAGE_SIGNATURE: &[u8] = b"RelayMessage(bytes32,address,bytes32,uint256)";
+
+decl_event!(
+    pub enum Event<T>
+    where
+        AccountId = <T as system::Trait>::AccountId,
+        Hash = <T as system::Trait>::Hash,
+    {
+        HeaderImported(H256, u64),
+        HeaderAttested(H256, AccountId),
+        HeaderFinalized(H256, u64),
+        CheckpointSet(H256, u64),
+        RelayersChanged(Vec<AccountId>),
+        ProvenMint(H256, Hash),
+    }
+);
+
+pub trait Trait: bridge::Trait {
+    type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    /// Number of blocks built on top of the header before its receipts can be minted.
+    type ConfirmationDepth: Get<u64>;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as EthLightClient {
+        Relayers get(fn relayers) config(): Vec<T::AccountId>;
+        Headers get(fn header): map hasher(blake2_128_concat) H256 => Option<EthHeader>;
+        CanonicalHashes get(fn canonical_hash): map hasher(twox_64_concat) u64 => Option<H256>;
+        BestHeader get(fn best_header): H256;
+        // the highest header attested by the validators quorum, finality is counted from it
+        AttestedHeader get(fn attested_header): H256;
+        HeaderAttestations get(fn header_attestations): map hasher(blake2_128_concat) H256 => Vec<T::AccountId>;
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        fn deposit_event() = default;
+
+        // trusted header the chain is built from, used to start and to recover the client
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn set_checkpoint(origin, header: Vec<u8>, total_difficulty: U256) -> DispatchResult {
+            <T as bridge::Trait>::GovernanceOrigin::ensure_origin(origin)?;
+            let (hash, mut header) = decode_header(&header)?;
+            header.total_difficulty = total_difficulty;
+
+            <CanonicalHashes>::insert(header.number, hash);
+            <BestHeader>::put(hash);
+            <AttestedHeader>::put(hash);
+            Self::deposit_event(RawEvent::CheckpointSet(hash, header.number));
+            <Headers>::insert(hash, header);
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        pub fn set_relayers(origin, relayers: Vec<T::AccountId>) -> DispatchResult {
+            <T as bridge::Trait>::GovernanceOrigin::ensure_origin(origin)?;
+            <Relayers<T>>::put(relayers.clone());
+            Self::deposit_event(RawEvent::RelayersChanged(relayers));
+            Ok(())
+        }
+
+        // RLP encoded headers, each one is a child of a known header
+        #[weight = FunctionOf(
+            |args: (&Vec<Vec<u8>>,)| HEADER_WEIGHT * (args.0.len().max(1) as Weight),
+            DispatchClass::Normal,
+            true
+        )]
+        pub fn submit_headers(origin, headers: Vec<Vec<u8>>) -> DispatchResult {
+            let relayer = ensure_signed(origin)?;
+            ensure!(Self::relayers().contains(&relayer), "Only relayers can submit headers");
+            ensure!(headers.len() <= MAX_HEADERS_PER_CALL, "Too many headers");
+
+            for header in headers.iter() {
+                Self::import_header(header)?;
+            }
+            Ok(())
+        }
+
+        // validator has seen the header on Ethereum
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        pub fn attest_header(origin, hash: H256) -> DispatchResult {
+            let validator = ensure_signed(origin)?;
+            let (validators, quorum) = <bridge::Module<T>>::chain_validator_set(ETHEREUM_CHAIN_ID);
+            ensure!(validators.contains(&validator), "Only validators can call this function");
+            let header = Self::header(hash).ok_or("Unknown header")?;
+            let attested = Self::header(Self::attested_header()).unwrap_or_default();
+            ensure!(header.number > attested.number, "Header is not above the attested one");
+            let mut attestations = Self::header_attestations(hash);
+            ensure!(!attestations.contains(&validator), "This validator has already attested the header");
+
+            attestations.push(validator.clone());
+            // only votes of the current validators are counted
+            let votes = attestations.iter().filter(|v| validators.contains(v)).count() as u64;
+            <HeaderAttestations<T>>::insert(hash, attestations);
+            Self::deposit_event(RawEvent::HeaderAttested(hash, validator));
+            if votes >= quorum {
+                Self::finalize(hash, header.number);
+            }
+            Ok(())
+        }
+
+        // mint the deposit with the proof of its receipt in the final header
+        #[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+        pub fn mint_with_proof(origin, block_hash: H256, receipt_index: u64, log_index: u32, proof: Vec<Vec<u8>>) -> DispatchResult {
+            ensure_signed(origin)?;
+            let header = Self::header(block_hash).ok_or("Unknown header")?;
+            ensure!(Self::is_final(block_hash, &header), "Header is not final");
+
+            let receipt = verify_proof(header.receipts_root, &receipt_key(receipt_index), &proof)?;
+            let message = decode_relay_message(&receipt, log_index)?;
+            // the contract relays a single token, the first one of the chain
+            let chain = <bridge::Module<T>>::chains(ETHEREUM_CHAIN_ID).ok_or("Chain is not registered")?;
+            ensure!(chain.contract == message.contract, "Log is not from the bridge contract");
+            let token_id = *chain.tokens.first().ok_or("Chain has no tokens")?;
+
+            let message_id = T::Hash::decode(&mut &message.message_id[..])
+                .map_err(|_| "Invalid message id")?;
+            let recipient = T::AccountId::decode(&mut &message.recipient[..])
+                .map_err(|_| "Invalid recipient")?;
+            ensure!(message.amount.bits() <= 128, "Amount overflows the balance");
+            let amount = message.amount.low_u128();
+            let balance: T::Balance = amount.saturated_into();
+            ensure!(balance.saturated_into::<u128>() == amount, "Amount overflows the balance");
+
+            <bridge::Module<T>>::mint_proven(ETHEREUM_CHAIN_ID, message_id, message.sender, recipient, token_id, balance)?;
+            Self::deposit_event(RawEvent::ProvenMint(block_hash, message_id));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    fn import_header(raw: &[u8]) -> Result<()> {
+        let (hash, mut header) = decode_header(raw)?;
+        ensure!(!<Headers>::contains_key(hash), "Header is already imported");
+        let parent = Self::header(header.parent_hash).ok_or("Unknown parent header")?;
+        ensure!(
+            header.number == parent.number + 1,
+            "Header number does not follow its parent"
+        );
+        ensure!(
+            header.timestamp > parent.timestamp,
+            "Header timestamp does not follow its parent"
+        );
+        ensure!(!header.difficulty.is_zero(), "Header has no difficulty");
+
+        // forks of the final chain are never accepted
+        let finalized = Self::finalized_number();
+        ensure!(
+            header.number > finalized
+                && (parent.number > finalized
+                    || Self::canonical_hash(parent.number) == Some(header.parent_hash)),
+            "Header conflicts with the final chain"
+        );
+
+        header.total_difficulty = parent.total_difficulty.saturating_add(header.difficulty);
+        let best = Self::header(Self::best_header()).unwrap_or_default();
+        let is_best = header.total_difficulty > best.total_difficulty;
+        let number = header.number;
+        <Headers>::insert(hash, header);
+
+        if is_best {
+            <BestHeader>::put(hash);
+            Self::set_canonical(number, hash);
+        }
+        Self::deposit_event(RawEvent::HeaderImported(hash, number));
+        Ok(())
+    }
+
+    // attested header wins over heavier chains relayers may have submitted
+    fn finalize(hash: H256, number: u64) {
+        if Self::canonical_hash(number) != Some(hash) {
+            <BestHeader>::put(hash);
+        }
+        Self::set_canonical(number, hash);
+        <AttestedHeader>::put(hash);
+        Self::deposit_event(RawEvent::HeaderFinalized(hash, number));
+    }
+
+    // make the header and its ancestors canonical, walk stops at the common ancestor
+    fn set_canonical(mut number: u64, mut hash: H256) {
+        while Self::canonical_hash(number) != Some(hash) {
+            <CanonicalHashes>::insert(number, hash);
+            match Self::header(hash) {
+                Some(header) if number > 0 => {
+                    number -= 1;
+                    hash = header.parent_hash;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn finalized_number() -> u64 {
+        Self::header(Self::attested_header())
+            .map(|attested| attested.number.saturating_sub(T::ConfirmationDepth::get()))
+            .unwrap_or_default()
+    }
+
+    pub fn is_final(hash: H256, header: &EthHeader) -> bool {
+        header.number <= Self::finalized_number()
+            && Self::canonical_hash(header.number) == Some(hash)
+    }
+}
+
+// `RelayMessage` event emitted by the bridge contract
+struct RelayMessage {
+    contract: H160,
+    message_id: [u8; 32],
+    sender: H160,
+    recipient: [u8; 32],
+    amount: U256,
+}
+
+fn decode_header(raw: &[u8]) -> Result<(H256, EthHeader)> {
+    let fields = decode_rlp_list(raw)?;
+    ensure!(fields.len() >= 15, "Invalid header");
+    let header = EthHeader {
+        parent_hash: decode_rlp_h256(fields[0])?,
+        receipts_root: decode_rlp_h256(fields[5])?,
+        difficulty: decode_rlp_u256(fields[7])?,
+        number: decode_rlp_u64(fields[8])?,
+        timestamp: decode_rlp_u64(fields[11])?,
+        total_difficulty: U256::zero(),
+    };
+    Ok((H256::from(keccak_256(raw)), header))
+}
+
+fn decode_relay_message(receipt: &[u8], log_index: u32) -> Result<RelayMessage> {
+    // typed receipts (EIP-2718) are prefixed with the transaction type
+    let receipt = match receipt.first() {
+        Some(kind) if *kind < 0xc0 => &receipt[1..],
+        _ => receipt,
+    };
+    let fields = decode_rlp_list(receipt)?;
+    ensure!(fields.len() == 4, "Invalid receipt");
+    ensure!(
+        decode_rlp_bytes(fields[0])? == &[1u8][..],
+        "Transaction has failed"
+    );
+
+    let logs = decode_rlp_list(fields[3])?;
+    let log = decode_rlp_list(logs.get(log_index as usize).ok_or("Log does not exist")?)?;
+    ensure!(log.len() == 3, "Invalid log");
+    let contract = decode_rlp_bytes(log[0])?;
+    ensure!(contract.len() == 20, "Invalid log");
+    let topics = decode_rlp_list(log[1])?;
+    let topic = decode_rlp_bytes(topics.first().ok_or("Log is not a RelayMessage")?)?;
+    ensure!(
+        topic == &keccak_256(RELAY_MESSAGE_SIGNATURE)[..],
+        "Log is not a RelayMessage"
+    );
+    // not indexed arguments are 32 bytes words
+    let data = decode_rlp_bytes(log[2])?;
+    ensure!(data.len() == 128, "Invalid RelayMessage data");
+
+    let mut message_id = [0u8; 32];
+    message_id.copy_from_slice(&data[..32]);
+    let mut recipient = [0u8; 32];
+    recipient.copy_from_slice(&data[64..96]);
+    Ok(RelayMessage {
+        contract: H160::from_slice(contract),
+        message_id,
+        sender: H160::from_slice(&data[44..64]),
+        recipient,
+        amount: U256::from_big_endian(&data[96..]),
+    })
+}
+
+// receipts trie is keyed by RLP encoded receipt index
+fn receipt_key(index: u64) -> Vec<u8> {
+    let bytes = index
+        .to_be_bytes()
+        .iter()
+        .skip_while(|b| **b == 0)
+        .cloned()
+        .collect::<Vec<u8>>();
+    match bytes.as_slice() {
+        [] => Vec::from(&[0x80][..]),
+        [b] if *b < 0x80 => bytes,
+        _ => {
+            let mut key = Vec::from(&[0x80 + bytes.len() as u8][..]);
+            key.extend(bytes);
+            key
+        }
+    }
+}
+
+/// Value stored under the key in Merkle-Patricia trie with the root.
+/// Proof is the list of RLP encoded nodes on the path from the root.
+fn verify_proof(root: H256, key: &[u8], proof: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let mut nibbles = Vec::new();
+    for b in key {
+        nibbles.push(b >> 4);
+        nibbles.push(b & 0x0f);
+    }
+    let mut nodes = proof.iter();
+    let mut node = proof_node(root.as_bytes(), &mut nodes)?;
+    let mut position = 0;
+
+    loop {
+        let items = decode_rlp_list(&node)?;
+        let child = match items.len() {
+            // branch
+            17 => {
+                if position == nibbles.len() {
+                    let value = decode_rlp_bytes(items[16])?;
+                    ensure!(!value.is_empty(), "Key is not in the trie");
+                    return Ok(value.to_vec());
+                }
+                position += 1;
+                items[nibbles[position - 1] as usize]
+            }
+            // extension or leaf
+            2 => {
+                let (path, is_leaf) = decode_path(decode_rlp_bytes(items[0])?)?;
+                ensure!(
+                    nibbles[position..].starts_with(&path),
+                    "Key is not in the trie"
+                );
+                position += path.len();
+                if is_leaf {
+                    ensure!(position == nibbles.len(), "Key is not in the trie");
+                    return Ok(decode_rlp_bytes(items[1])?.to_vec());
+                }
+                items[1]
+            }
+            _ => fail!("Invalid trie node"),
+        };
+        node = child_node(child, &mut nodes)?;
+    }
+}
+
+// nodes shorter than 32 bytes are embedded into their parent instead of the hash
+fn child_node(child: &[u8], nodes: &mut Iter<Vec<u8>>) -> Result<Vec<u8>> {
+    if split_rlp(child)?.0 {
+        return Ok(child.to_vec());
+    }
+    let hash = decode_rlp_bytes(child)?;
+    ensure!(hash.len() == 32, "Key is not in the trie");
+    proof_node(hash, nodes)
+}
+
+fn proof_node(hash: &[u8], nodes: &mut Iter<Vec<u8>>) -> Result<Vec<u8>> {
+    let node = nodes.next().ok_or("Proof is incomplete")?;
+    ensure!(keccak_256(node)[..] == *hash, "Proof node hash mismatch");
+    Ok(node.clone())
+}
+
+// hex-prefix encoded path of extension and leaf nodes
+fn decode_path(encoded: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let first = *encoded.first().ok_or("Invalid trie node")?;
+    let flag = first >> 4;
+    ensure!(flag <= 3, "Invalid trie node");
+    let mut path = Vec::new();
+    if flag & 1 == 1 {
+        path.push(first & 0x0f);
+    }
+    for b in &encoded[1..] {
+        path.push(b >> 4);
+        path.push(b & 0x0f);
+    }
+    Ok((path, flag & 2 == 2))
+}
+
+// splits RLP data into the first item payload and the rest, tells if the item is a list
+fn split_rlp(data: &[u8]) -> Result<(bool, &[u8], &[u8])> {
+    let first = *data.first().ok_or("Unexpected end of RLP data")?;
+    let (is_list, offset, len) = match first {
+        0x00..=0x7f => return Ok((false, &data[..1], &data[1..])),
+        0x80..=0xb7 => (false, 1, (first - 0x80) as usize),
+        0xb8..=0xbf => {
+            let size = (first - 0xb7) as usize;
+            (false, 1 + size, decode_length(&data[1..], size)?)
+        }
+        0xc0..=0xf7 => (true, 1, (first - 0xc0) as usize),
+        _ => {
+            let size = (first - 0xf7) as usize;
+            (true, 1 + size, decode_length(&data[1..], size)?)
+        }
+    };
+    let end = offset.checked_add(len).ok_or("RLP item is too long")?;
+    ensure!(data.len() >= end, "Unexpected end of RLP data");
+    Ok((is_list, &data[offset..end], &data[end..]))
+}
+
+fn decode_length(data: &[u8], size: usize) -> Result<usize> {
+    ensure!(size <= 4, "RLP item is too long");
+    ensure!(data.len() >= size, "Unexpected end of RLP data");
+    Ok(data[..size]
+        .iter()
+        .fold(0usize, |len, b| (len << 8) | *b as usize))
+}
+
+// encoded items of the list
+fn decode_rlp_list(data: &[u8]) -> Result<Vec<&[u8]>> {
+    let (is_list, mut payload, rest) = split_rlp(data)?;
+    ensure!(is_list && rest.is_empty(), "Expected RLP list");
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let (_, _, next) = split_rlp(payload)?;
+        items.push(&payload[..payload.len() - next.len()]);
+        payload = next;
+    }
+    Ok(items)
+}
+
+fn decode_rlp_bytes(data: &[u8]) -> Result<&[u8]> {
+    let (is_list, payload, rest) = split_rlp(data)?;
+    ensure!(!is_list && rest.is_empty(), "Expected RLP string");
+    Ok(payload)
+}
+
+fn decode_rlp_u64(data: &[u8]) -> Result<u64> {
+    let bytes = decode_rlp_bytes(data)?;
+    ensure!(bytes.len() <= 8, "RLP integer is too big");
+    Ok(bytes.iter().fold(0u64, |n, b| (n << 8) | *b as u64))
+}
+
+fn decode_rlp_u256(data: &[u8]) -> Result<U256> {
+    let bytes = decode_rlp_bytes(data)?;
+    ensure!(bytes.len() <= 32, "RLP integer is too big");
+    Ok(U256::from_big_endian(bytes))
+}
+
+fn decode_rlp_h256(data: &[u8]) -> Result<H256> {
+    let bytes = decode_rlp_bytes(data)?;
+    ensure!(bytes.len() == 32, "Expected 32 bytes hash");
+    Ok(H256::from_slice(bytes))
+}
+
+/// tests for this module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::token;
+    use crate::types::Token;
+    use frame_support::{
+        assert_noop, assert_ok, impl_outer_origin, parameter_types, weights::Weight,
+    };
+    use sp_runtime::{
+        testing::{Header, TestSignature, UintAuthorityId},
+        traits::{BlakeTwo256, IdentityLookup},
+        DispatchError, Perbill,
+    };
+
+    pub type BlockNumber = u64;
+    pub type Balance = u128;
+
+    impl_outer_origin! {
+        pub enum Origin for Test {}
+    }
+
+    #[derive(Clone, Eq, PartialEq)]
+    pub struct Test;
+    parameter_types! {
+        pub const BlockHashCount: u64 = 250;
+        pub const MaximumBlockWeight: Weight = 1024;
+        pub const MaximumBlockLength: u32 = 2 * 1024;
+        pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+        pub const ExistentialDeposit: Balance = 1;
+        pub const MinimumPeriod: u64 = 5;
+        pub const TransferTtl: BlockNumber = 100;
+        pub const LimitWindow: BlockNumber = 14_400;
+        pub const ValidatorBond: Balance = 0;
+        pub const TreasuryAccount: u64 = 100;
+        pub const ConfirmationDepth: u64 = 2;
+    }
+    impl system::Trait for Test {
+        type Origin = Origin;
+        type Call = ();
+        type Index = u64;
+        type BlockNumber = BlockNumber;
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = ();
+        type BlockHashCount = BlockHashCount;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+        type Version = ();
+        type ModuleToIndex = ();
+        type AccountData = balances::AccountData<u128>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+    }
+    impl balances::Trait for Test {
+        type Balance = Balance;
+        type DustRemoval = ();
+        type Event = ();
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = system::Module<Test>;
+    }
+    impl timestamp::Trait for Test {
+        type Moment = u64;
+        type OnTimestampSet = ();
+        type MinimumPeriod = MinimumPeriod;
+    }
+    impl token::Trait for Test {
+        type Event = ();
+        type Signature = TestSignature;
+        type Signer = UintAuthorityId;
+        type SnapshotOrigin = system::EnsureRoot<u64>;
+    }
+    impl bridge::Trait for Test {
+        type Event = ();
+        type TransferTtl = TransferTtl;
+        type LimitWindow = LimitWindow;
+        type ValidatorBond = ValidatorBond;
+        type Slash = ();
+        type TreasuryAccount = TreasuryAccount;
+        type GovernanceOrigin = system::EnsureRoot<u64>;
+        type ComplianceOrigin = system::EnsureRoot<u64>;
+        type SubmitTransaction = ();
+    }
+    impl Trait for Test {
+        type Event = ();
+        type ConfirmationDepth = ConfirmationDepth;
+    }
+
+    type LightClient = Module<Test>;
+    type BridgeModule = bridge::Module<Test>;
+    type TokenModule = token::Module<Test>;
+
+    const RELAYER: u64 = 1;
+    const USER: u64 = 5;
+    const TOKEN_ID: TokenId = 0;
+    const CHAIN_ID: ChainId = ETHEREUM_CHAIN_ID;
+    const CONTRACT: [u8; 20] = [0xda; 20];
+    const SENDER: [u8; 20] = [0x5e; 20];
+    const DIFFICULTY: u64 = 1_000;
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let mut storage = system::GenesisConfig::default()
+            .build_storage::<Test>()
+            .unwrap();
+        let _ = token::GenesisConfig {
+            tokens: vec![Token {
+                id: TOKEN_ID,
+                decimals: 18,
+                symbol: Vec::from("DAI"),
+            }],
+        }
+        .assimilate_storage(&mut storage);
+        let _ = bridge::GenesisConfig::<Test> {
+            validators_count: 3u32,
+            validator_accounts: vec![2, 3, 4],
+            chains: vec![(CHAIN_ID, H160::from(CONTRACT), vec![TOKEN_ID])],
+            current_limits: vec![(CHAIN_ID, TOKEN_ID, vec![100, 200, 50, 400, 1])],
+            fees: vec![],
+        }
+        .assimilate_storage(&mut storage);
+        let _ = GenesisConfig::<Test> {
+            relayers: vec![RELAYER],
+        }
+        .assimilate_storage(&mut storage);
+        storage.into()
+    }
+
+    // recorded data is replaced with RLP encoded fixtures built the same way
+    fn encode_length(len: usize, offset: u8) -> Vec<u8> {
+        if len < 56 {
+            return vec![offset + len as u8];
+        }
+        let bytes = (len as u64)
+            .to_be_bytes()
+            .iter()
+            .skip_while(|b| **b == 0)
+            .cloned()
+            .collect::<Vec<u8>>();
+        let mut out = vec![offset + 55 + bytes.len() as u8];
+        out.extend(bytes);
+        out
+    }
+
+    fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+        if bytes.len() == 1 && bytes[0] < 0x80 {
+            return bytes.to_vec();
+        }
+        let mut out = encode_length(bytes.len(), 0x80);
+        out.extend_from_slice(bytes);
+        out
+    }
+
+    fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        let mut out = encode_length(payload.len(), 0xc0);
+        out.extend(payload);
+        out
+    }
+
+    fn encode_uint(n: u64) -> Vec<u8> {
+        let bytes = n
+            .to_be_bytes()
+            .iter()
+            .skip_while(|b| **b == 0)
+            .cloned()
+            .collect::<Vec<u8>>();
+        encode_bytes(&bytes)
+    }
+
+    fn eth_header(parent: H256, number: u64, difficulty: u64, receipts_root: H256) -> Vec<u8> {
+        encode_list(&[
+            encode_bytes(parent.as_bytes()),
+            encode_bytes(&[0u8; 32]),
+            encode_bytes(&[0u8; 20]),
+            encode_bytes(&[0u8; 32]),
+            encode_bytes(&[0u8; 32]),
+            encode_bytes(receipts_root.as_bytes()),
+            encode_bytes(&[0u8; 256]),
+            encode_uint(difficulty),
+            encode_uint(number),
+            encode_uint(8_000_000),
+            encode_uint(0),
+            encode_uint(1_600_000_000 + number * 13),
+            encode_bytes(b"akropolis"),
+            encode_bytes(&[0u8; 32]),
+            encode_bytes(&[0u8; 8]),
+        ])
+    }
+
+    fn hash_of(raw: &[u8]) -> H256 {
+        H256::from(keccak_256(raw))
+    }
+
+    fn relay_message_log(message_id: u8, recipient: u64, amount: u64) -> Vec<u8> {
+        let mut data = vec![message_id; 32];
+        data.extend_from_slice(&[0u8; 12]);
+        data.extend_from_slice(&SENDER);
+        let mut account = recipient.to_le_bytes().to_vec();
+        account.resize(32, 0);
+        data.extend(account);
+        data.extend_from_slice(&[0u8; 24]);
+        data.extend_from_slice(&amount.to_be_bytes());
+        encode_list(&[
+            encode_bytes(&CONTRACT),
+            encode_list(&[encode_bytes(&keccak_256(RELAY_MESSAGE_SIGNATURE))]),
+            encode_bytes(&data),
+        ])
+    }
+
+    fn receipt(status: u64, logs: &[Vec<u8>]) -> Vec<u8> {
+        encode_list(&[
+            encode_uint(status),
+            encode_uint(21_000),
+            encode_bytes(&[0u8; 256]),
+            encode_list(logs),
+        ])
+    }
+
+    // trie of two receipts under keys 0x80 and 0x01: the branch by the first nibble and two leaves
+    fn receipts_trie(first: &[u8], second: &[u8]) -> (H256, Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        let first_leaf = encode_list(&[encode_bytes(&[0x30]), encode_bytes(first)]);
+        let second_leaf = encode_list(&[encode_bytes(&[0x31]), encode_bytes(second)]);
+        let mut children = vec![encode_bytes(&[]); 17];
+        children[8] = encode_bytes(&keccak_256(&first_leaf));
+        children[0] = encode_bytes(&keccak_256(&second_leaf));
+        let branch = encode_list(&children);
+        (
+            hash_of(&branch),
+            vec![branch.clone(), first_leaf],
+            vec![branch, second_leaf],
+        )
+    }
+
+    // checkpoint and a chain of `count` headers on top of it attested by the validators,
+    // the first one holds the receipts
+    fn import_chain(receipts_root: H256, count: u64) -> Vec<H256> {
+        let checkpoint = eth_header(H256::zero(), 100, DIFFICULTY, H256::zero());
+        assert_ok!(LightClient::set_checkpoint(
+            system::RawOrigin::Root.into(),
+            checkpoint.clone(),
+            U256::from(DIFFICULTY)
+        ));
+        let mut hashes = vec![hash_of(&checkpoint)];
+        for number in 101..101 + count {
+            let root = if number == 101 {
+                receipts_root
+            } else {
+                H256::zero()
+            };
+            let header = eth_header(*hashes.last().unwrap(), number, DIFFICULTY, root);
+            assert_ok!(LightClient::submit_headers(
+                Origin::signed(RELAYER),
+                vec![header.clone()]
+            ));
+            hashes.push(hash_of(&header));
+        }
+        attest(*hashes.last().unwrap());
+        hashes
+    }
+
+    fn attest(hash: H256) {
+        for validator in 2..4 {
+            assert_ok!(LightClient::attest_header(Origin::signed(validator), hash));
+        }
+    }
+
+    #[test]
+    fn rlp_decoding_works() {
+        let long = vec![0xab; 60];
+        let list = encode_list(&[encode_uint(0), encode_uint(0x7f), encode_bytes(&long)]);
+        let items = decode_rlp_list(&list).unwrap();
+        assert_eq!(items.len(), 3);
+        assert_eq!(decode_rlp_u64(items[0]), Ok(0));
+        assert_eq!(decode_rlp_u64(items[1]), Ok(0x7f));
+        assert_eq!(decode_rlp_bytes(items[2]), Ok(&long[..]));
+
+        assert_eq!(
+            decode_rlp_list(&list[..list.len() - 1]),
+            Err("Unexpected end of RLP data")
+        );
+        assert_eq!(decode_rlp_bytes(&list), Err("Expected RLP string"));
+        assert_eq!(receipt_key(0), vec![0x80]);
+        assert_eq!(receipt_key(0x7f), vec![0x7f]);
+        assert_eq!(receipt_key(0x80), vec![0x81, 0x80]);
+        assert_eq!(receipt_key(0x0400), vec![0x82, 0x04, 0x00]);
+    }
+
+    #[test]
+    fn headers_import_works() {
+        new_test_ext().execute_with(|| {
+            let hashes = import_chain(H256::zero(), 3);
+            assert_eq!(LightClient::best_header(), hashes[3]);
+            assert_eq!(LightClient::header(hashes[3]).unwrap().number, 103);
+            assert_eq!(
+                LightClient::header(hashes[3]).unwrap().total_difficulty,
+                U256::from(4 * DIFFICULTY)
+            );
+            assert_eq!(LightClient::canonical_hash(102), Some(hashes[2]));
+
+            let next = eth_header(hashes[3], 104, DIFFICULTY, H256::zero());
+            assert_noop!(
+                LightClient::submit_headers(Origin::signed(USER), vec![next.clone()]),
+                "Only relayers can submit headers"
+            );
+            let orphan = eth_header(H256::repeat_byte(1), 104, DIFFICULTY, H256::zero());
+            assert_noop!(
+                LightClient::submit_headers(Origin::signed(RELAYER), vec![orphan]),
+                "Unknown parent header"
+            );
+            let skipped = eth_header(hashes[3], 105, DIFFICULTY, H256::zero());
+            assert_noop!(
+                LightClient::submit_headers(Origin::signed(RELAYER), vec![skipped]),
+                "Header number does not follow its parent"
+            );
+            let no_difficulty = eth_header(hashes[3], 104, 0, H256::zero());
+            assert_noop!(
+                LightClient::submit_headers(Origin::signed(RELAYER), vec![no_difficulty]),
+                "Header has no difficulty"
+            );
+            assert_ok!(LightClient::submit_headers(
+                Origin::signed(RELAYER),
+                vec![next.clone()]
+            ));
+            assert_noop!(
+                LightClient::submit_headers(Origin::signed(RELAYER), vec![next]),
+                "Header is already imported"
+            );
+            assert_noop!(
+                LightClient::set_relayers(Origin::signed(RELAYER), vec![USER]),
+                DispatchError::BadOrigin
+            );
+        })
+    }
+
+    #[test]
+    fn heaviest_chain_should_be_canonical() {
+        new_test_ext().execute_with(|| {
+            let hashes = import_chain(H256::zero(), 3);
+
+            // fork from 102 with more difficult header
+            let fork = eth_header(hashes[2], 103, 3 * DIFFICULTY, H256::zero());
+            assert_ok!(LightClient::submit_headers(
+                Origin::signed(RELAYER),
+                vec![fork.clone()]
+            ));
+            assert_eq!(LightClient::best_header(), hash_of(&fork));
+            assert_eq!(LightClient::canonical_hash(103), Some(hash_of(&fork)));
+            assert_eq!(LightClient::canonical_hash(102), Some(hashes[2]));
+
+            // lighter fork is stored, but isn't canonical
+            let light = eth_header(hashes[3], 104, DIFFICULTY, H256::zero());
+            assert_ok!(LightClient::submit_headers(
+                Origin::signed(RELAYER),
+                vec![light.clone()]
+            ));
+            assert_eq!(LightClient::best_header(), hash_of(&fork));
+            assert!(LightClient::header(hash_of(&light)).is_some());
+
+            // 101 is final, fork from the checkpoint is rejected
+            let late = eth_header(hashes[0], 101, 100 * DIFFICULTY, H256::repeat_byte(1));
+            assert_noop!(
+                LightClient::submit_headers(Origin::signed(RELAYER), vec![late]),
+                "Header conflicts with the final chain"
+            );
+        })
+    }
+
+    #[test]
+    fn forged_headers_should_not_be_final() {
+        new_test_ext().execute_with(|| {
+            let first = receipt(1, &[relay_message_log(1, USER, 60)]);
+            let second = receipt(1, &[relay_message_log(2, USER, 30)]);
+            let (root, first_proof, _) = receipts_trie(&first, &second);
+            let hashes = import_chain(H256::zero(), 3);
+
+            // relayer forges a heavier chain from the last attested header with a fake receipt
+            let mut forged = vec![hashes[3]];
+            for number in 104..107 {
+                let root = if number == 104 { root } else { H256::zero() };
+                let header = eth_header(*forged.last().unwrap(), number, 100 * DIFFICULTY, root);
+                assert_ok!(LightClient::submit_headers(
+                    Origin::signed(RELAYER),
+                    vec![header.clone()]
+                ));
+                forged.push(hash_of(&header));
+            }
+            assert_eq!(LightClient::best_header(), forged[3]);
+            assert_noop!(
+                LightClient::mint_with_proof(
+                    Origin::signed(USER),
+                    forged[1],
+                    0,
+                    0,
+                    first_proof.clone()
+                ),
+                "Header is not final"
+            );
+            assert_noop!(
+                LightClient::attest_header(Origin::signed(RELAYER), forged[3]),
+                "Only validators can call this function"
+            );
+
+            // validators attest the real chain, the forged one is dropped
+            let real = eth_header(hashes[3], 104, DIFFICULTY, H256::zero());
+            assert_ok!(LightClient::submit_headers(
+                Origin::signed(RELAYER),
+                vec![real.clone()]
+            ));
+            assert_ok!(LightClient::attest_header(Origin::signed(2), forged[3]));
+            assert_noop!(
+                LightClient::attest_header(Origin::signed(2), forged[3]),
+                "This validator has already attested the header"
+            );
+            attest(hash_of(&real));
+            assert_eq!(LightClient::best_header(), hash_of(&real));
+            assert_eq!(LightClient::canonical_hash(104), Some(hash_of(&real)));
+            assert_noop!(
+                LightClient::attest_header(Origin::signed(3), forged[1]),
+                "Header is not above the attested one"
+            );
+
+            // forged parent below the final header is rejected
+            let forged_parent = eth_header(hashes[1], 102, 100 * DIFFICULTY, root);
+            assert_noop!(
+                LightClient::submit_headers(Origin::signed(RELAYER), vec![forged_parent]),
+                "Header conflicts with the final chain"
+            );
+            assert_noop!(
+                LightClient::mint_with_proof(Origin::signed(USER), forged[1], 0, 0, first_proof),
+                "Header is not final"
+            );
+        })
+    }
+
+    #[test]
+    fn mint_with_proof_works() {
+        new_test_ext().execute_with(|| {
+            let first = receipt(1, &[relay_message_log(1, USER, 60)]);
+            // typed receipt with unrelated log before the message
+            let mut second = vec![0x02];
+            second.extend(receipt(
+                1,
+                &[
+                    encode_list(&[
+                        encode_bytes(&CONTRACT),
+                        encode_list(&[encode_bytes(&[0u8; 32])]),
+                        encode_bytes(&[]),
+                    ]),
+                    relay_message_log(2, USER, 30),
+                ],
+            ));
+            let (root, first_proof, second_proof) = receipts_trie(&first, &second);
+            let hashes = import_chain(root, 3);
+
+            assert_noop!(
+                LightClient::mint_with_proof(
+                    Origin::signed(USER),
+                    hashes[2],
+                    0,
+                    0,
+                    first_proof.clone()
+                ),
+                "Header is not final"
+            );
+            assert_noop!(
+                LightClient::mint_with_proof(
+                    Origin::signed(USER),
+                    hashes[1],
+                    0,
+                    0,
+                    second_proof.clone()
+                ),
+                "Proof node hash mismatch"
+            );
+            assert_noop!(
+                LightClient::mint_with_proof(
+                    Origin::signed(USER),
+                    hashes[1],
+                    1,
+                    0,
+                    second_proof.clone()
+                ),
+                "Log is not a RelayMessage"
+            );
+            assert_noop!(
+                LightClient::mint_with_proof(
+                    Origin::signed(USER),
+                    hashes[1],
+                    2,
+                    0,
+                    second_proof.clone()
+                ),
+                "Key is not in the trie"
+            );
+
+            assert_ok!(LightClient::mint_with_proof(
+                Origin::signed(USER),
+                hashes[1],
+                0,
+                0,
+                first_proof.clone()
+            ));
+            assert_ok!(LightClient::mint_with_proof(
+                Origin::signed(USER),
+                hashes[1],
+                1,
+                1,
+                second_proof
+            ));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER), 90);
+
+            let message_id = H256::repeat_byte(1);
+            let message = BridgeModule::messages(message_id);
+            assert_eq!(message.status, Status::Confirmed);
+            assert_eq!(message.eth_address, H160::from(SENDER));
+            assert_eq!(BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID), 0);
+
+            assert_noop!(
+                LightClient::mint_with_proof(Origin::signed(USER), hashes[1], 0, 0, first_proof),
+                "This transfer already exists"
+            );
+        })
+    }
+
+    #[test]
+    fn proven_mint_fee_should_go_to_treasury() {
+        new_test_ext().execute_with(|| {
+            let deposit = receipt(1, &[relay_message_log(1, USER, 60)]);
+            let (root, proof, _) = receipts_trie(&deposit, &receipt(1, &[]));
+            let hashes = import_chain(root, 3);
+            assert_ok!(BridgeModule::set_fee(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID,
+                0,
+                1000
+            ));
+
+            assert_ok!(LightClient::mint_with_proof(
+                Origin::signed(USER),
+                hashes[1],
+                0,
+                0,
+                proof
+            ));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER), 54);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, TreasuryAccount::get()), 6);
+            assert_eq!(BridgeModule::fee_pot(TOKEN_ID), 0);
+            assert_eq!(BridgeModule::transfer_fee(H256::repeat_byte(1)), 0);
+        })
+    }
+
+    #[test]
+    fn mint_with_proof_should_fail() {
+        new_test_ext().execute_with(|| {
+            let failed = receipt(0, &[relay_message_log(1, USER, 60)]);
+            let too_big = receipt(1, &[relay_message_log(2, USER, 101)]);
+            let (root, failed_proof, too_big_proof) = receipts_trie(&failed, &too_big);
+            let hashes = import_chain(root, 3);
+
+            let register_chain = |contract: H160| {
+                BridgeModule::register_chain(
+                    system::RawOrigin::Root.into(),
+                    CHAIN_ID,
+                    contract,
+                    vec![TOKEN_ID],
+                    vec![],
+                    0,
+                )
+            };
+            assert_ok!(register_chain(H160::repeat_byte(1)));
+            assert_noop!(
+                LightClient::mint_with_proof(
+                    Origin::signed(USER),
+                    hashes[1],
+                    1,
+                    0,
+                    too_big_proof.clone()
+                ),
+                "Log is not from the bridge contract"
+            );
+            assert_ok!(register_chain(H160::from(CONTRACT)));
+            assert_noop!(
+                LightClient::mint_with_proof(Origin::signed(USER), hashes[1], 0, 0, failed_proof),
+                "Transaction has failed"
+            );
+            assert_noop!(
+                LightClient::mint_with_proof(
+                    Origin::signed(USER),
+                    hashes[1],
+                    1,
+                    1,
+                    too_big_proof.clone()
+                ),
+                "Log does not exist"
+            );
+            // bridge limits are applied to proven mints too
+            assert_noop!(
+                LightClient::mint_with_proof(Origin::signed(USER), hashes[1], 1, 0, too_big_proof),
+                "Invalid amount for transaction. Reached maximum limit."
+            );
+        })
+    }
+
+    #[test]
+    fn mint_with_proof_should_pass_compliance() {
+        new_test_ext().execute_with(|| {
+            let large = receipt(1, &[relay_message_log(1, USER, 60)]);
+            let small = receipt(1, &[relay_message_log(2, USER, 30)]);
+            let (root, large_proof, small_proof) = receipts_trie(&large, &small);
+            let hashes = import_chain(root, 3);
+
+            assert_ok!(BridgeModule::set_account_list(
+                system::RawOrigin::Root.into(),
+                USER,
+                Some(AccountList::Denied)
+            ));
+            assert_noop!(
+                LightClient::mint_with_proof(
+                    Origin::signed(USER),
+                    hashes[1],
+                    0,
+                    0,
+                    large_proof.clone()
+                ),
+                "Account is denied by compliance"
+            );
+
+            assert_ok!(BridgeModule::set_account_list(
+                system::RawOrigin::Root.into(),
+                USER,
+                None
+            ));
+            assert_ok!(BridgeModule::set_review_threshold(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID,
+                Some(50)
+            ));
+            assert_ok!(LightClient::mint_with_proof(
+                Origin::signed(USER),
+                hashes[1],
+                0,
+                0,
+                large_proof
+            ));
+            let message_id = H256::repeat_byte(1);
+            assert_eq!(BridgeModule::messages(message_id).status, Status::Held);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER), 0);
+
+            assert_ok!(LightClient::mint_with_proof(
+                Origin::signed(USER),
+                hashes[1],
+                1,
+                0,
+                small_proof
+            ));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER), 30);
+
+            // held deposit is minted once released, no votes are needed
+            assert_ok!(BridgeModule::release_transfer(
+                system::RawOrigin::Root.into(),
+                message_id
+            ));
+            assert_eq!(BridgeModule::messages(message_id).status, Status::Confirmed);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER), 90);
+            assert_eq!(BridgeModule::pending_mint_count(CHAIN_ID, TOKEN_ID), 0);
+        })
+    }
+}
@@ -58,13 +58,17 @@ pub use constants::{currency::*, time::*};
 pub mod types;
 pub use types::*;
 
+pub mod api;
 pub mod bridge;
 mod dao;
+mod eth_light_client;
 mod marketplace;
 mod token;
+pub mod token_payment;
 pub use bridge::Call as BridgeCall;
+pub use token_payment::ChargeTransactionPaymentInToken;
 
-mod price_oracle;
+pub mod price_oracle;
 
 /// Alias to 512-bit hash when used in the context of a transaction signature on the chain.
 pub type Signature = MultiSignature;
@@ -108,8 +112,8 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("akropolisos-node"),
     impl_name: create_runtime_str!("akropolisos-node"),
     authoring_version: 2,
-    spec_version: 2,
-    impl_version: 2,
+    spec_version: 3,
+    impl_version: 3,
     apis: RUNTIME_API_VERSIONS,
 };
 
@@ -140,6 +144,20 @@ impl OnUnbalanced<NegativeImbalance> for DealWithFees {
     }
 }
 
+pub struct TreasuryAccount;
+impl Get<AccountId> for TreasuryAccount {
+    fn get() -> AccountId {
+        Treasury::account_id()
+    }
+}
+
+pub struct BlockAuthor;
+impl Get<AccountId> for BlockAuthor {
+    fn get() -> AccountId {
+        Authorship::author()
+    }
+}
+
 parameter_types! {
     pub const BlockHashCount: BlockNumber = 250;
     pub const MaximumBlockWeight: Weight = 1_000_000_000;
@@ -600,7 +618,7 @@ impl system::offchain::CreateTransaction<Runtime, UncheckedExtrinsic> for Runtim
             system::CheckEra::<Runtime>::from(generic::Era::mortal(period, current_block)),
             system::CheckNonce::<Runtime>::from(index),
             system::CheckWeight::<Runtime>::new(),
-            pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+            ChargeTransactionPaymentInToken::<Runtime>::from(tip),
             Default::default(),
         );
         let raw_payload = SignedPayload::new(call, extra)
@@ -670,8 +688,48 @@ impl pallet_vesting::Trait for Runtime {
     type MinVestedTransfer = MinVestedTransfer;
 }
 
+parameter_types! {
+    pub const BridgeTransferTtl: BlockNumber = 7 * DAYS;
+    pub const BridgeLimitWindow: BlockNumber = DAYS;
+    pub const BridgeValidatorBond: Balance = 1_000 * DOLLARS;
+}
+
 impl bridge::Trait for Runtime {
     type Event = Event;
+    type TransferTtl = BridgeTransferTtl;
+    type LimitWindow = BridgeLimitWindow;
+    type ValidatorBond = BridgeValidatorBond;
+    type Slash = Treasury; // send the slashed bonds to the treasury.
+    type TreasuryAccount = TreasuryAccount;
+    /// Two thirds of the technical committee can override bridge validators.
+    type GovernanceOrigin =
+        pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, TechnicalCollective>;
+    /// Held transfers and account lists are managed by the council majority.
+    type ComplianceOrigin =
+        pallet_collective::EnsureProportionMoreThan<_1, _2, AccountId, CouncilCollective>;
+    type SubmitTransaction = SubmitBridgeTransaction;
+}
+
+/// Bridge offchain worker votes are signed by the local bridge key
+type SubmitBridgeTransaction =
+    system::offchain::TransactionSubmitter<bridge::crypto::Public, Runtime, UncheckedExtrinsic>;
+
+impl bridge::SubmitSignedCall<Runtime> for SubmitBridgeTransaction {
+    fn submit_signed(call: bridge::Call<Runtime>) -> bool {
+        use system::offchain::SubmitSignedTransaction;
+        <Self as SubmitSignedTransaction<Runtime, Call>>::submit_signed(Call::Bridge(call))
+            .iter()
+            .any(|(_, result)| result.is_ok())
+    }
+}
+
+parameter_types! {
+    pub const EthConfirmationDepth: u64 = 30;
+}
+
+impl eth_light_client::Trait for Runtime {
+    type Event = Event;
+    type ConfirmationDepth = EthConfirmationDepth;
 }
 
 impl dao::Trait for Runtime {
@@ -684,6 +742,25 @@ impl marketplace::Trait for Runtime {
 
 impl token::Trait for Runtime {
     type Event = Event;
+    type Signature = Signature;
+    type Signer = <Signature as Verify>::Signer;
+    /// A straight majority of the council can take token balance snapshots.
+    type SnapshotOrigin =
+        pallet_collective::EnsureProportionAtLeast<_1, _2, AccountId, CouncilCollective>;
+}
+
+parameter_types! {
+    pub const NativeSymbol: &'static [u8] = b"AKRO";
+    pub const NativeDecimals: u16 = 18;
+    pub const MaxTokenPriceAge: Moment = HOURS as Moment * MILLISECS_PER_BLOCK;
+}
+
+impl token_payment::Trait for Runtime {
+    type NativeSymbol = NativeSymbol;
+    type NativeDecimals = NativeDecimals;
+    // same as for native fees, 80% to treasury, 20% to author
+    type OnTokenFee = token_payment::SplitTokenFee<Runtime, TreasuryAccount, BlockAuthor>;
+    type MaxPriceAge = MaxTokenPriceAge;
 }
 
 /// We need to define the Transaction signer for that using the Key definition
@@ -704,6 +781,9 @@ impl price_oracle::Trait for Runtime {
     type SubmitUnsignedTransaction = SubmitPricefetchTransaction;
     type BlockFetchPeriod = BlockFetchPeriod;
     type GracePeriod = GracePeriod;
+    /// Two thirds of the technical committee can change price sources.
+    type SourceOrigin =
+        pallet_collective::EnsureProportionAtLeast<_2, _3, AccountId, TechnicalCollective>;
 }
 
 construct_runtime!(
@@ -743,9 +823,10 @@ construct_runtime!(
 		// Akropolis pallets
 		Token: token::{Module, Call, Storage, Config, Event<T>},
         Bridge: bridge::{Module, Call, Storage, Config<T>, Event<T>},
+		EthLightClient: eth_light_client::{Module, Call, Storage, Config<T>, Event<T>},
 		Dao: dao::{Module, Call, Storage, Config, Event<T>},
 		Marketplace: marketplace::{Module, Call, Storage, Event<T>},
-		PriceOracle: price_oracle::{Module, Call, Storage, Event<T>, ValidateUnsigned},
+		PriceOracle: price_oracle::{Module, Call, Storage, Event<T>, Config, ValidateUnsigned},
 	}
 );
 
@@ -766,7 +847,7 @@ pub type SignedExtra = (
     system::CheckEra<Runtime>,
     system::CheckNonce<Runtime>,
     system::CheckWeight<Runtime>,
-    pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+    ChargeTransactionPaymentInToken<Runtime>,
     pallet_contracts::CheckBlockGasLimit<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
@@ -919,6 +1000,91 @@ impl_runtime_apis! {
         }
     }
 
+    impl api::TokenApi<Block, AccountId, Balance> for Runtime {
+        fn tokens() -> Vec<types::Token> {
+            Token::tokens()
+        }
+
+        fn balance(token_id: TokenId, account: AccountId) -> Balance {
+            Token::balance_of(token_id, account)
+        }
+
+        fn free_balance(token_id: TokenId, account: AccountId) -> Balance {
+            Token::free_balance_of(token_id, &account)
+        }
+
+        fn locked_balance(token_id: TokenId, account: AccountId) -> Balance {
+            Token::locked(token_id, account)
+        }
+
+        fn allowance(token_id: TokenId, owner: AccountId, spender: AccountId) -> Balance {
+            Token::allowance_of(token_id, (owner, spender))
+        }
+
+        fn total_supply(token_id: TokenId) -> Balance {
+            Token::total_supply(token_id)
+        }
+
+        fn holders(token_id: TokenId) -> Vec<(AccountId, Balance)> {
+            Token::holders(token_id)
+        }
+
+        fn balance_at(
+            token_id: TokenId,
+            account: AccountId,
+            snapshot_id: SnapshotId,
+        ) -> Option<Balance> {
+            Token::balance_of_at(token_id, &account, snapshot_id).ok()
+        }
+
+        fn total_supply_at(token_id: TokenId, snapshot_id: SnapshotId) -> Option<Balance> {
+            Token::total_supply_at(token_id, snapshot_id).ok()
+        }
+    }
+
+    impl api::BridgeApi<Block, AccountId, Hash, Balance, BlockNumber> for Runtime {
+        fn attestations(message_id: Hash) -> Vec<types::Attestation<AccountId>> {
+            Bridge::attestations(message_id)
+        }
+
+        fn transfer(
+            message_id: Hash,
+        ) -> Option<types::TransferInfo<AccountId, Hash, Balance, BlockNumber>> {
+            Bridge::transfer_info(message_id)
+        }
+
+        fn pending_transfers(
+            token_id: TokenId,
+        ) -> Vec<types::TransferMessage<AccountId, Hash, Balance>> {
+            Bridge::pending_transfers(token_id)
+        }
+
+        fn account_pending_transfers(
+            account: AccountId,
+        ) -> Vec<types::TransferMessage<AccountId, Hash, Balance>> {
+            Bridge::account_pending_transfers(&account)
+        }
+
+        fn limits(chain_id: types::ChainId, token_id: TokenId) -> types::Limits<Balance> {
+            Bridge::current_limits(chain_id, token_id)
+        }
+
+        fn pending_volumes(chain_id: types::ChainId, token_id: TokenId) -> (Balance, Balance) {
+            (
+                Bridge::pending_burn_count(chain_id, token_id),
+                Bridge::pending_mint_count(chain_id, token_id),
+            )
+        }
+
+        fn remaining_allowance(
+            chain_id: types::ChainId,
+            token_id: TokenId,
+            account: AccountId,
+        ) -> Balance {
+            Bridge::remaining_allowance(chain_id, token_id, &account)
+        }
+    }
+
     impl sp_session::SessionKeys<Block> for Runtime {
         fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
             SessionKeys::generate(seed)
@@ -1040,5 +1206,6 @@ mod tests {
 
         is_submit_signed_transaction::<SubmitTransaction>();
         is_sign_and_submit_transaction::<SubmitTransaction>();
+        is_submit_signed_transaction::<SubmitBridgeTransaction>();
     }
 }
@@ -10,8 +10,11 @@
 /// https://github.com/paritytech/substrate/blob/master/frame/example-offchain-worker/src/lib.rs
 ///
 use codec::Encode;
-use frame_support::{ weights::SimpleDispatchInfo,
-    debug, decl_event, decl_module, decl_storage, dispatch, traits::Get, IterableStorageMap,
+use frame_support::{
+    debug, decl_event, decl_module, decl_storage, dispatch, ensure,
+    traits::{EnsureOrigin, Get},
+    weights::SimpleDispatchInfo,
+    IterableStorageMap, StorageMap, StorageValue,
 };
 #[cfg(not(feature = "std"))]
 #[allow(unused)]
@@ -21,7 +24,7 @@ use sp_core::crypto::KeyTypeId;
 use sp_io::{self, misc::print_utf8 as print_bytes};
 use sp_runtime::{
     offchain::http,
-    traits::{SaturatedConversion, Zero},
+    traits::{SaturatedConversion, Saturating, Zero},
     transaction_validity::{InvalidTransaction, TransactionValidity, ValidTransaction},
 };
 
@@ -30,6 +33,8 @@ use sp_std::prelude::*;
 use system::ensure_none;
 use system::offchain::SubmitUnsignedTransaction;
 
+use crate::types::{PriceSource, SourceId, SourceKind};
+
 type Result<T> = core::result::Result<T, &'static str>;
 
 /// Our local KeyType.
@@ -49,24 +54,44 @@ pub mod crypto {
     app_crypto!(sr25519, KEY_TYPE);
 }
 
-pub const FETCHED_CRYPTOS: [(&[u8], &[u8], &[u8]); 4] = [
-    (b"DAI", b"coincap", b"https://api.coincap.io/v2/assets/dai"),
-    (
+/// Sources fetched before the registry was introduced, they are registered at genesis
+/// and by the storage migration.
+pub fn default_sources() -> Vec<PriceSource> {
+    let source = |symbol: &[u8], kind, url: &[u8]| PriceSource {
+        symbol: symbol.to_vec(),
+        kind,
+        url: url.to_vec(),
+        weight: 1,
+        enabled: true,
+    };
+    let mut sources = Vec::new();
+    sources.push(source(
+        b"DAI",
+        SourceKind::Coincap,
+        b"https://api.coincap.io/v2/assets/dai",
+    ));
+    sources.push(source(
         b"USDT",
-        b"cryptocompare",
+        SourceKind::Cryptocompare,
         b"https://min-api.cryptocompare.com/data/price?fsym=USDT&tsyms=USD",
-    ),
-    (
+    ));
+    sources.push(source(
         b"USDC",
-        b"cryptocompare",
+        SourceKind::Cryptocompare,
         b"https://min-api.cryptocompare.com/data/price?fsym=USDC&tsyms=USD",
-    ),
-    (
+    ));
+    sources.push(source(
         b"cDAI",
-        b"coingecko",
+        SourceKind::Coingecko,
         b"https://api.coingecko.com/api/v3/simple/price?ids=cDAI&vs_currencies=USD",
-    ),
-];
+    ));
+    sources.push(source(
+        b"AKRO",
+        SourceKind::Coingecko,
+        b"https://api.coingecko.com/api/v3/simple/price?ids=akropolis&vs_currencies=USD",
+    ));
+    sources
+}
 
 /// The module's configuration trait.
 pub trait Trait: timestamp::Trait + balances::Trait + system::Trait {
@@ -85,6 +110,9 @@ pub trait Trait: timestamp::Trait + balances::Trait + system::Trait {
     // Wait period between automated fetches. Set to 0 disable this feature.
     //   Then you need to manucally kickoff pricefetch
     type BlockFetchPeriod: Get<Self::BlockNumber>;
+
+    /// Origin which can add, change and remove price sources.
+    type SourceOrigin: EnsureOrigin<Self::Origin>;
 }
 
 decl_event!(
@@ -93,8 +121,12 @@ decl_event!(
         Moment = <T as timestamp::Trait>::Moment,
         Balance = <T as balances::Trait>::Balance,
     {
-        FetchedPrice(Vec<u8>, Vec<u8>, Moment, Balance),
+        FetchedPrice(Vec<u8>, SourceId, Moment, Balance),
         AggregatedPrice(Vec<u8>, Moment, Balance),
+        SourceAdded(SourceId, Vec<u8>),
+        SourceUpdated(SourceId),
+        SourceEnabled(SourceId, bool),
+        SourceRemoved(SourceId),
     }
 );
 
@@ -105,12 +137,29 @@ decl_storage! {
     //   price has been inflated by 10,000, and in USD.
     //   When used, it should be divided by 10,000.
     // Using linked map for easy traversal from offchain worker or UI
+    // Each price is kept with the weight of its source
     pub TokenPriceHistory get(fn token_price_history):
-    map hasher(blake2_128_concat) Vec<u8> => Vec<T::Balance>;
+    map hasher(blake2_128_concat) Vec<u8> => Vec<(T::Balance, u32)>;
 
     // storage about aggregated price points (calculated with our logic)
     pub AggregatedPrices get(fn aggregated_prices):
     map hasher(blake2_128_concat) Vec<u8> => (T::Moment, T::Balance);
+
+    // sources fetched by the offchain worker, ids of removed sources are not reused
+    pub SourcesCount get(fn sources_count) build(|config: &GenesisConfig| {
+        config.sources.len() as SourceId
+    }): SourceId;
+    pub Sources get(fn sources) build(|config: &GenesisConfig| {
+        config.sources.clone().into_iter().enumerate()
+        .map(|(i, s): (usize, PriceSource)| (i as SourceId, s)).collect::<Vec<_>>()
+    }): map hasher(twox_64_concat) SourceId => Option<PriceSource>;
+
+    // 0: sources are hardcoded, prices are kept without weights
+    // 1: sources registry
+    StorageVersion get(fn storage_version) build(|_| 1u32): u32;
+  }
+  add_extra_genesis {
+    config(sources): Vec<PriceSource>;
   }
 }
 
@@ -122,16 +171,71 @@ decl_module! {
     // this is needed only if you are using events in your module
     fn deposit_event() = default;
 
+    fn on_runtime_upgrade() {
+      if Self::storage_version() == 0 {
+        Self::migrate_to_sources();
+        StorageVersion::put(1);
+      }
+    }
+
+    #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+    pub fn add_source(origin, source: PriceSource) -> dispatch::DispatchResult {
+      T::SourceOrigin::ensure_origin(origin)?;
+      Self::check_source(&source)?;
+
+      let id = Self::sources_count();
+      let next = id.checked_add(1).ok_or("Overflow adding a new price source")?;
+      let symbol = source.symbol.clone();
+      <Sources>::insert(id, source);
+      SourcesCount::put(next);
+      Self::deposit_event(RawEvent::SourceAdded(id, symbol));
+      Ok(())
+    }
+
+    // replace the dead API or change the weight of the source
+    #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+    pub fn update_source(origin, id: SourceId, source: PriceSource) -> dispatch::DispatchResult {
+      T::SourceOrigin::ensure_origin(origin)?;
+      ensure!(<Sources>::contains_key(id), "Price source doesn't exist");
+      Self::check_source(&source)?;
+
+      <Sources>::insert(id, source);
+      Self::deposit_event(RawEvent::SourceUpdated(id));
+      Ok(())
+    }
+
+    #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+    pub fn set_source_enabled(origin, id: SourceId, enabled: bool) -> dispatch::DispatchResult {
+      T::SourceOrigin::ensure_origin(origin)?;
+      let mut source = Self::sources(id).ok_or("Price source doesn't exist")?;
+
+      source.enabled = enabled;
+      <Sources>::insert(id, source);
+      Self::deposit_event(RawEvent::SourceEnabled(id, enabled));
+      Ok(())
+    }
+
+    #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+    pub fn remove_source(origin, id: SourceId) -> dispatch::DispatchResult {
+      T::SourceOrigin::ensure_origin(origin)?;
+      ensure!(<Sources>::contains_key(id), "Price source doesn't exist");
+
+      <Sources>::remove(id);
+      Self::deposit_event(RawEvent::SourceRemoved(id));
+      Ok(())
+    }
+
     #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
     pub fn record_price_unsigned(
         origin,
         _block_number: T::BlockNumber,
-        crypto_info: (Vec<u8>, Vec<u8>, Vec<u8>),
+        source_id: SourceId,
         price: T::Balance
     ) -> dispatch::DispatchResult {
         ensure_none(origin)?;
 
-        let (symbol, remote_src) = (crypto_info.0, crypto_info.1);
+        // prices fetched before the source was disabled or removed are dropped
+        let source = Self::enabled_source(source_id).ok_or("Price source is not enabled")?;
         let now = <timestamp::Module<T>>::get();
 
     //     //DEBUG
@@ -141,10 +245,10 @@ decl_module! {
     //     price
     // );
 
-    <TokenPriceHistory<T>>::mutate(&symbol, |prices| prices.push(price));
+    <TokenPriceHistory<T>>::mutate(&source.symbol, |prices| prices.push((price, source.weight)));
 
       // Spit out an event and Add to storage
-      Self::deposit_event(RawEvent::FetchedPrice(symbol, remote_src, now, price));
+      Self::deposit_event(RawEvent::FetchedPrice(source.symbol, source_id, now, price));
 
       Ok(())
     }
@@ -174,7 +278,7 @@ decl_module! {
         old_vec
     }else{
         let preserve_from_index = &old_vec.len().checked_sub(TOKENS_TO_KEEP).unwrap_or(9usize);
-        old_vec.drain(preserve_from_index..).collect::<Vec<T::Balance>>()
+        old_vec.drain(preserve_from_index..).collect::<Vec<(T::Balance, u32)>>()
     };
     <TokenPriceHistory<T>>::insert(&symbol, new_vec);
 
@@ -189,14 +293,11 @@ decl_module! {
 
       // Type I task: fetch price
       if duration > 0.into() && block % duration == 0.into() {
-        for (symbol, remote_src, remote_url) in FETCHED_CRYPTOS.iter() {
-          let res = Self::fetch_price_unsigned(block, *symbol, *remote_src, *remote_url);
+        for (id, source) in <Sources>::iter().filter(|(_, source)| source.enabled) {
+          let res = Self::fetch_price_unsigned(block, id, &source);
 
           if let Err(e) = res {
-            debug::error!("Error fetching: {:?}, {:?}: {:?}",
-            core::str::from_utf8(symbol).unwrap(),
-            core::str::from_utf8(remote_src).unwrap(),
-            e);
+            debug::error!("Error fetching price source {}: {:?}", id, e);
           }
         }
       }
@@ -251,36 +352,45 @@ impl<T: Trait> Module<T> {
         Ok(json_val)
     }
 
-    fn fetch_price_unsigned<'a>(
+    fn check_source(source: &PriceSource) -> Result<()> {
+        ensure!(!source.symbol.is_empty(), "Price source symbol is empty");
+        ensure!(!source.url.is_empty(), "Price source url is empty");
+        ensure!(source.weight > 0, "Price source weight should be positive");
+        Ok(())
+    }
+
+    fn enabled_source(id: SourceId) -> Option<PriceSource> {
+        Self::sources(id).filter(|source| source.enabled)
+    }
+
+    fn migrate_to_sources() {
+        for (id, source) in default_sources().into_iter().enumerate() {
+            <Sources>::insert(id as SourceId, source);
+        }
+        SourcesCount::put(default_sources().len() as SourceId);
+        // prices kept without weights were fetched from one source each
+        <TokenPriceHistory<T>>::translate(|_, prices: Vec<T::Balance>| {
+            Some(prices.into_iter().map(|price| (price, 1)).collect())
+        });
+    }
+
+    fn fetch_price_unsigned(
         block: T::BlockNumber,
-        symbol: &'a [u8],
-        remote_src: &'a [u8],
-        remote_url: &'a [u8],
+        id: SourceId,
+        source: &PriceSource,
     ) -> Result<()> {
-        // //DEBUG
-        // debug::info!(
-        //     "fetch price unsigned: {:?}:{:?}",
-        //     core::str::from_utf8(symbol).unwrap(),
-        //     core::str::from_utf8(remote_src).unwrap()
-        // );
-
-        let json = Self::fetch_json(remote_url)?;
-        let price = match remote_src {
-            src if src == b"coingecko" => Self::fetch_price_from_coingecko(json)
+        let json = Self::fetch_json(&source.url)?;
+        let price = match source.kind {
+            SourceKind::Coingecko => Self::fetch_price_from_coingecko(json)
                 .map_err(|_| "fetch_price_from_coingecko error"),
-            src if src == b"coincap" => {
+            SourceKind::Coincap => {
                 Self::fetch_price_from_coincap(json).map_err(|_| "fetch_price_from_coincap error")
             }
-            src if src == b"cryptocompare" => Self::fetch_price_from_cryptocompare(json)
+            SourceKind::Cryptocompare => Self::fetch_price_from_cryptocompare(json)
                 .map_err(|_| "fetch_price_from_cryptocompare error"),
-            _ => Err("Unknown remote source"),
         }?;
 
-        let call = Call::record_price_unsigned(
-            block,
-            (symbol.to_vec(), remote_src.to_vec(), remote_url.to_vec()),
-            price,
-        );
+        let call = Call::record_price_unsigned(block, id, price);
 
         T::SubmitUnsignedTransaction::submit_unsigned(call)
             .map_err(|_| "fetch_price: submit_unsigned(call) error")?;
@@ -338,15 +448,28 @@ impl<T: Trait> Module<T> {
         Ok(Self::round_value(val_f64))
     }
 
+    /// Average of the prices weighted by their sources, None if there are no prices.
+    pub fn weighted_price(prices: &[(T::Balance, u32)]) -> Option<T::Balance> {
+        let (price_sum, weight_sum) = prices.iter().fold(
+            (T::Balance::zero(), 0u32),
+            |(price_sum, weight_sum), (price, weight)| {
+                (
+                    price_sum.saturating_add(price.saturating_mul(T::Balance::from(*weight))),
+                    weight_sum.saturating_add(*weight),
+                )
+            },
+        );
+        if weight_sum == 0 {
+            return None;
+        }
+        // Avoiding floating-point arithmetic & do integer division
+        Some(price_sum / T::Balance::from(weight_sum))
+    }
+
     fn aggregate_price_points_unsigned<'a>(block: T::BlockNumber, symbol: &'a [u8]) -> Result<()> {
         let token_pricepoints_vec = <TokenPriceHistory<T>>::get(symbol);
-        let price_sum: T::Balance = token_pricepoints_vec
-            .iter()
-            .fold(T::Balance::zero(), |mem, price| mem + *price);
-
-        // Avoiding floating-point arithmetic & do integer division
-        let price_avg: T::Balance =
-            price_sum / T::Balance::from(token_pricepoints_vec.len() as u32);
+        let price_avg = Self::weighted_price(&token_pricepoints_vec)
+            .ok_or("No weighted prices to aggregate")?;
 
         let call = Call::record_aggregated_price_points_unsigned(block, symbol.to_vec(), price_avg);
 
@@ -366,7 +489,12 @@ impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
         // debug::info!("Calling {:?}", call);
 
         match call {
-            Call::record_price_unsigned(block, (symbol, ..), price) => Ok(ValidTransaction {
+            Call::record_price_unsigned(_, source_id, _)
+                if Self::enabled_source(*source_id).is_none() =>
+            {
+                InvalidTransaction::Call.into()
+            }
+            Call::record_price_unsigned(block, source_id, price) => Ok(ValidTransaction {
                 // We set base priority to 2**20 to make sure it's included before any other
                 // transactions in the pool. Next we tweak the priority depending on how much
                 // it differs from the current average. (the more it differs the more priority it
@@ -378,7 +506,7 @@ impl<T: Trait> frame_support::unsigned::ValidateUnsigned for Module<T> {
                 requires: vec![],
                 // We can still have multiple transactions compete for the same "spot",
                 // and the one with higher priority will replace other one in the pool.
-                provides: vec![(block, symbol, price).encode()],
+                provides: vec![(block, source_id, price).encode()],
                 // The transaction is only valid for next 5 blocks. After that it's
                 // going to be revalidated by the pool.
                 longevity: 5,
@@ -412,12 +540,15 @@ pub mod tests {
     //  3. with multiple record_price of same symbol inserted. On next cycle, the average of the price is calculated
     //  4. can fetch for BTC, parse the JSON blob and get a price > 0 out
     use super::*;
-    use frame_support::{impl_outer_dispatch, impl_outer_origin, parameter_types, weights::Weight};
+    use frame_support::{
+        assert_noop, assert_ok, impl_outer_dispatch, impl_outer_origin, parameter_types,
+        weights::Weight,
+    };
     use sp_core::H256;
     use sp_runtime::{
         testing::{Header, TestXt},
         traits::{BlakeTwo256, IdentityLookup},
-        Perbill,
+        DispatchError, Perbill,
     };
     use std::cell::RefCell;
 
@@ -513,6 +644,7 @@ pub mod tests {
         //   Then you need to manucally kickoff pricefetch
         type GracePeriod = GracePeriod;
         type BlockFetchPeriod = BlockFetchPeriod;
+        type SourceOrigin = system::EnsureRoot<u64>;
     }
 
     // This function basically just builds a genesis storage key/value store according to
@@ -530,4 +662,111 @@ pub mod tests {
             assert_eq!(1, 1);
         });
     }
+    fn source(symbol: &[u8], weight: u32) -> PriceSource {
+        PriceSource {
+            symbol: symbol.to_vec(),
+            kind: SourceKind::Coingecko,
+            url: b"https://api.coingecko.com/api/v3/simple/price".to_vec(),
+            weight,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn sources_should_be_managed_by_source_origin() {
+        new_test_ext().execute_with(|| {
+            let root = || -> Origin { system::RawOrigin::Root.into() };
+            assert_noop!(
+                PriceOracleModule::add_source(Origin::signed(1), source(b"DAI", 1)),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                PriceOracleModule::add_source(root(), source(b"DAI", 0)),
+                "Price source weight should be positive"
+            );
+
+            assert_ok!(PriceOracleModule::add_source(root(), source(b"DAI", 1)));
+            assert_ok!(PriceOracleModule::add_source(root(), source(b"USDC", 1)));
+            assert_eq!(PriceOracleModule::sources_count(), 2);
+
+            assert_ok!(PriceOracleModule::update_source(
+                root(),
+                1,
+                source(b"USDC", 2)
+            ));
+            assert_eq!(PriceOracleModule::sources(1).unwrap().weight, 2);
+            assert_ok!(PriceOracleModule::set_source_enabled(root(), 1, false));
+            assert_eq!(PriceOracleModule::sources(1).unwrap().enabled, false);
+
+            // ids of removed sources are not reused
+            assert_ok!(PriceOracleModule::remove_source(root(), 0));
+            assert_eq!(PriceOracleModule::sources(0), None);
+            assert_noop!(
+                PriceOracleModule::update_source(root(), 0, source(b"DAI", 1)),
+                "Price source doesn't exist"
+            );
+            assert_ok!(PriceOracleModule::add_source(root(), source(b"DAI", 1)));
+            assert_eq!(PriceOracleModule::sources(2), Some(source(b"DAI", 1)));
+        });
+    }
+
+    #[test]
+    fn prices_should_be_weighted_by_sources() {
+        new_test_ext().execute_with(|| {
+            let root = || -> Origin { system::RawOrigin::Root.into() };
+            let none = || -> Origin { system::RawOrigin::None.into() };
+            assert_ok!(PriceOracleModule::add_source(root(), source(b"DAI", 1)));
+            assert_ok!(PriceOracleModule::add_source(root(), source(b"DAI", 3)));
+            assert_ok!(PriceOracleModule::add_source(root(), source(b"DAI", 1)));
+            assert_ok!(PriceOracleModule::set_source_enabled(root(), 2, false));
+
+            assert_ok!(PriceOracleModule::record_price_unsigned(none(), 1, 0, 100));
+            assert_ok!(PriceOracleModule::record_price_unsigned(none(), 1, 1, 200));
+            assert_noop!(
+                PriceOracleModule::record_price_unsigned(none(), 1, 2, 1000),
+                "Price source is not enabled"
+            );
+
+            let prices = PriceOracleModule::token_price_history(b"DAI".to_vec());
+            assert_eq!(prices, vec![(100, 1), (200, 3)]);
+            assert_eq!(PriceOracleModule::weighted_price(&prices), Some(175));
+            assert_eq!(PriceOracleModule::weighted_price(&[]), None);
+        });
+    }
+
+    #[test]
+    fn migration_should_keep_price_history() {
+        use frame_support::{
+            storage::migration::put_storage_value, traits::OnRuntimeUpgrade, Blake2_128Concat,
+            StorageHasher,
+        };
+
+        new_test_ext().execute_with(|| {
+            // prices had no weights before
+            let key = Blake2_128Concat::hash(&b"DAI".to_vec().encode());
+            put_storage_value(
+                b"PriceOracle",
+                b"TokenPriceHistory",
+                &key,
+                vec![100u128, 200],
+            );
+            StorageVersion::put(0);
+
+            PriceOracleModule::on_runtime_upgrade();
+
+            assert_eq!(PriceOracleModule::storage_version(), 1);
+            let sources = default_sources();
+            assert_eq!(
+                PriceOracleModule::sources_count(),
+                sources.len() as SourceId
+            );
+            for (id, source) in sources.into_iter().enumerate() {
+                assert_eq!(PriceOracleModule::sources(id as SourceId), Some(source));
+            }
+            assert_eq!(
+                PriceOracleModule::token_price_history(b"DAI".to_vec()),
+                vec![(100, 1), (200, 1)]
+            );
+        });
+    }
 }
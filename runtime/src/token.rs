@@ -2,33 +2,53 @@
 /// You can use mint to create tokens or burn created tokens
 /// and transfer tokens on substrate side freely or operate with total_supply
 ///
-use crate::types::{Token, TokenId};
+use crate::types::{SnapshotId, Token, TokenId};
+use codec::{Decode, Encode};
 use frame_support::{
-    decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure,
-    weights::SimpleDispatchInfo, StorageMap,
+    decl_event, decl_module, decl_storage,
+    dispatch::DispatchResult,
+    ensure,
+    storage::migration::{get_storage_value, take_storage_value},
+    traits::EnsureOrigin,
+    weights::{DispatchClass, FunctionOf, SimpleDispatchInfo, Weight},
+    IterableStorageDoubleMap, Parameter, StorageDoubleMap, StorageMap, StorageValue,
 };
 use num_traits::ops::checked::{CheckedAdd, CheckedSub};
-use sp_runtime::traits::{StaticLookup, Zero};
+use sp_io::hashing::{blake2_256, twox_128};
+use sp_runtime::traits::{IdentifyAccount, Saturating, StaticLookup, Verify, Zero};
 use sp_std::prelude::Vec;
-use system::{self, ensure_signed};
+use system::{self, ensure_root, ensure_signed};
 
 type Result<T> = core::result::Result<T, &'static str>;
 
+// system accounts moved out of the opaque maps at the end of each block
+const MIGRATED_ACCOUNTS_PER_BLOCK: usize = 100;
+const MIGRATION_ENTRY_WEIGHT: Weight = 10_000;
+
 decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as system::Trait>::AccountId,
         Balance = <T as balances::Trait>::Balance,
+        BlockNumber = <T as system::Trait>::BlockNumber,
     {
         Transfer(AccountId, AccountId, Balance),
         Approval(AccountId, AccountId, Balance),
+        ApprovalExpiry(AccountId, AccountId, BlockNumber),
+        TransferFrom(AccountId, AccountId, AccountId, Balance),
         Mint(AccountId, Balance),
         Burn(AccountId, Balance),
+        Snapshot(TokenId, SnapshotId),
     }
 );
 
 pub trait Trait: balances::Trait + system::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+    /// Signature of the off-chain approvals submitted through `permit`
+    type Signature: Parameter + Verify<Signer = Self::Signer>;
+    type Signer: IdentifyAccount<AccountId = Self::AccountId>;
+    /// Origin allowed to take balance snapshots
+    type SnapshotOrigin: EnsureOrigin<Self::Origin>;
 }
 
 decl_storage! {
@@ -36,7 +56,8 @@ decl_storage! {
         pub Count get(fn count) build(|config: &GenesisConfig| {
             config.tokens.clone().len() as u32
         }): TokenId;
-        pub Locked get(fn locked): map hasher(opaque_blake2_256) (TokenId, T::AccountId) => T::Balance;
+        pub Locked get(fn locked):
+            double_map hasher(blake2_128_concat) TokenId, hasher(blake2_128_concat) T::AccountId => T::Balance;
 
         pub Tokens get(fn tokens) build(|config: &GenesisConfig| {
             config.tokens.clone()
@@ -53,8 +74,28 @@ decl_storage! {
             .map(|(i, t): (usize, Token)| (i as u32, t.symbol)).collect::<Vec<_>>()
         }): map hasher(opaque_blake2_256) TokenId => Vec<u8>;
         pub TotalSupply get(fn total_supply): map hasher(opaque_blake2_256) TokenId => T::Balance;
-        pub Balance get(fn balance_of): map hasher(opaque_blake2_256) (TokenId, T::AccountId) => T::Balance;
-        pub Allowance get(fn allowance_of): map hasher(opaque_blake2_256) (TokenId, T::AccountId, T::AccountId) => T::Balance;
+        pub Balance get(fn balance_of):
+            double_map hasher(blake2_128_concat) TokenId, hasher(blake2_128_concat) T::AccountId => T::Balance;
+        // keyed by token and (owner, spender)
+        pub Allowance get(fn allowance_of):
+            double_map hasher(blake2_128_concat) TokenId, hasher(blake2_128_concat) (T::AccountId, T::AccountId) => T::Balance;
+        pub AllowanceExpiry get(fn allowance_expiry):
+            double_map hasher(blake2_128_concat) TokenId, hasher(blake2_128_concat) (T::AccountId, T::AccountId) => Option<T::BlockNumber>;
+        pub PermitNonce get(fn permit_nonce): map hasher(opaque_blake2_256) T::AccountId => u64;
+        // snapshot ids start from 1, 0 means no snapshots were taken
+        pub CurrentSnapshot get(fn current_snapshot): map hasher(blake2_128_concat) TokenId => SnapshotId;
+        // values before the first change after each snapshot, ordered by snapshot id
+        pub BalanceSnapshots get(fn balance_snapshots):
+            double_map hasher(blake2_128_concat) TokenId, hasher(blake2_128_concat) T::AccountId => Vec<(SnapshotId, T::Balance)>;
+        pub TotalSupplySnapshots get(fn total_supply_snapshots):
+            map hasher(blake2_128_concat) TokenId => Vec<(SnapshotId, T::Balance)>;
+
+        // 0: balances, locks and allowances in opaque maps keyed by tuples
+        // 1: iterable double maps keyed by TokenId first
+        StorageVersion get(fn storage_version) build(|_| 1u32): u32;
+        // raw key of the last system account migrated to version 1,
+        // removed when all accounts are migrated
+        MigrationCursor get(fn migration_cursor): Option<Vec<u8>>;
     }
     add_extra_genesis{
         config(tokens): Vec<Token>;
@@ -65,6 +106,19 @@ decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() {
+            if Self::storage_version() == 0 {
+                MigrationCursor::put(Self::system_accounts_prefix());
+                StorageVersion::put(1);
+            }
+        }
+
+        fn on_finalize() {
+            if let Some(cursor) = Self::migration_cursor() {
+                Self::migrate_next_accounts(cursor);
+            }
+        }
+
         // ( ! ): can be called directly
         // ( ? ): do we even need this?
         #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
@@ -72,7 +126,7 @@ decl_module! {
             ensure_signed(origin)?;
             let token = <TokenMap>::get(token_id);
             Self::check_token_exist(&token.symbol)?;
-            Self::_burn(0, from.clone(), amount)?;
+            Self::_burn(token_id, from.clone(), amount)?;
             Self::deposit_event(RawEvent::Burn(from, amount));
             Ok(())
         }
@@ -110,6 +164,39 @@ decl_module! {
 
         #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
         fn approve(origin,
+            spender: <T::Lookup as StaticLookup>::Source,
+            token_id: TokenId,
+            #[compact] value: T::Balance,
+            expiry: Option<T::BlockNumber>
+        ) -> DispatchResult{
+            let sender = ensure_signed(origin)?;
+            let spender = T::Lookup::lookup(spender)?;
+
+            Self::set_allowance(token_id, sender, spender, value, expiry)?;
+            Ok(())
+        }
+
+        // keeps the expiry of the allowance, `approve` sets a new one
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        fn increase_allowance(origin,
+            spender: <T::Lookup as StaticLookup>::Source,
+            token_id: TokenId,
+            #[compact] value: T::Balance
+        ) -> DispatchResult{
+            let sender = ensure_signed(origin)?;
+            let spender = T::Lookup::lookup(spender)?;
+
+            let allowance = Self::allowance_of(token_id, (sender.clone(), spender.clone()))
+                .checked_add(&value)
+                .ok_or("Overflow in calculating allowance")?;
+
+            <Allowance<T>>::insert(token_id, (sender.clone(), spender.clone()), allowance);
+            Self::deposit_event(RawEvent::Approval(sender, spender, allowance));
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        fn decrease_allowance(origin,
             spender: <T::Lookup as StaticLookup>::Source,
             token_id: TokenId,
             #[compact] value: T::Balance
@@ -117,9 +204,34 @@ decl_module! {
             let sender = ensure_signed(origin)?;
             let spender = T::Lookup::lookup(spender)?;
 
-            <Allowance<T>>::insert((token_id, sender.clone(), spender.clone()), value);
+            let allowance = Self::allowance_of(token_id, (sender.clone(), spender.clone()))
+                .checked_sub(&value)
+                .ok_or("Underflow in calculating allowance")?;
 
-            Self::deposit_event(RawEvent::Approval(sender, spender, value));
+            <Allowance<T>>::insert(token_id, (sender.clone(), spender.clone()), allowance);
+            Self::deposit_event(RawEvent::Approval(sender, spender, allowance));
+            Ok(())
+        }
+
+        // approval signed off-chain by the owner, can be submitted by anyone
+        #[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+        fn permit(origin,
+            owner: T::AccountId,
+            spender: T::AccountId,
+            token_id: TokenId,
+            #[compact] value: T::Balance,
+            expiry: Option<T::BlockNumber>,
+            nonce: u64,
+            signature: T::Signature
+        ) -> DispatchResult{
+            ensure_signed(origin)?;
+            ensure!(Self::permit_nonce(&owner) == nonce, "Invalid permit nonce");
+
+            let payload = Self::permit_payload(token_id, &owner, &spender, value, expiry, nonce);
+            ensure!(signature.verify(&payload[..], &owner), "Invalid permit signature");
+
+            Self::set_allowance(token_id, owner.clone(), spender, value, expiry)?;
+            <PermitNonce<T>>::insert(owner, nonce + 1);
             Ok(())
         }
 
@@ -131,14 +243,53 @@ decl_module! {
             #[compact] value: T::Balance
         ) -> DispatchResult{
             let sender = ensure_signed(origin)?;
-            let allowance = Self::allowance_of((token_id, from.clone(), sender.clone()));
+            let key = (from.clone(), sender.clone());
+            if let Some(expiry) = Self::allowance_expiry(token_id, &key) {
+                ensure!(<system::Module<T>>::block_number() <= expiry, "Allowance expired");
+            }
+            let allowance = Self::allowance_of(token_id, &key);
 
             let updated_allowance = allowance.checked_sub(&value).ok_or("Underflow in calculating allowance")?;
 
 
             Self::make_transfer(token_id, from.clone(), to.clone(), value)?;
 
-            <Allowance<T>>::insert((token_id, from, sender), updated_allowance);
+            <Allowance<T>>::insert(token_id, key, updated_allowance);
+            Self::deposit_event(RawEvent::TransferFrom(sender, from, to, value));
+            Ok(())
+        }
+
+        #[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+        fn snapshot(origin, token_id: TokenId) -> DispatchResult {
+            T::SnapshotOrigin::ensure_origin(origin)?;
+            ensure!(<TokenMap>::contains_key(token_id), "Token is not registered");
+
+            let snapshot_id = Self::current_snapshot(token_id)
+                .checked_add(1)
+                .ok_or("Overflow in snapshot id")?;
+            <CurrentSnapshot>::insert(token_id, snapshot_id);
+
+            Self::deposit_event(RawEvent::Snapshot(token_id, snapshot_id));
+            Ok(())
+        }
+
+        // moves entries the block migration can't find: balances of holders
+        // without a system account and allowances, whose keys are hashed
+        #[weight = FunctionOf(
+            |args: (&Vec<T::AccountId>, &Vec<(T::AccountId, T::AccountId)>)| {
+                MIGRATION_ENTRY_WEIGHT * ((args.0.len() + args.1.len()).max(1) as Weight)
+            },
+            DispatchClass::Operational,
+            true
+        )]
+        fn migrate_accounts(
+            origin,
+            accounts: Vec<T::AccountId>,
+            allowances: Vec<(T::AccountId, T::AccountId)>
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::migrate_opaque_balances(&accounts);
+            Self::migrate_opaque_allowances(&allowances);
             Ok(())
         }
 
@@ -146,28 +297,246 @@ decl_module! {
 }
 
 impl<T: Trait> Module<T> {
+    /// Message the owner signs to permit spending of his tokens,
+    /// the genesis hash keeps it from being replayed on another network
+    pub fn permit_payload(
+        token_id: TokenId,
+        owner: &T::AccountId,
+        spender: &T::AccountId,
+        value: T::Balance,
+        expiry: Option<T::BlockNumber>,
+        nonce: u64,
+    ) -> Vec<u8> {
+        let genesis = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+        (
+            b"permit", genesis, token_id, owner, spender, value, expiry, nonce,
+        )
+            .encode()
+    }
+
+    /// All accounts with non-zero balance of the token
+    pub fn holders(token_id: TokenId) -> Vec<(T::AccountId, T::Balance)> {
+        <Balance<T>>::iter(token_id)
+            .filter(|(_, balance)| !balance.is_zero())
+            .collect()
+    }
+
+    /// Balance of the account at the moment `snapshot_id` was taken
+    pub fn balance_of_at(
+        token_id: TokenId,
+        account: &T::AccountId,
+        snapshot_id: SnapshotId,
+    ) -> Result<T::Balance> {
+        Self::check_snapshot(token_id, snapshot_id)?;
+        let snapshots = Self::balance_snapshots(token_id, account);
+        Ok(Self::value_at(&snapshots, snapshot_id)
+            .unwrap_or_else(|| Self::balance_of(token_id, account)))
+    }
+
+    /// Total supply of the token at the moment `snapshot_id` was taken
+    pub fn total_supply_at(token_id: TokenId, snapshot_id: SnapshotId) -> Result<T::Balance> {
+        Self::check_snapshot(token_id, snapshot_id)?;
+        let snapshots = Self::total_supply_snapshots(token_id);
+        Ok(Self::value_at(&snapshots, snapshot_id).unwrap_or_else(|| Self::total_supply(token_id)))
+    }
+
+    fn check_snapshot(token_id: TokenId, snapshot_id: SnapshotId) -> Result<()> {
+        ensure!(
+            snapshot_id > 0 && snapshot_id <= Self::current_snapshot(token_id),
+            "Snapshot does not exist"
+        );
+        Ok(())
+    }
+
+    // value recorded on the first change after the snapshot,
+    // None if the value hasn't changed since then
+    fn value_at(
+        snapshots: &[(SnapshotId, T::Balance)],
+        snapshot_id: SnapshotId,
+    ) -> Option<T::Balance> {
+        let index = match snapshots.binary_search_by_key(&snapshot_id, |(id, _)| *id) {
+            Ok(index) | Err(index) => index,
+        };
+        snapshots.get(index).map(|(_, value)| *value)
+    }
+
+    // records the value before it's changed for the first time since the last snapshot
+    fn record_snapshot(
+        snapshots: &mut Vec<(SnapshotId, T::Balance)>,
+        current: SnapshotId,
+        value: T::Balance,
+    ) {
+        if snapshots.last().map_or(true, |(id, _)| *id < current) {
+            snapshots.push((current, value));
+        }
+    }
+
+    fn update_balance_snapshot(token_id: TokenId, account: &T::AccountId) {
+        let current = Self::current_snapshot(token_id);
+        if current == 0 {
+            return;
+        }
+        let balance = Self::balance_of(token_id, account);
+        <BalanceSnapshots<T>>::mutate(token_id, account, |snapshots| {
+            Self::record_snapshot(snapshots, current, balance)
+        });
+    }
+
+    fn update_total_supply_snapshot(token_id: TokenId) {
+        let current = Self::current_snapshot(token_id);
+        if current == 0 {
+            return;
+        }
+        let total_supply = Self::total_supply(token_id);
+        <TotalSupplySnapshots<T>>::mutate(token_id, |snapshots| {
+            Self::record_snapshot(snapshots, current, total_supply)
+        });
+    }
+
+    /// Moves balances and locks of `accounts` from the opaque maps
+    /// keyed by `(TokenId, AccountId)` tuples into the double maps.
+    /// Tokens the account got in the double maps before its migration are kept.
+    fn migrate_opaque_balances(accounts: &[T::AccountId]) {
+        for token_id in Self::tokens().into_iter().map(|t| t.id) {
+            for owner in accounts {
+                let key = blake2_256(&(token_id, owner).encode());
+                let balance = Self::balance_of(token_id, owner);
+                if let Some(merged) = Self::take_merged(b"Balance", &key, balance) {
+                    <Balance<T>>::insert(token_id, owner, merged);
+                }
+                let locked = Self::locked(token_id, owner);
+                if let Some(merged) = Self::take_merged(b"Locked", &key, locked) {
+                    <Locked<T>>::insert(token_id, owner, merged);
+                }
+            }
+        }
+    }
+
+    // the old value is left in place if the sum overflows
+    fn take_merged(item: &[u8], key: &[u8], current: T::Balance) -> Option<T::Balance> {
+        let old = get_storage_value::<T::Balance>(b"TokenStorage", item, key)?;
+        let merged = current.checked_add(&old)?;
+        take_storage_value::<T::Balance>(b"TokenStorage", item, key);
+        Some(merged)
+    }
+
+    /// Moves allowances of `(owner, spender)` pairs from the opaque maps
+    /// keyed by `(TokenId, AccountId, AccountId)` tuples into the double maps.
+    /// Allowances the owner set in the double maps before the migration replace the old ones.
+    fn migrate_opaque_allowances(pairs: &[(T::AccountId, T::AccountId)]) {
+        for token_id in Self::tokens().into_iter().map(|t| t.id) {
+            for pair in pairs {
+                let key = blake2_256(&(token_id, &pair.0, &pair.1).encode());
+                let allowance =
+                    take_storage_value::<T::Balance>(b"TokenStorage", b"Allowance", &key);
+                let expiry =
+                    take_storage_value::<T::BlockNumber>(b"TokenStorage", b"AllowanceExpiry", &key);
+                if <Allowance<T>>::contains_key(token_id, pair) {
+                    continue;
+                }
+                if let Some(allowance) = allowance {
+                    <Allowance<T>>::insert(token_id, pair, allowance);
+                }
+                if let Some(expiry) = expiry {
+                    <AllowanceExpiry<T>>::insert(token_id, pair, expiry);
+                }
+            }
+        }
+    }
+
+    fn system_accounts_prefix() -> Vec<u8> {
+        let mut prefix = twox_128(b"System").to_vec();
+        prefix.extend_from_slice(&twox_128(b"Account"));
+        prefix
+    }
+
+    /// Migrates balances of up to `MIGRATED_ACCOUNTS_PER_BLOCK` system accounts
+    /// following the `cursor` key
+    fn migrate_next_accounts(mut cursor: Vec<u8>) {
+        let prefix = Self::system_accounts_prefix();
+        let mut accounts = Vec::new();
+        let mut finished = false;
+        while accounts.len() < MIGRATED_ACCOUNTS_PER_BLOCK {
+            match sp_io::storage::next_key(&cursor).filter(|key| key.starts_with(&prefix)) {
+                Some(key) => {
+                    // blake2_128_concat keeps the encoded account after the 16 byte hash
+                    if let Some(account) = key
+                        .get(prefix.len() + 16..)
+                        .and_then(|mut encoded| T::AccountId::decode(&mut encoded).ok())
+                    {
+                        accounts.push(account);
+                    }
+                    cursor = key;
+                }
+                None => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+
+        Self::migrate_opaque_balances(&accounts);
+        if finished {
+            MigrationCursor::kill();
+        } else {
+            MigrationCursor::put(cursor);
+        }
+    }
+
+    fn set_allowance(
+        token_id: TokenId,
+        owner: T::AccountId,
+        spender: T::AccountId,
+        value: T::Balance,
+        expiry: Option<T::BlockNumber>,
+    ) -> Result<()> {
+        let key = (owner.clone(), spender.clone());
+        match expiry {
+            Some(block) => {
+                ensure!(
+                    block >= <system::Module<T>>::block_number(),
+                    "Allowance expiry is in the past"
+                );
+                <AllowanceExpiry<T>>::insert(token_id, &key, block);
+                Self::deposit_event(RawEvent::ApprovalExpiry(
+                    owner.clone(),
+                    spender.clone(),
+                    block,
+                ));
+            }
+            None => <AllowanceExpiry<T>>::remove(token_id, &key),
+        }
+        <Allowance<T>>::insert(token_id, key, value);
+
+        Self::deposit_event(RawEvent::Approval(owner, spender, value));
+        Ok(())
+    }
+
     pub fn _burn(token_id: TokenId, from: T::AccountId, amount: T::Balance) -> Result<()> {
         ensure!(
-            Self::total_supply(0) >= amount,
+            Self::total_supply(token_id) >= amount,
             "Cannot burn more than total supply"
         );
 
-        let free_balance = <Balance<T>>::get((token_id, from.clone()))
-            - <Locked<T>>::get((token_id, from.clone()));
+        let free_balance =
+            <Balance<T>>::get(token_id, &from).saturating_sub(<Locked<T>>::get(token_id, &from));
         ensure!(
             free_balance > T::Balance::zero(),
             "Cannot burn with zero balance"
         );
         ensure!(free_balance >= amount, "Not enough because of locked funds");
 
-        let next_balance = free_balance
+        // locked funds stay on the balance
+        let next_balance = <Balance<T>>::get(token_id, &from)
             .checked_sub(&amount)
             .ok_or("Underflow subtracting from balance burn")?;
-        let next_total = Self::total_supply(0)
+        let next_total = Self::total_supply(token_id)
             .checked_sub(&amount)
             .ok_or("Underflow subtracting from total supply")?;
 
-        <Balance<T>>::insert((token_id, from.clone()), next_balance);
+        Self::update_balance_snapshot(token_id, &from);
+        Self::update_total_supply_snapshot(token_id);
+        <Balance<T>>::insert(token_id, &from, next_balance);
         <TotalSupply<T>>::insert(token_id, next_total);
 
         Ok(())
@@ -175,45 +544,57 @@ impl<T: Trait> Module<T> {
     pub fn _mint(token_id: TokenId, to: T::AccountId, amount: T::Balance) -> Result<()> {
         ensure!(!amount.is_zero(), "Amount should be non-zero");
 
-        let old_balance = <Balance<T>>::get((token_id, to.clone()));
+        let old_balance = <Balance<T>>::get(token_id, &to);
         let next_balance = old_balance
             .checked_add(&amount)
             .ok_or("Overflow adding to balance")?;
-        let next_total = Self::total_supply(0)
+        let next_total = Self::total_supply(token_id)
             .checked_add(&amount)
             .ok_or("Overflow adding to total supply")?;
 
-        <Balance<T>>::insert((token_id, to.clone()), next_balance);
+        Self::update_balance_snapshot(token_id, &to);
+        Self::update_total_supply_snapshot(token_id);
+        <Balance<T>>::insert(token_id, &to, next_balance);
         <TotalSupply<T>>::insert(token_id, next_total);
 
         Ok(())
     }
 
-    fn make_transfer(
+    pub fn make_transfer(
         token_id: TokenId,
         from: T::AccountId,
         to: T::AccountId,
         amount: T::Balance,
     ) -> Result<()> {
-        let from_balance = <Balance<T>>::get((token_id, from.clone()));
+        let from_balance = <Balance<T>>::get(token_id, &from);
         ensure!(from_balance >= amount, "User does not have enough tokens");
-        let free_balance = <Balance<T>>::get((token_id, from.clone()))
-            - <Locked<T>>::get((token_id, from.clone()));
+        let free_balance =
+            <Balance<T>>::get(token_id, &from).saturating_sub(<Locked<T>>::get(token_id, &from));
         ensure!(free_balance >= amount, "Not enough because of locked funds");
 
-        <Balance<T>>::insert((token_id, from.clone()), from_balance - amount);
-        <Balance<T>>::mutate((token_id, to.clone()), |balance| *balance += amount);
+        Self::update_balance_snapshot(token_id, &from);
+        Self::update_balance_snapshot(token_id, &to);
+        <Balance<T>>::insert(token_id, &from, from_balance - amount);
+        <Balance<T>>::mutate(token_id, &to, |balance| *balance += amount);
 
         Self::deposit_event(RawEvent::Transfer(from, to, amount));
 
         Ok(())
     }
+    pub fn free_balance_of(token_id: TokenId, account: &T::AccountId) -> T::Balance {
+        <Balance<T>>::get(token_id, account)
+            .checked_sub(&<Locked<T>>::get(token_id, account))
+            .unwrap_or_else(T::Balance::zero)
+    }
     pub fn lock(token_id: TokenId, account: T::AccountId, amount: T::Balance) -> Result<()> {
         //TODO: substract this amount from the main balance?
         //              Balance: 1000, Locked: 0
         // lock(400) => Balance: 1000, Locked: 400 or
         // lock(400) => Balance: 600, Locked: 400
-        <Locked<T>>::insert((token_id, account.clone()), amount);
+        let locked = <Locked<T>>::get(token_id, &account)
+            .checked_add(&amount)
+            .ok_or("Overflow while locking")?;
+        <Locked<T>>::insert(token_id, account, locked);
 
         Ok(())
     }
@@ -222,15 +603,15 @@ impl<T: Trait> Module<T> {
         //                Balance: 1000, Locked: 400
         // unlock(400) => Balance: 1000, Locked: 0 or
         // unlock(400) => Balance: 1400, Locked: 0
-        let balance = <Locked<T>>::get((token_id, account.clone()));
+        let balance = <Locked<T>>::get(token_id, account);
         let new_balance = balance
             .checked_sub(&amount)
-            .expect("Underflow while unlocking. Check if user has enough locked funds.");
+            .ok_or("Underflow while unlocking, not enough locked funds")?;
         let zero = T::Balance::zero();
 
         match new_balance {
-            b if b == zero => <Locked<T>>::remove((token_id, account.clone())),
-            _ => <Locked<T>>::insert((token_id, account.clone()), new_balance),
+            b if b == zero => <Locked<T>>::remove(token_id, account),
+            _ => <Locked<T>>::insert(token_id, account, new_balance),
         }
         Ok(())
     }
@@ -265,9 +646,9 @@ mod tests {
     };
     use sp_core::H256;
     use sp_runtime::{
-        testing::Header,
+        testing::{Header, TestSignature, UintAuthorityId},
         traits::{BlakeTwo256, IdentityLookup},
-        Perbill,
+        DispatchError, Perbill,
     };
     use std::cell::RefCell;
 
@@ -343,15 +724,20 @@ mod tests {
     }
     impl Trait for Test {
         type Event = ();
+        type Signature = TestSignature;
+        type Signer = UintAuthorityId;
+        type SnapshotOrigin = system::EnsureRoot<u64>;
     }
 
     type TokenModule = Module<Test>;
+    type System = system::Module<Test>;
 
     const TOKEN_NAME: &[u8; 4] = b"DOOM";
     const TOKEN_SHORT_NAME: &[u8; 1] = b"T";
     const TOKEN_LONG_NAME: &[u8; 34] = b"nobody_really_want_such_long_token";
     const USER1: u64 = 1;
     const USER2: u64 = 2;
+    const USER3: u64 = 3;
     const TOKEN_ID: u32 = 0;
 
     pub struct ExtBuilder {
@@ -401,7 +787,7 @@ mod tests {
                 &TokenModule::token_map(TOKEN_ID).symbol
             ));
             assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 1000);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 1000);
             assert_eq!(TokenModule::total_supply(TOKEN_ID), 1000);
         })
     }
@@ -413,10 +799,10 @@ mod tests {
                 &TokenModule::token_map(TOKEN_ID).symbol
             ));
             assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 1000);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 1000);
 
             assert_ok!(TokenModule::_burn(TOKEN_ID, USER2, 1000));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 0);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 0);
         })
     }
 
@@ -425,15 +811,15 @@ mod tests {
         ExtBuilder::default().build().execute_with(|| {
             assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
 
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 1000);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 1000);
             assert_ok!(TokenModule::transfer(
                 Origin::signed(USER2),
                 USER1,
                 TOKEN_ID,
                 300
             ));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 700);
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER1)), 300);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 700);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER1), 300);
         })
     }
     #[test]
@@ -441,9 +827,9 @@ mod tests {
         ExtBuilder::default().build().execute_with(|| {
             assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
 
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 1000);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 1000);
             assert_ok!(TokenModule::lock(TOKEN_ID, USER2, 400));
-            assert_eq!(TokenModule::locked((TOKEN_ID, USER2)), 400);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), 400);
         })
     }
 
@@ -452,11 +838,44 @@ mod tests {
         ExtBuilder::default().build().execute_with(|| {
             assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
 
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 1000);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 1000);
+            assert_ok!(TokenModule::lock(TOKEN_ID, USER2, 400));
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), 400);
+            assert_ok!(TokenModule::unlock(TOKEN_ID, &USER2, 400));
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), 0);
+        })
+    }
+
+    #[test]
+    fn burn_should_keep_locked_funds() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
+            assert_ok!(TokenModule::lock(TOKEN_ID, USER2, 400));
+
+            assert_ok!(TokenModule::_burn(TOKEN_ID, USER2, 100));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 900);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), 400);
+            assert_noop!(
+                TokenModule::_burn(TOKEN_ID, USER2, 600),
+                "Not enough because of locked funds"
+            );
+        })
+    }
+
+    #[test]
+    fn token_locks_should_add_up() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
+
             assert_ok!(TokenModule::lock(TOKEN_ID, USER2, 400));
-            assert_eq!(TokenModule::locked((TOKEN_ID, USER2)), 400);
+            assert_ok!(TokenModule::lock(TOKEN_ID, USER2, 100));
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), 500);
+            assert_noop!(
+                TokenModule::unlock(TOKEN_ID, &USER2, 600),
+                "Underflow while unlocking, not enough locked funds"
+            );
             assert_ok!(TokenModule::unlock(TOKEN_ID, &USER2, 400));
-            assert_eq!(TokenModule::locked((TOKEN_ID, USER2)), 0);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), 100);
         })
     }
 
@@ -465,16 +884,16 @@ mod tests {
         ExtBuilder::default().build().execute_with(|| {
             assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
 
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 1000);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 1000);
             assert_ok!(TokenModule::transfer(
                 Origin::signed(USER2),
                 USER1,
                 TOKEN_ID,
                 300
             ));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 700);
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER1)), 300);
-            assert_eq!(TokenModule::locked((TOKEN_ID, USER2)), 0);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 700);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER1), 300);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER2), 0);
             assert_noop!(
                 TokenModule::transfer(Origin::signed(USER2), USER1, TOKEN_ID, 1300),
                 "User does not have enough tokens"
@@ -485,20 +904,20 @@ mod tests {
     fn token_transfer_burn_works() {
         ExtBuilder::default().build().execute_with(|| {
             assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 1000);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 1000);
 
             assert_ok!(TokenModule::_burn(TOKEN_ID, USER2, 300));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 700);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 700);
         })
     }
     #[test]
     fn token_transfer_burn_all_works() {
         ExtBuilder::default().build().execute_with(|| {
             assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 1000));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 1000);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 1000);
 
             assert_ok!(TokenModule::_burn(TOKEN_ID, USER2, 1000));
-            assert_eq!(TokenModule::balance_of((TOKEN_ID, USER2)), 0);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER2), 0);
         })
     }
 
@@ -515,4 +934,372 @@ mod tests {
             );
         })
     }
+
+    #[test]
+    fn transfer_from_works() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER1, 1000));
+            assert_ok!(TokenModule::approve(
+                Origin::signed(USER1),
+                USER2,
+                TOKEN_ID,
+                500,
+                None
+            ));
+            assert_ok!(TokenModule::transfer_from(
+                Origin::signed(USER2),
+                USER1,
+                USER3,
+                TOKEN_ID,
+                300
+            ));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER1), 700);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER3), 300);
+            assert_eq!(TokenModule::allowance_of(TOKEN_ID, (USER1, USER2)), 200);
+            assert_noop!(
+                TokenModule::transfer_from(Origin::signed(USER2), USER1, USER3, TOKEN_ID, 300),
+                "Underflow in calculating allowance"
+            );
+        })
+    }
+
+    #[test]
+    fn increase_and_decrease_allowance_works() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(TokenModule::increase_allowance(
+                Origin::signed(USER1),
+                USER2,
+                TOKEN_ID,
+                500
+            ));
+            assert_ok!(TokenModule::increase_allowance(
+                Origin::signed(USER1),
+                USER2,
+                TOKEN_ID,
+                200
+            ));
+            assert_eq!(TokenModule::allowance_of(TOKEN_ID, (USER1, USER2)), 700);
+            assert_ok!(TokenModule::decrease_allowance(
+                Origin::signed(USER1),
+                USER2,
+                TOKEN_ID,
+                600
+            ));
+            assert_eq!(TokenModule::allowance_of(TOKEN_ID, (USER1, USER2)), 100);
+            assert_noop!(
+                TokenModule::decrease_allowance(Origin::signed(USER1), USER2, TOKEN_ID, 101),
+                "Underflow in calculating allowance"
+            );
+        })
+    }
+
+    #[test]
+    fn expired_allowance_should_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER1, 1000));
+            System::set_block_number(5);
+            assert_noop!(
+                TokenModule::approve(Origin::signed(USER1), USER2, TOKEN_ID, 500, Some(4)),
+                "Allowance expiry is in the past"
+            );
+            assert_ok!(TokenModule::approve(
+                Origin::signed(USER1),
+                USER2,
+                TOKEN_ID,
+                500,
+                Some(10)
+            ));
+            assert_ok!(TokenModule::transfer_from(
+                Origin::signed(USER2),
+                USER1,
+                USER3,
+                TOKEN_ID,
+                100
+            ));
+
+            System::set_block_number(11);
+            assert_noop!(
+                TokenModule::transfer_from(Origin::signed(USER2), USER1, USER3, TOKEN_ID, 100),
+                "Allowance expired"
+            );
+        })
+    }
+
+    #[test]
+    fn permit_works() {
+        ExtBuilder::default().build().execute_with(|| {
+            let payload = TokenModule::permit_payload(TOKEN_ID, &USER1, &USER2, 500, None, 0);
+            let signature = TestSignature(USER1, payload);
+
+            // relayer submits the approval on behalf of the owner
+            assert_ok!(TokenModule::permit(
+                Origin::signed(USER3),
+                USER1,
+                USER2,
+                TOKEN_ID,
+                500,
+                None,
+                0,
+                signature.clone()
+            ));
+            assert_eq!(TokenModule::allowance_of(TOKEN_ID, (USER1, USER2)), 500);
+            assert_eq!(TokenModule::permit_nonce(USER1), 1);
+
+            // replay of the same permit
+            assert_noop!(
+                TokenModule::permit(
+                    Origin::signed(USER3),
+                    USER1,
+                    USER2,
+                    TOKEN_ID,
+                    500,
+                    None,
+                    0,
+                    signature
+                ),
+                "Invalid permit nonce"
+            );
+        })
+    }
+
+    #[test]
+    fn permit_of_another_network_should_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            let other_genesis = H256::repeat_byte(1);
+            let payload = (
+                b"permit",
+                other_genesis,
+                TOKEN_ID,
+                USER1,
+                USER2,
+                500u128,
+                None::<u64>,
+                0u64,
+            )
+                .encode();
+
+            assert_noop!(
+                TokenModule::permit(
+                    Origin::signed(USER3),
+                    USER1,
+                    USER2,
+                    TOKEN_ID,
+                    500,
+                    None,
+                    0,
+                    TestSignature(USER1, payload)
+                ),
+                "Invalid permit signature"
+            );
+        })
+    }
+
+    #[test]
+    fn permit_with_wrong_signature_should_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            let payload = TokenModule::permit_payload(TOKEN_ID, &USER1, &USER2, 500, None, 0);
+            // signed by spender instead of owner
+            let signature = TestSignature(USER2, payload);
+
+            assert_eq!(
+                TokenModule::permit(
+                    Origin::signed(USER3),
+                    USER1,
+                    USER2,
+                    TOKEN_ID,
+                    500,
+                    None,
+                    0,
+                    signature
+                ),
+                Err(DispatchError::Other("Invalid permit signature"))
+            );
+            assert_eq!(TokenModule::allowance_of(TOKEN_ID, (USER1, USER2)), 0);
+        })
+    }
+
+    #[test]
+    fn holders_works() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER1, 1000));
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 500));
+            assert_ok!(TokenModule::_burn(TOKEN_ID, USER2, 500));
+
+            assert_eq!(TokenModule::holders(TOKEN_ID), vec![(USER1, 1000)]);
+            assert_eq!(TokenModule::holders(1), vec![]);
+        })
+    }
+
+    #[test]
+    fn opaque_storage_migration_works() {
+        use frame_support::traits::OnFinalize;
+        use frame_support::{storage::migration::put_storage_value, traits::OnRuntimeUpgrade};
+
+        ExtBuilder::default().build().execute_with(|| {
+            let put = |item: &[u8], key: Vec<u8>, value: Balance| {
+                put_storage_value(b"TokenStorage", item, &blake2_256(&key), value)
+            };
+            put(b"Balance", (TOKEN_ID, USER1).encode(), 1000);
+            put(b"Locked", (TOKEN_ID, USER1).encode(), 400);
+            put(b"Allowance", (TOKEN_ID, USER1, USER2).encode(), 300);
+            // USER3 has no system account
+            put(b"Balance", (TOKEN_ID, USER3).encode(), 700);
+            StorageVersion::put(0);
+            system::Account::<Test>::insert(USER1, system::AccountInfo::default());
+
+            TokenModule::on_runtime_upgrade();
+            assert_eq!(TokenModule::storage_version(), 1);
+            assert!(TokenModule::migration_cursor().is_some());
+
+            TokenModule::on_finalize(1);
+            assert_eq!(TokenModule::migration_cursor(), None);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER1), 1000);
+            assert_eq!(TokenModule::locked(TOKEN_ID, USER1), 400);
+            assert_eq!(TokenModule::allowance_of(TOKEN_ID, (USER1, USER2)), 0);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER3), 0);
+
+            assert_noop!(
+                TokenModule::migrate_accounts(Origin::signed(USER1), vec![USER3], vec![]),
+                DispatchError::BadOrigin
+            );
+            assert_ok!(TokenModule::migrate_accounts(
+                system::RawOrigin::Root.into(),
+                vec![USER3],
+                vec![(USER1, USER2)]
+            ));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER3), 700);
+            assert_eq!(TokenModule::allowance_of(TOKEN_ID, (USER1, USER2)), 300);
+            let mut holders = TokenModule::holders(TOKEN_ID);
+            holders.sort();
+            assert_eq!(holders, vec![(USER1, 1000), (USER3, 700)]);
+        })
+    }
+
+    #[test]
+    fn migration_should_keep_new_balances() {
+        use frame_support::traits::OnFinalize;
+        use frame_support::{storage::migration::put_storage_value, traits::OnRuntimeUpgrade};
+
+        ExtBuilder::default().build().execute_with(|| {
+            let put = |item: &[u8], key: Vec<u8>, value: Balance| {
+                put_storage_value(b"TokenStorage", item, &blake2_256(&key), value)
+            };
+            put(b"Balance", (TOKEN_ID, USER1).encode(), 1000);
+            put(b"Allowance", (TOKEN_ID, USER1, USER2).encode(), 300);
+            StorageVersion::put(0);
+            system::Account::<Test>::insert(USER1, system::AccountInfo::default());
+            TokenModule::on_runtime_upgrade();
+
+            // credited and approved in the new layout before the account's turn
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER1, 500));
+            assert_ok!(TokenModule::approve(
+                Origin::signed(USER1),
+                USER2,
+                TOKEN_ID,
+                100,
+                None
+            ));
+            TokenModule::on_finalize(1);
+            assert_eq!(TokenModule::migration_cursor(), None);
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER1), 1500);
+
+            assert_ok!(TokenModule::migrate_accounts(
+                system::RawOrigin::Root.into(),
+                vec![],
+                vec![(USER1, USER2)]
+            ));
+            assert_eq!(TokenModule::allowance_of(TOKEN_ID, (USER1, USER2)), 100);
+        })
+    }
+
+    #[test]
+    fn balance_snapshots_work() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER1, 1000));
+            assert_ok!(TokenModule::snapshot(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID
+            ));
+            assert_eq!(TokenModule::current_snapshot(TOKEN_ID), 1);
+
+            assert_ok!(TokenModule::make_transfer(TOKEN_ID, USER1, USER2, 300));
+            assert_ok!(TokenModule::make_transfer(TOKEN_ID, USER1, USER2, 200));
+            assert_ok!(TokenModule::snapshot(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID
+            ));
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER2, 500));
+            // nothing changed during snapshot 3
+            assert_ok!(TokenModule::snapshot(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID
+            ));
+            assert_ok!(TokenModule::snapshot(
+                system::RawOrigin::Root.into(),
+                TOKEN_ID
+            ));
+            assert_ok!(TokenModule::_burn(TOKEN_ID, USER1, 500));
+
+            assert_eq!(TokenModule::balance_of_at(TOKEN_ID, &USER1, 1), Ok(1000));
+            assert_eq!(TokenModule::balance_of_at(TOKEN_ID, &USER2, 1), Ok(0));
+            assert_eq!(TokenModule::total_supply_at(TOKEN_ID, 1), Ok(1000));
+
+            assert_eq!(TokenModule::balance_of_at(TOKEN_ID, &USER1, 2), Ok(500));
+            assert_eq!(TokenModule::balance_of_at(TOKEN_ID, &USER2, 2), Ok(500));
+            assert_eq!(TokenModule::total_supply_at(TOKEN_ID, 2), Ok(1000));
+
+            assert_eq!(TokenModule::balance_of_at(TOKEN_ID, &USER1, 3), Ok(500));
+            assert_eq!(TokenModule::balance_of_at(TOKEN_ID, &USER2, 3), Ok(1000));
+            assert_eq!(TokenModule::total_supply_at(TOKEN_ID, 3), Ok(1500));
+
+            assert_eq!(TokenModule::balance_of_at(TOKEN_ID, &USER1, 4), Ok(500));
+            assert_eq!(TokenModule::balance_of_at(TOKEN_ID, &USER2, 4), Ok(1000));
+            assert_eq!(TokenModule::total_supply_at(TOKEN_ID, 4), Ok(1500));
+
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER1), 0);
+            assert_eq!(TokenModule::total_supply(TOKEN_ID), 1000);
+        })
+    }
+
+    #[test]
+    fn snapshot_should_fail() {
+        ExtBuilder::default().build().execute_with(|| {
+            assert_noop!(
+                TokenModule::snapshot(Origin::signed(USER1), TOKEN_ID),
+                DispatchError::BadOrigin
+            );
+            assert_noop!(
+                TokenModule::snapshot(system::RawOrigin::Root.into(), 1),
+                "Token is not registered"
+            );
+            assert_eq!(
+                TokenModule::balance_of_at(TOKEN_ID, &USER1, 0),
+                Err("Snapshot does not exist")
+            );
+            assert_eq!(
+                TokenModule::total_supply_at(TOKEN_ID, 1),
+                Err("Snapshot does not exist")
+            );
+        })
+    }
+
+    #[test]
+    fn total_supply_should_be_kept_per_token() {
+        ExtBuilder::default().build().execute_with(|| {
+            let supply = TokenModule::total_supply(TOKEN_ID);
+            assert_ok!(TokenModule::_mint(1, USER2, 300));
+            assert_eq!(TokenModule::total_supply(1), 300);
+            assert_eq!(TokenModule::total_supply(TOKEN_ID), supply);
+
+            assert_ok!(TokenModule::_burn(1, USER2, 100));
+            assert_eq!(TokenModule::total_supply(1), 200);
+            assert_eq!(TokenModule::total_supply(TOKEN_ID), supply);
+            // supply of the other token doesn't count
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER1, 1000));
+            assert_noop!(
+                TokenModule::_burn(1, USER1, 500),
+                "Cannot burn more than total supply"
+            );
+        })
+    }
 }
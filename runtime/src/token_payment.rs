@@ -0,0 +1,445 @@
+/// Signed extension allowing to pay transaction fees in bridged tokens
+///
+/// Works as `pallet_transaction_payment::ChargeTransactionPayment` when no token is chosen.
+/// Otherwise the fee is computed in native currency as usual and converted into
+/// the chosen token through `price_oracle::AggregatedPrices`.
+///
+use crate::price_oracle;
+use crate::token;
+use crate::types::TokenId;
+use codec::{Decode, Encode};
+use frame_support::{
+    ensure,
+    traits::{Currency, Get},
+    weights::DispatchInfo,
+};
+use pallet_transaction_payment::ChargeTransactionPayment;
+use sp_runtime::{
+    traits::{SaturatedConversion, Saturating, SignedExtension, Zero},
+    transaction_validity::{
+        InvalidTransaction, TransactionPriority, TransactionValidity, TransactionValidityError,
+        ValidTransaction,
+    },
+};
+
+type Result<T> = core::result::Result<T, &'static str>;
+
+type NativeBalanceOf<T> = <<T as pallet_transaction_payment::Trait>::Currency as Currency<
+    <T as system::Trait>::AccountId,
+>>::Balance;
+
+pub trait Trait: pallet_transaction_payment::Trait + price_oracle::Trait + token::Trait {
+    /// Symbol of the native currency in `price_oracle::AggregatedPrices`
+    type NativeSymbol: Get<&'static [u8]>;
+    /// Decimals of the native currency
+    type NativeDecimals: Get<u16>;
+    /// Handler for the fees collected in tokens
+    type OnTokenFee: OnTokenFee<Self::AccountId, Self::Balance>;
+    /// Fees aren't converted by prices aggregated earlier than this
+    type MaxPriceAge: Get<Self::Moment>;
+}
+
+/// Takes the fee paid in token from the payer.
+pub trait OnTokenFee<AccountId, Balance> {
+    fn on_token_fee(who: &AccountId, token_id: TokenId, amount: Balance) -> Result<()>;
+}
+
+/// Splits the fee as native fees are: 80% to the treasury and 20% to the block author.
+pub struct SplitTokenFee<T, Treasury, Author>(sp_std::marker::PhantomData<(T, Treasury, Author)>);
+
+impl<T, Treasury, Author> OnTokenFee<T::AccountId, T::Balance>
+    for SplitTokenFee<T, Treasury, Author>
+where
+    T: token::Trait,
+    Treasury: Get<T::AccountId>,
+    Author: Get<T::AccountId>,
+{
+    fn on_token_fee(who: &T::AccountId, token_id: TokenId, amount: T::Balance) -> Result<()> {
+        ensure!(
+            <token::Module<T>>::free_balance_of(token_id, who) >= amount,
+            "Not enough tokens to pay the fee"
+        );
+        let to_author = amount / T::Balance::from(5u32);
+        <token::Module<T>>::make_transfer(
+            token_id,
+            who.clone(),
+            Treasury::get(),
+            amount - to_author,
+        )?;
+        <token::Module<T>>::make_transfer(token_id, who.clone(), Author::get(), to_author)
+    }
+}
+
+/// Require the transactor to pay for themselves and maybe include a tip to gain additional priority
+/// in the queue. Fee is charged in `TokenId` if it's provided or in native currency otherwise.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct ChargeTransactionPaymentInToken<T: Trait + Send + Sync>(
+    #[codec(compact)] NativeBalanceOf<T>,
+    Option<TokenId>,
+);
+
+impl<T: Trait + Send + Sync> ChargeTransactionPaymentInToken<T> {
+    /// utility constructor. Used only in client/factory code.
+    pub fn from(fee: NativeBalanceOf<T>) -> Self {
+        Self(fee, None)
+    }
+
+    /// pay fee with the given token instead of native currency
+    pub fn in_token(fee: NativeBalanceOf<T>, token_id: TokenId) -> Self {
+        Self(fee, Some(token_id))
+    }
+
+    /// native fee converted into amount of `token_id` by aggregated prices
+    pub fn token_fee(native_fee: NativeBalanceOf<T>, token_id: TokenId) -> Result<T::Balance> {
+        let token = <token::Module<T>>::token_map(token_id);
+        ensure!(!token.symbol.is_empty(), "Unknown token");
+
+        let (native_updated, native_price) =
+            <price_oracle::Module<T>>::aggregated_prices(T::NativeSymbol::get().to_vec());
+        let (token_updated, token_price) =
+            <price_oracle::Module<T>>::aggregated_prices(token.symbol);
+        // stale price would let users underpay
+        let now = <timestamp::Module<T>>::get();
+        ensure!(
+            now.saturating_sub(native_updated.min(token_updated)) <= T::MaxPriceAge::get(),
+            "Price is too old to convert fee into token"
+        );
+
+        convert_fee(
+            native_fee.saturated_into::<u128>(),
+            native_price.saturated_into::<u128>(),
+            token_price.saturated_into::<u128>(),
+            T::NativeDecimals::get(),
+            token.decimals,
+        )
+        .map(|fee| fee.saturated_into::<T::Balance>())
+        .ok_or("No price to convert fee into token")
+    }
+}
+
+/// Converts `fee` from native currency into token using USD prices of both.
+/// Prices have the same precision, so only decimals of the amounts are adjusted.
+pub fn convert_fee(
+    fee: u128,
+    native_price: u128,
+    token_price: u128,
+    native_decimals: u16,
+    token_decimals: u16,
+) -> Option<u128> {
+    if native_price.is_zero() || token_price.is_zero() {
+        return None;
+    }
+    let amount = fee.checked_mul(native_price)? / token_price;
+    if token_decimals >= native_decimals {
+        amount.checked_mul(10u128.checked_pow((token_decimals - native_decimals).into())?)
+    } else {
+        Some(amount / 10u128.checked_pow((native_decimals - token_decimals).into())?)
+    }
+}
+
+impl<T: Trait + Send + Sync> sp_std::fmt::Debug for ChargeTransactionPaymentInToken<T> {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "ChargeTransactionPaymentInToken<{:?}, {:?}>", self.0, self.1)
+    }
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, _: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        Ok(())
+    }
+}
+
+impl<T: Trait + Send + Sync> SignedExtension for ChargeTransactionPaymentInToken<T>
+where
+    NativeBalanceOf<T>: Send + Sync,
+{
+    const IDENTIFIER: &'static str = "ChargeTransactionPaymentInToken";
+    type AccountId = T::AccountId;
+    type Call = <T as system::Trait>::Call;
+    type AdditionalSigned = ();
+    type DispatchInfo = DispatchInfo;
+    type Pre = ();
+    fn additional_signed(&self) -> core::result::Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: Self::DispatchInfo,
+        len: usize,
+    ) -> TransactionValidity {
+        let (tip, token_id) = (self.0, self.1);
+        let token_id = match token_id {
+            Some(id) => id,
+            None => return ChargeTransactionPayment::<T>::from(tip).validate(who, call, info, len),
+        };
+
+        let fee = ChargeTransactionPayment::<T>::compute_fee(len as u32, info, tip);
+        if !fee.is_zero() {
+            let amount = Self::token_fee(fee, token_id)
+                .map_err(|_| TransactionValidityError::from(InvalidTransaction::Payment))?;
+            T::OnTokenFee::on_token_fee(who, token_id, amount)
+                .map_err(|_| TransactionValidityError::from(InvalidTransaction::Payment))?;
+        }
+
+        let mut r = ValidTransaction::default();
+        r.priority = fee.saturated_into::<TransactionPriority>();
+        Ok(r)
+    }
+}
+
+/// tests for this module
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Token;
+    use frame_support::{
+        assert_ok, impl_outer_dispatch, impl_outer_origin, parameter_types,
+        weights::{DispatchClass, Weight},
+    };
+    use sp_core::H256;
+    use sp_runtime::{
+        testing::{Header, TestSignature, TestXt, UintAuthorityId},
+        traits::{BlakeTwo256, ConvertInto, IdentityLookup},
+        Perbill,
+    };
+
+    impl_outer_origin! {
+        pub enum Origin for Test {}
+    }
+
+    impl_outer_dispatch! {
+        pub enum Call for Test where origin: Origin {
+            price_oracle::PriceOracleModule,
+        }
+    }
+
+    #[derive(Clone, Eq, PartialEq)]
+    pub struct Test;
+
+    parameter_types! {
+        pub const BlockHashCount: u64 = 250;
+        pub const MaximumBlockWeight: Weight = 1024;
+        pub const MaximumBlockLength: u32 = 2 * 1024;
+        pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+    }
+    impl system::Trait for Test {
+        type Origin = Origin;
+        type Call = ();
+        type Index = u64;
+        type BlockNumber = u64;
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type Event = ();
+        type BlockHashCount = BlockHashCount;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+        type Version = ();
+        type ModuleToIndex = ();
+        type AccountData = balances::AccountData<u128>;
+        type OnNewAccount = ();
+        type OnKilledAccount = ();
+    }
+
+    parameter_types! {
+        pub const ExistentialDeposit: u128 = 1;
+    }
+    impl balances::Trait for Test {
+        type Balance = u128;
+        type DustRemoval = ();
+        type Event = ();
+        type ExistentialDeposit = ExistentialDeposit;
+        type AccountStore = system::Module<Test>;
+    }
+
+    parameter_types! {
+        pub const TransactionBaseFee: u128 = 1000;
+        pub const TransactionByteFee: u128 = 0;
+    }
+    impl pallet_transaction_payment::Trait for Test {
+        type Currency = balances::Module<Test>;
+        type OnTransactionPayment = ();
+        type TransactionBaseFee = TransactionBaseFee;
+        type TransactionByteFee = TransactionByteFee;
+        type WeightToFee = ConvertInto;
+        type FeeMultiplierUpdate = ();
+    }
+
+    parameter_types! {
+        pub const MinimumPeriod: u64 = 5;
+    }
+    impl timestamp::Trait for Test {
+        type Moment = u64;
+        type OnTimestampSet = ();
+        type MinimumPeriod = MinimumPeriod;
+    }
+
+    impl token::Trait for Test {
+        type Event = ();
+        type Signature = TestSignature;
+        type Signer = UintAuthorityId;
+        type SnapshotOrigin = system::EnsureRoot<u64>;
+    }
+
+    type Extrinsic = TestXt<Call, ()>;
+    type SubmitPFTransaction =
+        system::offchain::TransactionSubmitter<price_oracle::crypto::Public, Call, Extrinsic>;
+
+    parameter_types! {
+        pub const BlockFetchPeriod: u64 = 2;
+        pub const GracePeriod: u64 = 5;
+    }
+    impl price_oracle::Trait for Test {
+        type Event = ();
+        type Call = Call;
+        type SubmitUnsignedTransaction = SubmitPFTransaction;
+        type GracePeriod = GracePeriod;
+        type BlockFetchPeriod = BlockFetchPeriod;
+        type SourceOrigin = system::EnsureRoot<u64>;
+    }
+
+    parameter_types! {
+        pub const NativeSymbol: &'static [u8] = b"AKRO";
+        pub const NativeDecimals: u16 = 18;
+        pub const MaxPriceAge: u64 = 60_000;
+        pub const TreasuryAccount: u64 = 100;
+        pub const BlockAuthor: u64 = 200;
+    }
+    impl Trait for Test {
+        type NativeSymbol = NativeSymbol;
+        type NativeDecimals = NativeDecimals;
+        type OnTokenFee = SplitTokenFee<Test, TreasuryAccount, BlockAuthor>;
+        type MaxPriceAge = MaxPriceAge;
+    }
+
+    type PriceOracleModule = price_oracle::Module<Test>;
+    type TokenModule = token::Module<Test>;
+    type Charge = ChargeTransactionPaymentInToken<Test>;
+
+    const PRICE_1_USD: u128 = 1_000_000_000_000_000_000;
+    const USER: u64 = 1;
+    const TOKEN_ID: TokenId = 0;
+    const NOW: u64 = 1_000_000;
+
+    fn new_test_ext() -> sp_io::TestExternalities {
+        let mut storage = system::GenesisConfig::default()
+            .build_storage::<Test>()
+            .unwrap();
+        let _ = token::GenesisConfig {
+            tokens: vec![Token {
+                id: TOKEN_ID,
+                decimals: 18,
+                symbol: Vec::from("DAI"),
+            }],
+        }
+        .assimilate_storage(&mut storage);
+        let mut ext = sp_io::TestExternalities::from(storage);
+        ext.execute_with(|| {
+            timestamp::Module::<Test>::set_timestamp(NOW);
+            set_price(b"AKRO", NOW, PRICE_1_USD);
+            set_price(b"DAI", NOW, PRICE_1_USD / 2);
+        });
+        ext
+    }
+
+    fn set_price(symbol: &[u8], updated: u64, price: u128) {
+        price_oracle::AggregatedPrices::<Test>::insert(symbol.to_vec(), (updated, price));
+    }
+
+    fn info() -> DispatchInfo {
+        DispatchInfo {
+            weight: 0,
+            class: DispatchClass::Normal,
+            pays_fee: true,
+        }
+    }
+
+    #[test]
+    fn fee_should_be_charged_in_token() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER, 10_000));
+
+            // 1000 of native currency costs 2000 of the twice cheaper token
+            let validity = Charge::in_token(0, TOKEN_ID).validate(&USER, &(), info(), 0);
+            assert_eq!(validity.map(|v| v.priority), Ok(1000));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER), 8000);
+            // split as native fees
+            assert_eq!(
+                TokenModule::balance_of(TOKEN_ID, TreasuryAccount::get()),
+                1600
+            );
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, BlockAuthor::get()), 400);
+        })
+    }
+
+    #[test]
+    fn fee_without_tokens_should_fail() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER, 1999));
+            assert_eq!(
+                Charge::in_token(0, TOKEN_ID).validate(&USER, &(), info(), 0),
+                Err(InvalidTransaction::Payment.into())
+            );
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER), 1999);
+        })
+    }
+
+    #[test]
+    fn fee_without_fresh_price_should_fail() {
+        new_test_ext().execute_with(|| {
+            assert_ok!(TokenModule::_mint(TOKEN_ID, USER, 10_000));
+            let charge = Charge::in_token(0, TOKEN_ID);
+
+            set_price(b"DAI", NOW, 0);
+            assert_eq!(
+                charge.validate(&USER, &(), info(), 0),
+                Err(InvalidTransaction::Payment.into())
+            );
+
+            set_price(b"DAI", NOW - MaxPriceAge::get() - 1, PRICE_1_USD / 2);
+            assert_eq!(
+                charge.validate(&USER, &(), info(), 0),
+                Err(InvalidTransaction::Payment.into())
+            );
+
+            set_price(b"DAI", NOW - MaxPriceAge::get(), PRICE_1_USD / 2);
+            assert_ok!(charge.validate(&USER, &(), info(), 0));
+            assert_eq!(TokenModule::balance_of(TOKEN_ID, USER), 8000);
+        })
+    }
+
+    #[test]
+    fn convert_fee_with_equal_prices_works() {
+        assert_eq!(convert_fee(1000, PRICE_1_USD, PRICE_1_USD, 18, 18), Some(1000));
+    }
+
+    #[test]
+    fn convert_fee_by_price_works() {
+        // native is twice cheaper than token
+        assert_eq!(
+            convert_fee(1000, PRICE_1_USD / 2, PRICE_1_USD, 18, 18),
+            Some(500)
+        );
+        assert_eq!(
+            convert_fee(1000, PRICE_1_USD, PRICE_1_USD / 4, 18, 18),
+            Some(4000)
+        );
+    }
+
+    #[test]
+    fn convert_fee_by_decimals_works() {
+        assert_eq!(convert_fee(1000, PRICE_1_USD, PRICE_1_USD, 14, 18), Some(10_000_000));
+        assert_eq!(convert_fee(10_000_000, PRICE_1_USD, PRICE_1_USD, 18, 14), Some(1000));
+    }
+
+    #[test]
+    fn convert_fee_without_price_fails() {
+        assert_eq!(convert_fee(1000, 0, PRICE_1_USD, 18, 18), None);
+        assert_eq!(convert_fee(1000, PRICE_1_USD, 0, 18, 18), None);
+    }
+}
@@ -1,5 +1,5 @@
 use codec::{Decode, Encode};
-use sp_core::H160;
+use sp_core::{ecdsa, H160, H256, U256};
 use sp_std::prelude::Vec;
 
 #[cfg(feature = "std")]
@@ -69,6 +69,9 @@ pub enum Action<AccountId, Balance, Timeout> {
 
 //token factory
 pub type TokenId = u32;
+pub type SnapshotId = u32;
+// id of the native currency in the bridge, never assigned to tokens
+pub const NATIVE_TOKEN_ID: TokenId = TokenId::max_value();
 
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 #[cfg_attr(feature = "std", derive(Deserialize, Serialize, Debug))]
@@ -78,8 +81,31 @@ pub struct Token {
     pub symbol: Vec<u8>,
 }
 
+//price oracle
+pub type SourceId = u32;
+
+// parser of the price source response
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Deserialize, Serialize, Debug))]
+pub enum SourceKind {
+    Coincap,
+    Cryptocompare,
+    Coingecko,
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Deserialize, Serialize, Debug))]
+pub struct PriceSource {
+    pub symbol: Vec<u8>,
+    pub kind: SourceKind,
+    pub url: Vec<u8>,
+    // share of the source prices in the aggregated price
+    pub weight: u32,
+    pub enabled: bool,
+}
+
 //bridge
-#[derive(Encode, Decode, Clone, PartialEq)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct Limits<Balance> {
     pub max_tx_value: Balance,
@@ -89,7 +115,113 @@ pub struct Limits<Balance> {
     pub min_tx_value: Balance,
 }
 
+pub type ChainId = u32;
+// chain served by the bridge before the registry was introduced
+pub const ETHEREUM_CHAIN_ID: ChainId = 0;
+
+// counterparty chain served by the bridge
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Chain<AccountId> {
+    pub contract: H160,
+    pub tokens: Vec<TokenId>,
+    // bridge validators vote for the chain when empty
+    pub validators: Vec<AccountId>,
+    pub quorum: u64,
+}
+
+// validator's signature of the withdrawal, the bridge contract checks it against the signer
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Attestation<AccountId> {
+    pub validator: AccountId,
+    pub signer: H160,
+    pub signature: ecdsa::Signature,
+}
+
+// Ethereum block header imported by the light client
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct EthHeader {
+    pub parent_hash: H256,
+    pub number: u64,
+    pub timestamp: u64,
+    pub receipts_root: H256,
+    pub difficulty: U256,
+    // sum of difficulties of the header and its ancestors, the heaviest chain is canonical
+    pub total_difficulty: U256,
+}
+
+// validator's vote submitted within a batch
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum BridgeVote<AccountId, Hash, Balance> {
+    // chain, message id, sender, recipient, token, amount
+    Mint(ChainId, Hash, H160, AccountId, TokenId, Balance),
+    // message id and the attestation of the withdrawal
+    Approve(Hash, ecdsa::Signature),
+    Confirm(Hash),
+    Cancel(Hash),
+}
+
+// fee charged by the bridge: flat amount plus basis points of transferred amount
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct BridgeFee<Balance> {
+    pub flat: Balance,
+    pub bps: u32,
+}
+
+// volume transferred by an account in the current and the previous limit window,
+// windows are numbered by the block number divided by the window length
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct WindowVolume<BlockNumber, Balance> {
+    pub window: BlockNumber,
+    pub current: Balance,
+    pub previous: Balance,
+}
+
+// progress of the bridge storage migration, done in chunks over several blocks
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct StorageMigration {
+    // next transfer to migrate
+    pub next: ProposalId,
+    // the bridge is paused during the migration and then set back
+    pub operational: bool,
+}
+
+// compliance lists of the bridge accounts
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub enum AccountList {
+    // never held for the review
+    Allowed,
+    // can't send or receive bridge transfers
+    Denied,
+}
+
 // bridge types
+
+// Message ids are `blake2_256` hashes of SCALE encoded tuples, so the counterparty chain
+// and relayers can compute them without querying the bridge:
+//   transfer: (TRANSFER_MESSAGE, chain_id, sender, recipient: H160, token_id, amount, nonce)
+//       where nonce is the number of transfers made by the sender before
+//   pause:    (PAUSE_MESSAGE, nonce)
+//   resume:   (RESUME_MESSAGE, nonce)
+//       where nonce is the number of times the bridge was paused or resumed,
+//       by validators or by governance
+//   limits:   (LIMITS_MESSAGE, chain_id, token_id, limits, nonce)
+//       where nonce is the number of times the limits of the token on the chain
+//       were changed, by validators or by governance
+// Deposits and validator set changes use ids of the events on the counterparty chain.
+pub const TRANSFER_MESSAGE: &[u8] = b"transfer";
+pub const PAUSE_MESSAGE: &[u8] = b"pause";
+pub const RESUME_MESSAGE: &[u8] = b"resume";
+pub const LIMITS_MESSAGE: &[u8] = b"limits";
+pub type Nonce = u64;
+
 #[derive(Encode, Decode, Clone)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub struct BridgeTransfer<Hash> {
@@ -100,7 +232,7 @@ pub struct BridgeTransfer<Hash> {
     pub kind: Kind,
 }
 
-#[derive(Encode, Decode, Clone, PartialEq)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub enum Status {
     Revoked,
@@ -114,9 +246,11 @@ pub enum Status {
     Approved,
     Canceled,
     Confirmed,
+    // waits for the compliance review
+    Held,
 }
 
-#[derive(Encode, Decode, Clone, PartialEq)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Debug))]
 pub enum Kind {
     Transfer,
@@ -135,6 +269,19 @@ pub struct TransferMessage<AccountId, Hash, Balance> {
     pub amount: Balance,
     pub status: Status,
     pub action: Status,
+    pub chain: ChainId,
+}
+
+// transfer with its votes and status changes, returned by the runtime API
+#[derive(Encode, Decode, Clone)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct TransferInfo<AccountId, Hash, Balance, BlockNumber> {
+    pub message: TransferMessage<AccountId, Hash, Balance>,
+    pub open: bool,
+    pub votes: MemberId,
+    pub quorum: u64,
+    pub deadline: Option<BlockNumber>,
+    pub history: Vec<(Status, BlockNumber)>,
 }
 
 #[derive(Encode, Decode, Clone)]
@@ -143,6 +290,9 @@ pub struct LimitMessage<Hash, Balance> {
     pub id: Hash,
     pub limits: Limits<Balance>,
     pub status: Status,
+    // fields added later go last, so that stored messages can be extended
+    pub token: TokenId,
+    pub chain: ChainId,
 }
 
 #[derive(Encode, Decode, Clone)]
@@ -179,6 +329,7 @@ where
             amount: B::default(),
             status: Status::Withdraw,
             action: Status::Withdraw,
+            chain: ChainId::default(),
         }
     }
 }
@@ -193,6 +344,8 @@ where
             id: H::default(),
             limits: Limits::default(),
             status: Status::UpdateLimits,
+            token: TokenId::default(),
+            chain: ChainId::default(),
         }
     }
 }
@@ -125,6 +125,7 @@ pub fn config_endowed(
         pallet_vesting: Some(Default::default()),
         bridge: None,
         dao: None,
+        price_oracle: None,
         token: Some(TokenConfig { tokens: vec![Token {
 			id: 0,
 			decimals: 18,
@@ -58,7 +58,7 @@ pub fn signed_extra(nonce: Index, extra_fee: Balance) -> SignedExtra {
         frame_system::CheckEra::from(Era::mortal(256, 0)),
         frame_system::CheckNonce::from(nonce),
         frame_system::CheckWeight::new(),
-        pallet_transaction_payment::ChargeTransactionPayment::from(extra_fee),
+        akropolisos_runtime::ChargeTransactionPaymentInToken::from(extra_fee),
         Default::default(),
     )
 }